@@ -1,31 +1,730 @@
 use crate::{
-    create_file_picker, download_file,
-    schedule::{DoseKind, Vaccine, VaccineAppointment, VaccineRecord},
+    card, clear_location_hash,
+    coverage::{self, Coverage, Stats},
+    create_file_picker, csv, debug_log, download_file, download_files, ics,
+    import::{self, DateConflict, Leniency, UnknownVaccineAction},
+    location_hash,
+    passport::{self, Passport},
+    poll_saves,
+    schedule::{
+        self, age_in_month, month_gaps, visit_injections, AntibodyEvent, BoosterSchedule,
+        CoAdministration, Contraindication, DatePrecision, DoseKind, DoseLabels, DoseOffset,
+        ExportFilter, Exposure, Hemisphere, PlanningMode, Reaction, RecordSource, ScheduleOptions,
+        Severity, Unscheduled, Urgency, Vaccine, VaccineAppointment, VaccineRecord,
+    },
+    share, share_url, text, timeline, tz,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{Datelike, NaiveDate};
-use egui::TextWrapMode;
+use egui::{collapsing_header::CollapsingState, TextWrapMode};
 use egui_dnd::dnd;
 use itertools::Itertools;
 use jiff::{civil::date as jiffdate, tz::TimeZone, Zoned};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Mutex,
+};
 
-#[derive(Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
 #[serde(default)]
 pub struct VaccineConfig {
+    #[serde(deserialize_with = "schedule::deserialize_vaccine_name")]
+    name: String,
+    enabled: bool,
+    // Documented prior infection or immunity; only boosters are scheduled.
+    already_immune: bool,
+    // Hold off on this vaccine until then. Cleared once the date passes.
+    #[serde(deserialize_with = "tz::deserialize_option_zoned")]
+    snooze_until: Option<Zoned>,
+    // Plan a titer check this many years apart.
+    titer_check_years: Option<i16>,
+    // Boosters on this schedule instead of the built-in one, e.g. on a doctor's advice.
+    booster_override: Option<BoosterSchedule>,
+}
+
+// Identifies a planned appointment: (vaccine, kind, year, month).
+type AppointmentKey = (String, DoseKind, i16, i8);
+
+// Write appointment notes in date order rather than the hash map's, so that saving the same
+// profile twice gives the same file.
+fn serialize_appointment_notes<S: serde::Serializer>(
+    notes: &HashMap<AppointmentKey, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        notes
+            .iter()
+            .sorted_by_key(|((vaccine, kind, year, month), _)| {
+                (*year, *month, vaccine.as_str(), *kind)
+            }),
+    )
+}
+
+// Notes saved for a vaccine under an older name are kept with its current one, as records are.
+fn deserialize_appointment_notes<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<AppointmentKey, String>, D::Error> {
+    Ok(
+        HashMap::<AppointmentKey, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|((vaccine, kind, year, month), note)| {
+                let vaccine = schedule::current_vaccine_name(&vaccine).to_owned();
+                ((vaccine, kind, year, month), note)
+            })
+            .collect(),
+    )
+}
+
+// Save one profile so that the same profile always gives the same text, for keeping it under
+// version control: records in date order, and Unix line endings on every platform.
+pub(crate) fn serialize_profile_stable(profile: &Profile) -> Result<String> {
+    let mut profile = profile.clone();
+    profile.records.sort();
+    profile.skipped.sort();
+    let config = ron::ser::PrettyConfig::default()
+        .new_line("\n".to_owned())
+        .indentor("    ".to_owned());
+    Ok(ron::ser::to_string_pretty(&profile, config)?)
+}
+
+fn appointment_key(appt: &VaccineAppointment) -> AppointmentKey {
+    (
+        appt.vaccine().to_owned(),
+        appt.kind(),
+        appt.year(),
+        appt.month(),
+    )
+}
+
+// A profile's vaccine priority order and which of them are turned on, without anything else, to
+// share or to reuse in another profile.
+#[derive(Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+struct VaccineOrder {
+    vaccine_order: Vec<VaccineOrderEntry>,
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+struct VaccineOrderEntry {
+    #[serde(deserialize_with = "schedule::deserialize_vaccine_name")]
     name: String,
     enabled: bool,
 }
 
+impl VaccineOrder {
+    fn of(profile: &Profile) -> Self {
+        Self {
+            vaccine_order: profile
+                .vaccines
+                .iter()
+                .map(|v| VaccineOrderEntry {
+                    name: v.name.clone(),
+                    enabled: v.enabled,
+                })
+                .collect(),
+        }
+    }
+}
+
 // Configuration for the scheduling process.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Profile {
     vaccines: Vec<VaccineConfig>,
     end_plan_year: i16,
+    date_of_birth: Option<jiff::civil::Date>,
+    // Plan through the year this age is reached, when the date of birth is known. The end plan
+    // year is still what gets stored and used; this just keeps it in step.
+    plan_to_age: Option<i16>,
+    // IANA name of the zone new records are dated in, if not this machine's.
+    time_zone: Option<String>,
+    // Free-form medical context, e.g. "immunocompromised", carried along in exports.
+    notes: String,
+    min_appointment_gap_months: i16,
+    booster_horizon_years: Option<i16>,
+    preferred_day_of_month: Option<i8>,
+    // Put doses that can't share a visit in different weeks of the month, rather than months.
+    spread_within_month: bool,
+    coadministration: CoAdministration,
+    no_prior_flu_exposure: bool,
+    // Decides when the seasonal vaccines are planned.
+    hemisphere: Hemisphere,
+    // Whether doses with a range of intervals come as soon as allowed or as late as allowed,
+    // sharing visits.
+    planning_mode: PlanningMode,
+    // Answers to the vaccines' contraindication questions. Unanswered questions are asked once a
+    // vaccine that needs them is enabled.
+    contraindications: BTreeMap<Contraindication, bool>,
     records: Vec<VaccineRecord>,
+    // Keep self-reported records of a routine childhood schedule, dated from the date of birth,
+    // for those who likely had the shots but can't find the records.
+    assume_childhood_vaccines: bool,
+    // Immune globulin and similar products that live vaccines have to wait for.
+    antibody_events: Vec<AntibodyEvent>,
+    // Known exposures to a disease, which call for a booster.
+    exposures: Vec<Exposure>,
+    // Notes pinned to planned appointments. These live outside of the schedule so that they
+    // survive recomputation.
+    #[serde(
+        serialize_with = "serialize_appointment_notes",
+        deserialize_with = "deserialize_appointment_notes"
+    )]
+    appointment_notes: HashMap<AppointmentKey, String>,
+    // Planned appointments the user said they will miss.
+    skipped: Vec<VaccineAppointment>,
     schedule: Vec<VaccineAppointment>,
+    // For each enabled vaccine with nothing in the schedule, why not. Made along with it.
+    #[serde(skip)]
+    unscheduled: HashMap<String, Unscheduled>,
+    // `plan_inputs_hash` as of the last time the schedule was made.
+    #[serde(skip)]
+    plan_hash: Option<u64>,
+}
+
+impl Profile {
+    fn plan(&self, now: &Zoned) -> Result<Vec<VaccineAppointment>> {
+        self.plan_with(now, &self.schedule_options())
+    }
+
+    fn plan_with(&self, now: &Zoned, options: &ScheduleOptions) -> Result<Vec<VaccineAppointment>> {
+        Vaccine::schedule(
+            now,
+            self.vaccines
+                .iter()
+                .filter(|v| v.enabled)
+                .map(|v| v.name.clone()),
+            self.end_plan_year,
+            &self.records,
+            options,
+        )
+    }
+
+    fn schedule_options(&self) -> ScheduleOptions {
+        ScheduleOptions {
+            min_gap_mo: self.min_appointment_gap_months,
+            coadministration: self.coadministration.clone(),
+            seasonal_naive: self.no_prior_flu_exposure,
+            hemisphere: self.hemisphere,
+            planning_mode: self.planning_mode,
+            already_immune: self
+                .vaccines
+                .iter()
+                .filter(|v| v.already_immune)
+                .map(|v| v.name.clone())
+                .collect(),
+            skipped: self.skipped.clone(),
+            date_of_birth: self.date_of_birth,
+            booster_horizon_years: self.booster_horizon_years,
+            preferred_day_of_month: self.preferred_day_of_month,
+            spread_within_month: self.spread_within_month,
+            antibody_events: self.antibody_events.clone(),
+            exposures: self.exposures.clone(),
+            snoozed: self
+                .vaccines
+                .iter()
+                .filter_map(|v| Some((v.name.clone(), v.snooze_until.clone()?)))
+                .collect(),
+            contraindicated: self.contraindicated(),
+            titer_checks: self
+                .vaccines
+                .iter()
+                .filter_map(|v| Some((v.name.clone(), v.titer_check_years?)))
+                .collect(),
+            booster_overrides: self
+                .vaccines
+                .iter()
+                .filter_map(|v| Some((v.name.clone(), v.booster_override.clone()?)))
+                .collect(),
+        }
+    }
+
+    // Whether the record's vaccine is enabled, so that the record has a say in the plan. Records
+    // of other vaccines are kept, but the scheduler never looks at them.
+    fn record_in_plan(&self, record: &VaccineRecord) -> bool {
+        self.vaccines
+            .iter()
+            .any(|v| v.enabled && v.name == record.vaccine())
+    }
+
+    // For each record, the shortest interval in months allowed before it, if the dose was given
+    // sooner than that after the previous dose of its series.
+    fn doses_too_close(&self, now: &Zoned) -> Result<Vec<Option<i16>>> {
+        let mut too_close = vec![None; self.records.len()];
+        for name in self.records.iter().map(|r| r.vaccine()).unique() {
+            let Some(vaccine) = Vaccine::get_vaccines().get(name) else {
+                continue;
+            };
+            let records = self.records.iter().filter(|r| r.vaccine() == name);
+            for (record, interval) in vaccine.doses_too_close(now, records)? {
+                if let Some(i) = self.records.iter().position(|r| std::ptr::eq(r, record)) {
+                    too_close[i] = Some(interval);
+                }
+            }
+        }
+        Ok(too_close)
+    }
+
+    // The questions answered "yes".
+    fn contraindicated(&self) -> HashSet<Contraindication> {
+        self.contraindications
+            .iter()
+            .filter(|(_, yes)| **yes)
+            .map(|(c, _)| *c)
+            .collect()
+    }
+
+    // Enabled vaccines that aren't planned because of an answer, by the question that holds
+    // them off.
+    fn held_off(&self) -> Vec<(Contraindication, Vec<String>)> {
+        let contraindicated = self.contraindicated();
+        let vaccines = Vaccine::get_vaccines();
+        self.vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .filter_map(|v| {
+                let held_off_by = vaccines
+                    .get(v.name.as_str())?
+                    .held_off_by(&contraindicated)?;
+                Some((held_off_by, v.name.clone()))
+            })
+            .into_group_map()
+            .into_iter()
+            .sorted()
+            .collect()
+    }
+
+    // Questions for the enabled vaccines that haven't been answered yet.
+    fn unanswered_questions(&self) -> Vec<Contraindication> {
+        self.vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .flat_map(|v| Vaccine::get_vaccines()[v.name.as_str()].contraindications())
+            .filter(|c| !self.contraindications.contains_key(c))
+            .copied()
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    // For each enabled vaccine with nothing planned, why not.
+    fn unscheduled_reasons(&self, now: &Zoned) -> Result<HashMap<String, Unscheduled>> {
+        let options = self.schedule_options();
+        let mut out = HashMap::new();
+        for v in self.vaccines.iter().filter(|v| v.enabled) {
+            if self.schedule.iter().any(|appt| appt.vaccine() == v.name) {
+                continue;
+            }
+            let vaccine = &Vaccine::get_vaccines()[v.name.as_str()];
+            if let Some(reason) =
+                vaccine.why_unscheduled(now, self.end_plan_year, &self.records, &options)?
+            {
+                out.insert(v.name.clone(), reason);
+            }
+        }
+        Ok(out)
+    }
+
+    // Enable exactly the recommended vaccines, leaving their order alone. Returns the previous
+    // selection for `restore_enabled`.
+    fn enable_recommended(&mut self) -> Vec<(String, bool)> {
+        let vaccines = Vaccine::get_vaccines();
+        self.vaccines
+            .iter_mut()
+            .map(|v| {
+                let previous = (v.name.clone(), v.enabled);
+                v.enabled = vaccines[v.name.as_str()].recommended();
+                previous
+            })
+            .collect()
+    }
+
+    // Set up a new profile from the first-launch questions. Of the recommended vaccines, only
+    // those the patient will be old enough for within the year are turned on; the rest can be
+    // enabled as they come due.
+    fn apply_onboarding(&mut self, answers: &Onboarding, today: jiff::civil::Date) -> Result<()> {
+        self.date_of_birth = answers.date_of_birth;
+        self.assume_childhood_vaccines = answers.childhood_vaccines;
+        self.hemisphere = answers.hemisphere;
+        self.contraindications
+            .insert(Contraindication::Pregnant, answers.pregnant);
+        let cutoff = today.saturating_add(jiff::Span::new().months(ONBOARDING_AGE_WINDOW_MO));
+        let vaccines = Vaccine::get_vaccines();
+        for v in self.vaccines.iter_mut() {
+            let Some(vaccine) = vaccines.get(v.name.as_str()) else {
+                continue;
+            };
+            let old_enough = answers.date_of_birth.is_none_or(|dob| {
+                dob.saturating_add(jiff::Span::new().months(vaccine.minimum_age_mo())) <= cutoff
+            });
+            v.enabled = vaccine.recommended() && old_enough;
+        }
+        self.sync_childhood_baseline()
+    }
+
+    // Put the assumed childhood records in line with the setting and the date of birth. A
+    // vaccine with any other record on file is left alone.
+    fn sync_childhood_baseline(&mut self) -> Result<()> {
+        self.records.retain(|r| r.source() != RecordSource::Assumed);
+        if let (true, Some(dob)) = (self.assume_childhood_vaccines, self.date_of_birth) {
+            let tz = self.time_zone();
+            for (vaccine, ages_mo) in CHILDHOOD_BASELINE {
+                if self.records.iter().any(|r| r.vaccine() == *vaccine) {
+                    continue;
+                }
+                for (i, age_mo) in ages_mo.iter().enumerate() {
+                    let date = dob
+                        .checked_add(jiff::Span::new().months(*age_mo))?
+                        .to_zoned(tz.clone())?;
+                    self.records.push(
+                        VaccineRecord::new(
+                            vaccine,
+                            date,
+                            DoseKind::Dose(i.try_into()?),
+                            ASSUMED_RECORD_NOTE,
+                        )
+                        .with_source(RecordSource::Assumed),
+                    );
+                }
+            }
+        }
+        self.records.sort();
+        Ok(())
+    }
+
+    fn restore_enabled(&mut self, previous: &[(String, bool)]) {
+        for (name, enabled) in previous {
+            if let Some(v) = self.vaccines.iter_mut().find(|v| v.name == *name) {
+                v.enabled = *enabled;
+            }
+        }
+    }
+
+    fn time_zone(&self) -> TimeZone {
+        match &self.time_zone {
+            Some(name) => tz::named(name),
+            None => tz::system(),
+        }
+    }
+
+    // Move the end plan year to match the target age, if there is one.
+    fn sync_end_plan_year(&mut self) {
+        if let (Some(dob), Some(age)) = (self.date_of_birth, self.plan_to_age) {
+            self.end_plan_year = dob.year() + age;
+        }
+    }
+
+    // When a combination vaccine and any of its components are both enabled, keep one of them.
+    // Whichever was in `just_enabled` wins, and overlaps that don't involve it are left as they
+    // are. Without `just_enabled`, every overlap is settled: the combination is kept if
+    // `prefer_combined`, otherwise the components. Returns each vaccine that was turned off with
+    // what replaced it.
+    fn resolve_combinations(
+        &mut self,
+        prefer_combined: bool,
+        just_enabled: Option<&HashSet<String>>,
+    ) -> Vec<(String, String)> {
+        let vaccines = Vaccine::get_vaccines();
+        let enabled = self
+            .vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .filter_map(|v| vaccines.get(v.name.as_str()))
+            .collect_vec();
+        let mut changes = Vec::new();
+        for combination in &enabled {
+            let components = enabled
+                .iter()
+                .filter(|v| combination.covers(v))
+                .map(|v| v.name())
+                .collect_vec();
+            if components.is_empty() {
+                continue;
+            }
+            let keep_combined = match just_enabled {
+                None => prefer_combined,
+                Some(just) if just.contains(combination.name()) => true,
+                Some(just) if components.iter().any(|c| just.contains(*c)) => false,
+                Some(_) => continue,
+            };
+            if keep_combined {
+                for component in components {
+                    changes.push((component.to_owned(), combination.name().to_owned()));
+                }
+            } else {
+                // Fill in any components that were off, so that nothing loses coverage.
+                let all_components = self
+                    .vaccines
+                    .iter()
+                    .filter(|v| {
+                        vaccines
+                            .get(v.name.as_str())
+                            .is_some_and(|c| combination.covers(c))
+                    })
+                    .map(|v| v.name.as_str())
+                    .collect_vec();
+                changes.push((combination.name().to_owned(), all_components.join(" and ")));
+            }
+        }
+        for (disabled, _) in &changes {
+            let disabled_vaccine = vaccines.get(disabled.as_str());
+            for v in self.vaccines.iter_mut() {
+                if v.name == *disabled {
+                    v.enabled = false;
+                } else if let (Some(combination), Some(component)) =
+                    (disabled_vaccine, vaccines.get(v.name.as_str()))
+                {
+                    // A combination that was turned off hands over to all of its components.
+                    if combination.covers(component) {
+                        v.enabled = true;
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    fn enabled_names(&self) -> HashSet<String> {
+        self.vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .map(|v| v.name.clone())
+            .collect()
+    }
+
+    // A hash of everything the plan depends on, along with the day it was made for, since
+    // records count whole months from the day they were given. The schedule is part of it, so a
+    // plan that was loaded or edited is also made again. New settings that change the plan need
+    // to be added here.
+    fn plan_inputs_hash(&self, now: &Zoned) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            &self.vaccines,
+            self.end_plan_year,
+            self.date_of_birth,
+            self.plan_to_age,
+            self.min_appointment_gap_months,
+            self.booster_horizon_years,
+            self.preferred_day_of_month,
+            self.spread_within_month,
+            &self.coadministration,
+            self.no_prior_flu_exposure,
+            self.hemisphere,
+            self.planning_mode,
+        )
+            .hash(&mut hasher);
+        (
+            &self.contraindications,
+            &self.records,
+            self.assume_childhood_vaccines,
+            &self.antibody_events,
+            &self.exposures,
+            &self.skipped,
+            &self.schedule,
+            now.date(),
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Bring the stored plan up to date.
+    fn replan(&mut self, now: &Zoned) -> Result<()> {
+        self.sync_end_plan_year();
+        self.clear_expired_snoozes(now);
+        self.schedule = self.plan(now)?;
+        self.unscheduled = self.unscheduled_reasons(now)?;
+        Ok(())
+    }
+
+    // Drop skips that no longer line up with a planned dose, e.g. because the dose has since been
+    // recorded or the vaccine was turned off. They would do nothing but sit in the list.
+    fn prune_skips(&mut self, now: &Zoned) -> Result<()> {
+        let unskipped = self.plan_with(
+            now,
+            &ScheduleOptions {
+                skipped: vec![],
+                ..self.schedule_options()
+            },
+        )?;
+        self.skipped.retain(|skip| {
+            unskipped.iter().any(|appt| {
+                appt.vaccine() == skip.vaccine()
+                    && appt.kind() == skip.kind()
+                    && appt.month_offset(now) <= skip.month_offset(now)
+            })
+        });
+        Ok(())
+    }
+
+    fn clear_expired_snoozes(&mut self, now: &Zoned) {
+        for vaccine in self.vaccines.iter_mut() {
+            if vaccine
+                .snooze_until
+                .as_ref()
+                .is_some_and(|until| until <= now)
+            {
+                vaccine.snooze_until = None;
+            }
+        }
+    }
+
+    // Bring the vaccine list in line with `database` after an update: vaccines that are new to it
+    // are appended, enabled if recommended and `enable_new` is set, and ones that are gone are
+    // dropped. Old names were already mapped to current ones when the profile was read.
+    fn reconcile_vaccines(
+        &mut self,
+        database: &HashMap<&str, Vaccine>,
+        enable_new: bool,
+    ) -> VaccineListChanges {
+        let mut changes = VaccineListChanges::default();
+        let mut seen = HashSet::new();
+        self.vaccines.retain_mut(|v| {
+            v.name = schedule::current_vaccine_name(&v.name).to_owned();
+            if !database.contains_key(v.name.as_str()) {
+                changes.removed.push(v.name.clone());
+                return false;
+            }
+            seen.insert(v.name.clone())
+        });
+        for vaccine in database.values().sorted_by_key(|v| v.name()) {
+            if !seen.contains(vaccine.name()) {
+                let enabled = enable_new && vaccine.recommended();
+                changes.added.push((vaccine.name().to_owned(), enabled));
+                self.vaccines.push(VaccineConfig {
+                    name: vaccine.name().to_owned(),
+                    enabled,
+                    ..Default::default()
+                });
+            }
+        }
+        changes
+    }
+
+    // Reorder the vaccine list and turn vaccines on and off to match `order`, e.g. from another
+    // profile. Vaccines it doesn't mention keep their place relative to each other after the
+    // ones it does; names that aren't in this profile are skipped. Returns how many were
+    // skipped.
+    fn apply_vaccine_order(&mut self, order: &VaccineOrder) -> usize {
+        let mut rest = std::mem::take(&mut self.vaccines);
+        let mut skipped = 0;
+        for entry in &order.vaccine_order {
+            match rest.iter().position(|v| v.name == entry.name) {
+                Some(i) => {
+                    let mut config = rest.remove(i);
+                    config.enabled = entry.enabled;
+                    self.vaccines.push(config);
+                }
+                None => skipped += 1,
+            }
+        }
+        self.vaccines.extend(rest);
+        skipped
+    }
+
+    // Everything but the records and the plan built from them.
+    fn settings_eq(&self, other: &Self) -> bool {
+        self.vaccines == other.vaccines
+            && self.end_plan_year == other.end_plan_year
+            && self.date_of_birth == other.date_of_birth
+            && self.plan_to_age == other.plan_to_age
+            && self.time_zone == other.time_zone
+            && self.notes == other.notes
+            && self.min_appointment_gap_months == other.min_appointment_gap_months
+            && self.booster_horizon_years == other.booster_horizon_years
+            && self.preferred_day_of_month == other.preferred_day_of_month
+            && self.spread_within_month == other.spread_within_month
+            && self.coadministration == other.coadministration
+            && self.no_prior_flu_exposure == other.no_prior_flu_exposure
+            && self.hemisphere == other.hemisphere
+            && self.planning_mode == other.planning_mode
+            && self.contraindications == other.contraindications
+            && self.assume_childhood_vaccines == other.assume_childhood_vaccines
+            && self.antibody_events == other.antibody_events
+            && self.exposures == other.exposures
+    }
+
+    // Add the records we don't already have. Returns how many were added.
+    fn merge_records(&mut self, records: Vec<VaccineRecord>) -> usize {
+        let before = self.records.len();
+        for record in records {
+            if !self.records.iter().any(|r| r.same_shot(&record)) {
+                self.records.push(record);
+            }
+        }
+        self.records.sort();
+        self.records.len() - before
+    }
+
+    // Notes that no longer match a planned appointment, usually because the appointment moved
+    // to a different month. These are shown separately rather than dropped.
+    fn orphaned_notes(&self) -> Vec<(AppointmentKey, String)> {
+        self.appointment_notes
+            .iter()
+            .filter(|(key, _)| !self.schedule.iter().any(|a| appointment_key(a) == **key))
+            .map(|(key, note)| (key.clone(), note.clone()))
+            .sorted()
+            .collect()
+    }
+
+    // Where an orphaned note's appointment went, if the same dose is still planned.
+    fn moved_appointment(&self, key: &AppointmentKey) -> Option<&VaccineAppointment> {
+        self.schedule
+            .iter()
+            .find(|a| a.vaccine() == key.0 && a.kind() == key.1)
+    }
+
+    fn reattach_note(&mut self, key: &AppointmentKey) {
+        let Some(target) = self.moved_appointment(key).map(appointment_key) else {
+            return;
+        };
+        if let Some(note) = self.appointment_notes.remove(key) {
+            let existing = self.appointment_notes.entry(target).or_default();
+            if !existing.is_empty() {
+                existing.push_str("; ");
+            }
+            existing.push_str(&note);
+        }
+    }
+}
+
+// What `Profile::reconcile_vaccines` changed: the vaccines added, with whether each was
+// enabled, and the ones removed.
+#[derive(Debug, Default, PartialEq)]
+struct VaccineListChanges {
+    added: Vec<(String, bool)>,
+    removed: Vec<String>,
+}
+
+impl VaccineListChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl fmt::Display for VaccineListChanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            let added = self
+                .added
+                .iter()
+                .map(|(name, enabled)| {
+                    if *enabled {
+                        format!("{name} (enabled)")
+                    } else {
+                        name.clone()
+                    }
+                })
+                .join(", ");
+            parts.push(format!("added {added}"));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", self.removed.join(", ")));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
 }
 
 impl Default for Profile {
@@ -37,31 +736,453 @@ impl Default for Profile {
                 .map(|v| VaccineConfig {
                     name: v.name().to_owned(),
                     enabled: v.recommended(),
+                    already_immune: false,
+                    snooze_until: None,
+                    titer_check_years: None,
+                    booster_override: None,
                 })
                 .collect(),
-            end_plan_year: Zoned::now().year() + 55,
+            end_plan_year: tz::now().year() + 55,
+            date_of_birth: None,
+            plan_to_age: None,
+            time_zone: None,
+            notes: String::new(),
+            min_appointment_gap_months: 0,
+            booster_horizon_years: None,
+            preferred_day_of_month: None,
+            spread_within_month: false,
+            coadministration: CoAdministration::default(),
+            no_prior_flu_exposure: false,
+            hemisphere: Hemisphere::default(),
+            planning_mode: PlanningMode::default(),
+            contraindications: BTreeMap::new(),
             records: vec![],
+            assume_childhood_vaccines: false,
+            antibody_events: vec![],
+            exposures: vec![],
+            appointment_notes: HashMap::new(),
+            skipped: vec![],
             schedule: vec![],
+            unscheduled: HashMap::new(),
+            plan_hash: None,
+        }
+    }
+}
+
+// How long the notice that the plan was made again stays up.
+const REPLAN_FLASH_SECONDS: f64 = 0.5;
+
+// How far ahead the first-launch setup looks when deciding whether someone is old enough for a
+// vaccine.
+const ONBOARDING_AGE_WINDOW_MO: i64 = 12;
+
+// Doses of a routine childhood schedule, by age in months, assumed for those who opt in. The
+// spacing keeps to the intervals in the vaccine database.
+const CHILDHOOD_BASELINE: &[(&str, &[i16])] = &[
+    ("Hepatitis B", &[0]),
+    ("IPV", &[2, 6, 12, 48]),
+    ("MMR", &[12, 72]),
+];
+
+const CHILDHOOD_BASELINE_HELP: &str = "Adds self-reported records of Hepatitis B, polio (IPV), and MMR dated through early childhood, so they aren't planned again. Turn this off to take them back out.";
+
+// The note on records added for `Profile::assume_childhood_vaccines`, saying where they came from.
+const ASSUMED_RECORD_NOTE: &str = "Assumed from a standard childhood schedule";
+
+// The first-launch questions, one step at a time.
+#[derive(Debug, Default)]
+struct Onboarding {
+    step: usize,
+    date_of_birth: Option<jiff::civil::Date>,
+    hemisphere: Hemisphere,
+    pregnant: bool,
+    has_records: bool,
+    childhood_vaccines: bool,
+}
+
+impl Onboarding {
+    const STEPS: usize = 4;
+}
+
+// Records typed in a line at a time, for copying a paper card quickly.
+#[derive(Debug, Default)]
+struct BulkAdd {
+    text: String,
+    // From the last attempt to add them, by line.
+    errors: Vec<String>,
+    // Put the cursor in the text box on the next frame.
+    focus: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum EmptySchedule {
+    NoneEnabled,
+    // The plan ends this year, so whatever comes next falls outside of it.
+    PlanEndsThisYear,
+    UpToDate,
+}
+
+// The shapes of RON file that the various exports produce.
+#[derive(Debug)]
+enum RonImport {
+    App(Box<VaccineHelperApp>),
+    Profile(Box<Profile>),
+    Records(Vec<VaccineRecord>),
+    Appointments(Vec<VaccineAppointment>),
+    VaccineOrder(VaccineOrder),
+}
+
+impl RonImport {
+    fn parse(content: &str) -> Result<Self> {
+        Ok(Self::parse_lenient(content)?.0)
+    }
+
+    // With `serde(default)` any struct deserializes as an app or a profile, so look at which
+    // fields are present to tell them apart. Files from other versions of the app are read as
+    // far as they can be; see `import::parse_lenient`.
+    fn parse_lenient(content: &str) -> Result<(Self, Leniency)> {
+        match ron::de::from_str::<ron::Value>(content)? {
+            ron::Value::Seq(_) => match import::parse_lenient(content) {
+                Ok((records, leniency)) => Ok((Self::Records(records), leniency)),
+                Err(_) => {
+                    let (appointments, leniency) = import::parse_lenient(content)?;
+                    Ok((Self::Appointments(appointments), leniency))
+                }
+            },
+            ron::Value::Map(map) => {
+                let has = |field: &str| {
+                    map.keys()
+                        .any(|k| *k == ron::Value::String(field.to_owned()))
+                };
+                if has("vaccine_order") {
+                    let (order, leniency) = import::parse_lenient(content)?;
+                    Ok((Self::VaccineOrder(order), leniency))
+                } else if has("profiles") {
+                    let (app, leniency) = VaccineHelperApp::from_ron(content)?;
+                    Ok((Self::App(Box::new(app)), leniency))
+                } else if has("vaccines") || has("records") {
+                    let (mut profile, leniency) = import::parse_lenient::<Profile>(content)?;
+                    // As for a saved app, drop vaccines this version doesn't have and add the
+                    // ones the file is missing. A bare profile doesn't say whether to turn new
+                    // vaccines on, so they are left off.
+                    profile.reconcile_vaccines(Vaccine::get_vaccines(), false);
+                    Ok((Self::Profile(Box::new(profile)), leniency))
+                } else {
+                    bail!("not a vaccine helper file")
+                }
+            }
+            _ => bail!("not a vaccine helper file"),
+        }
+    }
+}
+
+// Plan a saved profile, or the active profile of a saved app, without the UI. Returns the plan
+// and the profile's notes.
+pub(crate) fn plan_saved(content: &str, now: &Zoned) -> Result<(Vec<VaccineAppointment>, String)> {
+    let mut profile = match RonImport::parse(content)? {
+        RonImport::App(mut app) => app.profiles.remove(&app.active_profile).unwrap_or_default(),
+        RonImport::Profile(profile) => *profile,
+        RonImport::Records(_) | RonImport::Appointments(_) | RonImport::VaccineOrder(_) => {
+            bail!("not a profile or app file")
+        }
+    };
+    profile.replan(now)?;
+    Ok((profile.schedule, profile.notes))
+}
+
+// What importing a profile over the active one would change, shown before anything is applied.
+#[derive(Debug, Default, Eq, PartialEq)]
+struct ProfileDiff {
+    incoming_records: usize,
+    current_records: usize,
+    // Incoming records the current profile doesn't have.
+    added: usize,
+    // Current records the incoming profile doesn't have.
+    removed: usize,
+    settings_differ: bool,
+}
+
+impl ProfileDiff {
+    fn new(current: &Profile, incoming: &Profile) -> Self {
+        let missing_from = |a: &Profile, b: &Profile| {
+            a.records
+                .iter()
+                .filter(|r| !b.records.iter().any(|other| other.same_shot(r)))
+                .count()
+        };
+        Self {
+            incoming_records: incoming.records.len(),
+            current_records: current.records.len(),
+            added: missing_from(incoming, current),
+            removed: missing_from(current, incoming),
+            settings_differ: !current.settings_eq(incoming),
+        }
+    }
+
+    fn summary(&self) -> String {
+        let mut out = format!(
+            "This import has {} records; your current profile has {}. {} of the imported records are new",
+            self.incoming_records, self.current_records, self.added
+        );
+        if self.removed > 0 {
+            out.push_str(&format!(
+                ", and {} of yours are not in it and would be lost by replacing",
+                self.removed
+            ));
+        }
+        out.push('.');
+        if self.settings_differ {
+            out.push_str(" Its schedule settings differ from yours.");
+        }
+        out
+    }
+}
+
+// An import that would change existing data, held until the user confirms it.
+#[derive(Debug)]
+enum StagedImport {
+    Profile(Box<Profile>, ProfileDiff),
+    Restore(Box<VaccineHelperApp>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ImportChoice {
+    // Overwrite the active profile, or everything for a full save.
+    Replace,
+    // Keep the active profile's settings and add the records it is missing.
+    Merge,
+    AddProfile,
+}
+
+// An import held back until the user decides what to do with records for unknown vaccines.
+#[derive(Debug)]
+enum PendingImport {
+    Records(Vec<VaccineRecord>),
+    Profile(Box<Profile>),
+    Restore(Box<VaccineHelperApp>),
+}
+
+#[derive(Debug)]
+struct ImportReview {
+    pending: PendingImport,
+    unknown: Vec<(String, usize)>,
+    actions: HashMap<String, UnknownVaccineAction>,
+}
+
+impl PendingImport {
+    fn unknown_vaccines(&self) -> Vec<(String, usize)> {
+        match self {
+            Self::Records(records) => import::unknown_vaccines(records),
+            Self::Profile(profile) => import::unknown_vaccines(&profile.records),
+            Self::Restore(app) => {
+                import::unknown_vaccines(app.profiles.values().flat_map(|p| &p.records))
+            }
+        }
+    }
+}
+
+impl ImportReview {
+    // Start with our best guess for each unknown vaccine.
+    fn new(pending: PendingImport, unknown: Vec<(String, usize)>) -> Self {
+        let actions = unknown
+            .iter()
+            .map(|(name, _)| {
+                let action = import::suggest_vaccine(name)
+                    .map(UnknownVaccineAction::Rename)
+                    .unwrap_or(UnknownVaccineAction::Keep);
+                (name.clone(), action)
+            })
+            .collect();
+        Self {
+            pending,
+            unknown,
+            actions,
         }
     }
 }
 
+// How long each month lasts while the aging simulation plays.
+const SIMULATION_STEP_SECS: f64 = 0.4;
+
+// A scrubber over the coming years, to show how the plan plays out as the patient ages. It is a
+// preview: nothing is recorded, and doses are assumed to be skipped along the way.
+#[derive(Debug)]
+struct Simulation {
+    start: Zoned,
+    months: i16,
+    playing: bool,
+    // When the last month went by while playing, in egui's clock.
+    last_step: f64,
+    // The preview date, if any, to go back to when the simulation is closed.
+    previous: Option<Zoned>,
+}
+
+// Content picked by the user in an import dialog, applied on the next frame.
+static RESTORE_CONTENT: Mutex<Option<String>> = Mutex::new(None);
+static IMPORT_CSV_CONTENT: Mutex<Option<String>> = Mutex::new(None);
+
+// Browsers reserve these for their own open/save, so only bind them natively.
+const IMPORT_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::O);
+const EXPORT_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::S);
+const HAS_SHORTCUTS: bool = cfg!(not(target_arch = "wasm32"));
+
+// Imported records this close to one already on file, for the same vaccine and dose, are taken to
+// be the same shot.
+const IMPORT_DATE_TOLERANCE_DAYS: i32 = 7;
+
+// With "Hide completed", vaccines needing nothing within this many months are left off the list.
+const COMPLETED_HORIZON_MO: i16 = 24;
+
+// Bump this and add a step to `VaccineHelperApp::migrate` when a persisted field changes meaning.
+const SCHEMA_VERSION: u32 = 2;
+
+// Data saved before versioning was added has no version field.
+fn unversioned_schema() -> u32 {
+    1
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct VaccineHelperApp {
+    #[serde(default = "unversioned_schema")]
+    schema_version: u32,
     active_profile: String,
     profiles: HashMap<String, Profile>,
 
-    // Window state
+    // Window state. Not saved, so that windows always start closed.
+    #[serde(skip)]
     show_profiles: bool,
+    #[serde(skip)]
     show_preferences: bool,
+    #[serde(skip)]
     show_about: bool,
+    #[serde(skip)]
+    show_debug_log: bool,
+    // In the debug log window: the raw month offsets worked out for this vaccine.
+    #[serde(skip)]
+    offsets_vaccine: Option<String>,
+    #[serde(skip)]
+    show_passport: bool,
+    #[serde(skip)]
+    show_household: bool,
+    #[serde(skip)]
+    show_timeline: bool,
+    #[serde(skip)]
+    show_coverage: bool,
+
+    // Preferences
+    dose_labels: DoseLabels,
+    overdue_grace_months: i16,
+    // How many months ahead appointments are flagged as due soon, to leave time to book them.
+    reminder_lead_months: i16,
+    hide_completed: bool,
+    show_ages: bool,
+    // Settle on combination vaccines, rather than their components, when both are enabled.
+    prefer_combined: bool,
+    // Show the months between planned doses in each vaccine's details.
+    show_dose_gaps: bool,
+    // Timeline zoom, in points per month.
+    timeline_month_width: f32,
+    // Which doses the calendar, text, and CSV exports include.
+    export_filter: ExportFilter,
+    // Turn on recommended vaccines that an update adds to the built-in list.
+    enable_new_vaccines: bool,
+    // Show only what needs doing soon, in place of the records, settings, and full schedule.
+    focus_mode: bool,
 
     // Add record widget
     add_record: Option<VaccineRecord>,
+    #[serde(skip)]
+    bulk_add: Option<BulkAdd>,
+
+    // The record whose date is being edited in place. Records are re-sorted once the edit is
+    // done, so that the row doesn't jump around mid-edit.
+    #[serde(skip)]
+    editing_record: Option<usize>,
 
     // Add profile widget
     add_profile_name: String,
+    add_profile_date_of_birth: Option<jiff::civil::Date>,
+    add_profile_time_zone: String,
+
+    // Result of the last import, shown under the records.
+    #[serde(skip)]
+    import_message: Option<String>,
+
+    // When set, used in place of the wall clock for scheduling and rendering.
+    #[serde(skip)]
+    now_override: Option<Zoned>,
+    // The aging simulation, which drives `now_override` while it is open.
+    #[serde(skip)]
+    simulation: Option<Simulation>,
+
+    // Shown when a loaded profile had to have its end plan year corrected.
+    #[serde(skip)]
+    end_plan_notice: Option<String>,
+
+    // Shown when loading brought the profiles' vaccine lists up to date with the built-in list.
+    #[serde(skip)]
+    vaccine_list_notice: Option<String>,
+
+    // Add co-administration conflict widget
+    #[serde(skip)]
+    add_separate_pair: (usize, usize),
+    #[serde(skip)]
+    add_antibody_event: AntibodyEvent,
+    #[serde(skip)]
+    add_exposure: Exposure,
+
+    // The appointment whose note is being edited.
+    #[serde(skip)]
+    editing_note: Option<AppointmentKey>,
+
+    // Records opened from a share link, shown read-only in place of the normal UI.
+    #[serde(skip)]
+    shared_records: Option<Vec<VaccineRecord>>,
+
+    // An import waiting on the unrecognized vaccines prompt.
+    #[serde(skip)]
+    import_review: Option<ImportReview>,
+
+    // Imported records that may be ones already on file, with whether to take the imported date.
+    #[serde(skip)]
+    import_conflicts: Vec<(DateConflict, bool)>,
+
+    // An import waiting for confirmation before it changes existing data.
+    #[serde(skip)]
+    staged_import: Option<StagedImport>,
+
+    // The vaccine selection from before "Use Recommended", by profile, so it can be undone.
+    #[serde(skip)]
+    undo_enabled: Option<(String, Vec<(String, bool)>)>,
+
+    // What was last turned off because a combination vaccine overlapped it.
+    #[serde(skip)]
+    combination_notice: Option<String>,
+    // The profile, preference, and enabled vaccines as of the last look at combinations, so
+    // that they are only settled when one of those changes.
+    #[serde(skip)]
+    combinations_checked: Option<(String, bool, HashSet<String>)>,
+
+    // Records being copied from another profile into the active one: the source profile's name
+    // and, for each of its records, whether it is selected.
+    #[serde(skip)]
+    copy_records: Option<(String, Vec<bool>)>,
+
+    // When the plan was last made again, in UI time, to flash a notice.
+    #[serde(skip)]
+    replanned_at: Option<f64>,
+
+    // Set once the first-launch questions have been answered or skipped.
+    onboarded: bool,
+    #[serde(skip)]
+    onboarding: Option<Onboarding>,
+    // Health questions already unanswered when each profile was first shown, plus those put off
+    // with "Ask Later". Only questions raised since, e.g. by enabling a vaccine, pop up.
+    #[serde(skip)]
+    questions_seen: HashMap<String, HashSet<Contraindication>>,
 }
 
 impl Default for VaccineHelperApp {
@@ -69,13 +1190,63 @@ impl Default for VaccineHelperApp {
         // let mut profiles = ;
         // profiles.insert("Default".to_owned(), Profile::default());
         Self {
+            schema_version: SCHEMA_VERSION,
             active_profile: "Default".to_owned(),
             profiles: HashMap::from_iter([("Default".to_owned(), Profile::default())]),
             show_profiles: false,
             show_preferences: false,
             show_about: false,
+            show_debug_log: false,
+            offsets_vaccine: None,
+            show_passport: false,
+            show_household: false,
+            show_timeline: false,
+            show_coverage: false,
+            dose_labels: DoseLabels::default(),
+            overdue_grace_months: 1,
+            reminder_lead_months: 1,
+            hide_completed: false,
+            show_ages: true,
+            prefer_combined: false,
+            show_dose_gaps: true,
+            timeline_month_width: 8.0,
+            export_filter: ExportFilter::default(),
+            enable_new_vaccines: true,
+            focus_mode: false,
             add_record: None,
+            bulk_add: None,
+            editing_record: None,
             add_profile_name: "".to_owned(),
+            add_profile_date_of_birth: None,
+            add_profile_time_zone: "".to_owned(),
+            import_message: None,
+            now_override: None,
+            simulation: None,
+            end_plan_notice: None,
+            vaccine_list_notice: None,
+            add_separate_pair: (0, 0),
+            add_antibody_event: AntibodyEvent {
+                product: String::new(),
+                date: tz::now().date(),
+                deferral_mo: 3,
+            },
+            add_exposure: Exposure {
+                disease: "Tetanus".to_owned(),
+                date: tz::now().date(),
+            },
+            editing_note: None,
+            shared_records: None,
+            import_review: None,
+            import_conflicts: Vec::new(),
+            staged_import: None,
+            undo_enabled: None,
+            combination_notice: None,
+            combinations_checked: None,
+            copy_records: None,
+            replanned_at: None,
+            onboarded: false,
+            onboarding: None,
+            questions_seen: HashMap::new(),
         }
     }
 }
@@ -90,11 +1261,59 @@ impl VaccineHelperApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-        }
+        let mut app = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Self>(storage, eframe::APP_KEY))
+            .map(Self::migrate)
+            .unwrap_or_default();
 
-        Default::default()
+        // A share link opens a read-only view of the records it carries. Anything else in the
+        // fragment is ignored and we start up normally.
+        app.shared_records = location_hash().and_then(|hash| share::decode_records(&hash).ok());
+        app
+    }
+
+    // Parse an exported save file, upgrading it to the current schema. Also says what had to be
+    // skipped or filled in to read a file from a different version of the app.
+    fn from_ron(content: &str) -> Result<(Self, Leniency)> {
+        let (app, leniency) = import::parse_lenient::<Self>(content)?;
+        Ok((app.migrate(), leniency))
+    }
+
+    // Upgrade state saved by an older version of the app, one schema version at a time.
+    fn migrate(mut self) -> Self {
+        if self.schema_version < 2 {
+            // v1 files were hand-editable and may name an active profile that does not exist,
+            // or have no profiles at all.
+            if self.profiles.is_empty() {
+                self.profiles
+                    .insert("Default".to_owned(), Profile::default());
+            }
+            if !self.profiles.contains_key(&self.active_profile) {
+                self.active_profile = self.profiles.keys().min().cloned().unwrap_or_default();
+            }
+            // The cached schedule is always recomputed, so don't trust one from an old version.
+            for profile in self.profiles.values_mut() {
+                profile.schedule.clear();
+            }
+            self.schema_version = 2;
+        }
+        // Not a schema change: the built-in vaccines can change with any update.
+        let notices = self
+            .profiles
+            .iter_mut()
+            .sorted_by(|a, b| a.0.cmp(b.0))
+            .filter_map(|(name, profile)| {
+                let changes =
+                    profile.reconcile_vaccines(Vaccine::get_vaccines(), self.enable_new_vaccines);
+                (!changes.is_empty()).then(|| format!("{name}: {changes}."))
+            })
+            .collect_vec();
+        if !notices.is_empty() {
+            self.vaccine_list_notice =
+                Some(format!("Updated the vaccine list. {}", notices.join(" ")));
+        }
+        self
     }
 }
 
@@ -104,14 +1323,114 @@ impl eframe::App for VaccineHelperApp {
         // Put your widgets into a `SidePanel`, `TopBottomPanel`, `CentralPanel`, `Window` or `Area`.
         // For inspiration and more examples, go to https://emilk.github.io/egui
 
+        if self.shared_records.is_some() {
+            self.show_shared_view(ctx);
+            return;
+        }
+
+        if HAS_SHORTCUTS {
+            if ctx.input_mut(|i| i.consume_shortcut(&IMPORT_SHORTCUT)) {
+                Self::import_records().ok();
+            }
+            if ctx.input_mut(|i| i.consume_shortcut(&EXPORT_SHORTCUT)) {
+                self.export_records();
+            }
+        }
+        self.take_pending_imports();
+
+        let (saving, save_errors) = poll_saves();
+        if !save_errors.is_empty() {
+            self.import_message = Some(save_errors.join(" "));
+        }
+        if saving {
+            // Nothing else may wake the UI when the save finishes.
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
         // Menu Bar
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    let shortcut_text = |shortcut| {
+                        if HAS_SHORTCUTS {
+                            ctx.format_shortcut(shortcut)
+                        } else {
+                            String::new()
+                        }
+                    };
+                    let import = egui::Button::new("Import Records...")
+                        .shortcut_text(shortcut_text(&IMPORT_SHORTCUT));
+                    if ui.add(import).clicked() {
+                        Self::import_records().ok();
+                        ui.close_menu();
+                    }
+                    let export = egui::Button::new("Export Records...")
+                        .shortcut_text(shortcut_text(&EXPORT_SHORTCUT));
+                    if ui.add(export).clicked() {
+                        self.export_records();
+                        ui.close_menu();
+                    }
+                    if ui.button("Export Calendar...").clicked() {
+                        self.export_calendar();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Export Plan as CSV...")
+                        .on_hover_text("The planned appointments, for your own spreadsheet")
+                        .clicked()
+                    {
+                        self.export_plan_csv();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Export Profile...")
+                        .on_hover_text(
+                            "Save just this profile, laid out the same way every time so that it diffs cleanly",
+                        )
+                        .clicked()
+                    {
+                        self.export_profile().ok();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Export Vaccine Order...")
+                        .on_hover_text(
+                            "Save the vaccine priority order, to import it into another profile",
+                        )
+                        .clicked()
+                    {
+                        self.export_vaccine_order().ok();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Export Vaccine Database...")
+                        .on_hover_text("Save the built-in schedules and notes, to review them")
+                        .clicked()
+                    {
+                        self.export_vaccine_database().ok();
+                        ui.close_menu();
+                    }
+                    ui.separator();
                     if ui.button("Profiles...").clicked() {
                         self.show_profiles = true;
                         ui.close_menu();
                     }
+                    if ui
+                        .button("Simulate Aging...")
+                        .on_hover_text("Sweep through the coming years to see when vaccines come due")
+                        .clicked()
+                    {
+                        self.start_simulation();
+                        ui.close_menu();
+                    }
+                    if ui.button("Household Schedule...").clicked() {
+                        self.show_household = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Disease Coverage...").clicked() {
+                        self.show_coverage = true;
+                        ui.close_menu();
+                    }
                     if ui.button("Preferences...").clicked() {
                         self.show_preferences = true;
                         ui.close_menu();
@@ -131,15 +1450,67 @@ impl eframe::App for VaccineHelperApp {
                         self.show_about = true;
                         ui.close_menu();
                     }
+                    if ui.button("Debug Log...").clicked() {
+                        self.show_debug_log = true;
+                        ui.close_menu();
+                    }
                 });
+                ui.toggle_value(&mut self.focus_mode, "Focus")
+                    .on_hover_text("Show only what is overdue or due soon");
+                if saving {
+                    ui.spinner();
+                    ui.label("Saving...");
+                }
+                let time = ui.input(|i| i.time);
+                if self
+                    .replanned_at
+                    .is_some_and(|at| time - at < REPLAN_FLASH_SECONDS)
+                {
+                    ui.weak("Recomputing…");
+                    ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                }
             });
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(warning) = tz::fallback_warning() {
+                ui.colored_label(ui.visuals().warn_fg_color, warning);
+            }
+            if let Some(now) = &self.now_override {
+                let mut stop = false;
+                let (what, button) = if self.simulation.is_some() {
+                    ("Simulating", "Stop Simulation")
+                } else {
+                    ("Previewing", "Stop Preview")
+                };
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!("{what} as of {}", now.strftime("%B %-d, %Y")))
+                            .heading()
+                            .color(ui.visuals().warn_fg_color),
+                    )
+                    .on_hover_text(
+                        "The plan is shown as it will look then. Your records are unchanged.",
+                    );
+                    stop = ui.button(button).clicked();
+                });
+                // Closing a simulation goes back to the preview it was started from, if any.
+                if stop && self.simulation.is_some() {
+                    self.stop_simulation();
+                } else if stop {
+                    self.set_preview(None);
+                }
+            }
+            self.show_next_action(ui).unwrap();
+            self.show_stats(ui);
             egui::ScrollArea::vertical().show(ui, |ui| {
-                self.show_records_section(ui).unwrap();
-                self.show_config_section(ui).unwrap();
-                self.show_schedule_section(ui).unwrap();
+                if self.focus_mode {
+                    self.show_focus_section(ui).unwrap();
+                } else {
+                    self.show_records_section(ui).unwrap();
+                    self.show_config_section(ui).unwrap();
+                    self.show_schedule_section(ui).unwrap();
+                }
 
                 ui.with_layout(egui::Layout::bottom_up(egui::Align::RIGHT), |ui| {
                     powered_by_egui_and_eframe(ui);
@@ -149,9 +1520,25 @@ impl eframe::App for VaccineHelperApp {
         });
 
         // Show sub-windows
+        if self.needs_onboarding() && self.onboarding.is_none() {
+            self.onboarding = Some(Onboarding::default());
+        }
+        self.show_onboarding(ctx);
+        self.show_import_review(ctx);
+        self.show_import_conflicts(ctx);
+        if self.onboarding.is_none() {
+            self.show_health_questions(ctx);
+        }
+        self.show_import_confirmation(ctx);
         self.show_profile_list(ctx);
         self.show_preferences(ctx);
         self.show_about(ctx);
+        self.show_debug_log(ctx);
+        self.show_passport(ctx);
+        self.show_household(ctx);
+        self.show_timeline(ctx);
+        self.show_coverage(ctx);
+        self.show_simulation(ctx).unwrap();
     }
 
     /// Called by the frame work to save state before shutdown.
@@ -169,40 +1556,649 @@ impl VaccineHelperApp {
         self.profiles.get_mut(&self.active_profile).unwrap()
     }
 
-    fn show_records_section(&mut self, ui: &mut egui::Ui) -> Result<()> {
-        static RESTORE_CONTENT: Mutex<Option<String>> = Mutex::new(None);
+    fn now(&self) -> Zoned {
+        self.now_override.clone().unwrap_or_else(tz::now)
+    }
+
+    // Plan as though today were `date`, to see what will be due then. Records are not touched.
+    fn set_preview(&mut self, date: Option<jiff::civil::Date>) {
+        self.now_override = date.and_then(|date| date.to_zoned(tz::system()).ok());
+    }
+
+    fn start_simulation(&mut self) {
+        if self.simulation.is_none() {
+            self.simulation = Some(Simulation {
+                start: self.now(),
+                months: 0,
+                playing: false,
+                last_step: 0.0,
+                previous: self.now_override.clone(),
+            });
+        }
+    }
+
+    // Months from the start of the simulation to the end of the plan.
+    fn simulation_span(&self) -> i16 {
+        self.simulation.as_ref().map_or(0, |sim| {
+            ((self.profile().end_plan_year - sim.start.year()) * 12 - sim.start.month() as i16 + 1)
+                .max(0)
+        })
+    }
+
+    // Move the simulation to `months` after its start.
+    fn scrub_simulation(&mut self, months: i16) -> Result<()> {
+        let span = self.simulation_span();
+        let Some(sim) = self.simulation.as_mut() else {
+            return Ok(());
+        };
+        sim.months = months.clamp(0, span);
+        self.now_override = Some(
+            sim.start
+                .checked_add(jiff::Span::new().months(sim.months))?,
+        );
+        Ok(())
+    }
+
+    fn stop_simulation(&mut self) {
+        if let Some(sim) = self.simulation.take() {
+            self.now_override = sim.previous;
+        }
+    }
+
+    fn recompute_schedule(&mut self) -> Result<()> {
+        let now = self.now();
+        // A preview moves the skips of today into the past; keep them for when it ends.
+        if self.now_override.is_none() {
+            self.profile_mut().prune_skips(&now)?;
+        }
+        self.profile_mut().replan(&now)
+    }
+
+    // Make the plan again only if something it depends on changed. Returns whether it did.
+    fn refresh_schedule(&mut self) -> Result<bool> {
+        let now = self.now();
+        if self.profile().plan_hash == Some(self.profile().plan_inputs_hash(&now)) {
+            return Ok(false);
+        }
+        self.recompute_schedule()?;
+        let hash = self.profile().plan_inputs_hash(&now);
+        self.profile_mut().plan_hash = Some(hash);
+        Ok(true)
+    }
+
+    // Settle overlapping combination vaccines when the preference changes, or in favor of the
+    // vaccines the user just enabled. A profile is left as it was saved until then.
+    fn sync_combinations(&mut self) -> Vec<(String, String)> {
+        let prefer_combined = self.prefer_combined;
+        let enabled = self.profile().enabled_names();
+        let changes = match &self.combinations_checked {
+            Some((profile, prefer, before)) if *profile == self.active_profile => {
+                if *prefer != prefer_combined {
+                    self.profile_mut()
+                        .resolve_combinations(prefer_combined, None)
+                } else if *before != enabled {
+                    let just_enabled = enabled.difference(before).cloned().collect();
+                    self.profile_mut()
+                        .resolve_combinations(prefer_combined, Some(&just_enabled))
+                } else {
+                    vec![]
+                }
+            }
+            _ => vec![],
+        };
+        self.combinations_checked = Some((
+            self.active_profile.clone(),
+            prefer_combined,
+            self.profile().enabled_names(),
+        ));
+        changes
+    }
+
+    fn undo_enable_recommended(&mut self) {
+        if let Some((name, previous)) = self.undo_enabled.take() {
+            if let Some(profile) = self.profiles.get_mut(&name) {
+                profile.restore_enabled(&previous);
+            }
+        }
+    }
+
+    fn commit_record_edit(&mut self) {
+        if self.editing_record.take().is_some() {
+            self.profile_mut().records.sort();
+        }
+    }
+
+    // Apply what was clicked in one pass over the records grid. All of the indices are into the
+    // list as it was drawn, which may be out of order while a date is being edited, so rows are
+    // deleted before the edit is closed and the list re-sorted.
+    fn apply_record_changes(
+        &mut self,
+        deletions: &[usize],
+        commit_edit: bool,
+        start_edit: Option<usize>,
+    ) {
+        let after_deletions = |i: usize| {
+            (!deletions.contains(&i)).then(|| i - deletions.iter().filter(|d| **d < i).count())
+        };
+        let editing = self.editing_record.and_then(after_deletions);
+        let mut start_edit = start_edit.and_then(after_deletions);
+        let close_edit = self.editing_record.is_some()
+            && (commit_edit || start_edit.is_some() || !deletions.is_empty());
+        let records = &mut self.profile_mut().records;
+        for deletion in deletions.iter().sorted().rev() {
+            records.remove(*deletion);
+        }
+        if close_edit {
+            let mut order = (0..records.len()).collect_vec();
+            order.sort_by(|a, b| records[*a].cmp(&records[*b]));
+            start_edit = start_edit.and_then(|i| order.iter().position(|j| *j == i));
+            *records = order.iter().map(|i| records[*i].clone()).collect();
+            self.editing_record = None;
+        } else {
+            self.editing_record = editing;
+        }
+        self.editing_record = start_edit.or(self.editing_record);
+    }
+
+    // Everyone's upcoming appointments, tagged with the profile name, in date order.
+    fn household_schedule(&self) -> Vec<(String, VaccineAppointment)> {
+        let now = self.now();
+        let mut out = Vec::new();
+        for (name, profile) in self.profiles.iter().sorted_by_key(|(name, _)| *name) {
+            match profile.plan(&now) {
+                Ok(plan) => out.extend(
+                    plan.into_iter()
+                        .filter(|appt| appt.year() < profile.end_plan_year)
+                        .map(|appt| (name.clone(), appt)),
+                ),
+                Err(e) => log::warn!("failed to schedule profile {name}: {e}"),
+            }
+        }
+        // Stable, so profiles stay in name order within a month.
+        out.sort_by(|(_, a), (_, b)| a.cmp(b));
+        out
+    }
+
+    // Group the scheduled appointments by (year, month), in order, stopping at the end of the plan.
+    fn schedule_by_month(&self) -> Vec<((i16, i8), Vec<&VaccineAppointment>)> {
+        let profile = self.profile();
+        profile
+            .schedule
+            .iter()
+            .filter(|appt| appt.year() < profile.end_plan_year)
+            .chunk_by(|appt| (appt.year(), appt.month()))
+            .into_iter()
+            .map(|(ym, appts)| (ym, appts.collect()))
+            .collect()
+    }
+
+    // The soonest planned shot. Within a month the schedule is already in priority order, then by
+    // dose, so the first one wins. Titer checks are blood draws, not shots, so they are skipped.
+    fn next_appointment(&self) -> Option<&VaccineAppointment> {
+        let profile = self.profile();
+        profile
+            .schedule
+            .iter()
+            .filter(|appt| appt.year() < profile.end_plan_year)
+            .find(|appt| appt.kind() != DoseKind::TiterCheck)
+    }
+
+    // Import a record for each line of `date, vaccine, kind`, dated in the profile's zone, the
+    // same way as a file: unknown names are reviewed and records already on file are skipped. If
+    // any line doesn't parse nothing is imported, and the errors are returned.
+    fn bulk_add_records(&mut self, text: &str) -> Vec<String> {
+        let (records, errors) = csv::parse_each(text, &self.profile().time_zone());
+        if errors.is_empty() {
+            self.commit_record_edit();
+            self.import(PendingImport::Records(
+                records
+                    .into_iter()
+                    .map(|record| record.with_source(RecordSource::SelfReported))
+                    .collect(),
+            ));
+        }
+        errors
+    }
+
+    fn show_bulk_add(&mut self, ui: &mut egui::Ui, mut bulk: BulkAdd) {
+        ui.label("One record per line, as date, vaccine, kind. Ctrl+Enter adds them.");
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut bulk.text)
+                .code_editor()
+                .desired_rows(8)
+                .desired_width(f32::INFINITY)
+                .hint_text("2020-06-01, Tdap, Dose#1\n2021-10-15, Flu, Booster"),
+        );
+        if std::mem::take(&mut bulk.focus) {
+            response.request_focus();
+        }
+        for error in &bulk.errors {
+            ui.colored_label(ui.visuals().warn_fg_color, error);
+        }
+        let mut submit = response.has_focus()
+            && ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Enter));
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            submit |= ui.button("Add Records").clicked();
+            cancel = ui.button("Cancel").clicked();
+        });
+        if submit {
+            bulk.errors = self.bulk_add_records(&bulk.text);
+            if bulk.errors.is_empty() {
+                return;
+            }
+        }
+        if !cancel {
+            self.bulk_add = Some(bulk);
+        }
+    }
+
+    // Record the appointment as received today.
+    fn mark_done(&mut self, appt: &VaccineAppointment) -> Result<()> {
+        let today = self.now().with_time_zone(self.profile().time_zone());
+        self.commit_record_edit();
+        self.profile_mut()
+            .records
+            .push(VaccineRecord::new(appt.vaccine(), today, appt.kind(), ""));
+        self.profile_mut().records.sort();
+        self.recompute_schedule()
+    }
+
+    fn show_next_action(&mut self, ui: &mut egui::Ui) -> Result<()> {
+        let Some(next) = self.next_appointment().cloned() else {
+            ui.label(egui::RichText::new("You're all caught up.").heading());
+            return Ok(());
+        };
+        let mut done = false;
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(format!(
+                    "Your next shot is {} {} in {}.",
+                    next.vaccine(),
+                    next.label(&self.dose_labels),
+                    next.date().strftime("%B %Y")
+                ))
+                .heading(),
+            );
+            // A simulated date is not one to record a shot on.
+            done = ui
+                .add_enabled(self.simulation.is_none(), egui::Button::new("Mark Done"))
+                .on_hover_text("Record this shot as received today.")
+                .clicked();
+        });
+        if done {
+            self.mark_done(&next)?;
+        }
+        Ok(())
+    }
+
+    // A one line summary of the records and the plan, to show progress.
+    fn show_stats(&self, ui: &mut egui::Ui) {
+        let profile = self.profile();
+        if profile.records.is_empty() && profile.schedule.is_empty() {
+            return;
+        }
+        let stats = Stats::new(&profile.records, &profile.schedule, &self.now());
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.label(stats.to_string()).on_hover_text(format!(
+                "{} doses and {} boosters on record. {} shots left in the plan.",
+                stats.doses, stats.boosters, stats.upcoming
+            ));
+        });
+    }
+
+    fn export_record_cards(&mut self) {
+        let records = self
+            .profile()
+            .records
+            .iter()
+            .filter(|record| self.export_filter.keeps_record(record))
+            .cloned()
+            .collect::<Vec<_>>();
+        match card::record_cards(&self.active_profile, &records) {
+            Ok(cards) => {
+                let pages = cards.len();
+                let files = cards
+                    .into_iter()
+                    .enumerate()
+                    .map(|(page, png)| (card::card_filename(page, pages), png))
+                    .collect::<Vec<_>>();
+                download_files(&files, card::PNG_MIME_TYPE).ok();
+            }
+            Err(e) => self.import_message = Some(format!("Could not draw the record card: {e}")),
+        }
+    }
+
+    fn export_records(&self) {
+        let data =
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).expect("serialize");
+        download_file(&data, "vaccine_helper.ron", "application/ron").ok();
+    }
+
+    fn import_records() -> Result<()> {
+        create_file_picker(&["ron"], |content| {
+            *RESTORE_CONTENT.lock().unwrap() = Some(content);
+        })
+    }
+
+    fn import_csv(&mut self, content: &str, tz: &TimeZone) {
+        match csv::parse_records(content, tz) {
+            Ok(records) => self.import(PendingImport::Records(records)),
+            Err(e) => self.import_message = Some(format!("Import failed: {e}")),
+        }
+    }
+
+    // Commit an import, unless it names vaccines we don't know, in which case ask first.
+    fn import(&mut self, pending: PendingImport) {
+        let unknown = pending.unknown_vaccines();
+        if unknown.is_empty() {
+            self.finish_import(pending, &HashMap::new());
+        } else {
+            self.import_message =
+                Some("Some records name unrecognized vaccines; review them to continue.".into());
+            self.import_review = Some(ImportReview::new(pending, unknown));
+        }
+    }
+
+    fn finish_import(
+        &mut self,
+        pending: PendingImport,
+        actions: &HashMap<String, UnknownVaccineAction>,
+    ) {
+        match pending {
+            PendingImport::Records(mut records) => {
+                import::resolve_unknown(&mut records, actions);
+                let reconciled =
+                    import::reconcile(&self.profile().records, records, IMPORT_DATE_TOLERANCE_DAYS);
+                let mut message = format!("Imported {} records.", reconciled.new.len());
+                if !reconciled.matched.is_empty() {
+                    message.push_str(&format!(
+                        " Skipped {} already on file.",
+                        reconciled.matched.len()
+                    ));
+                }
+                if !reconciled.conflicting.is_empty() {
+                    message.push_str(" Some may already be on file under a different date.");
+                }
+                self.commit_record_edit();
+                self.profile_mut().records.extend(reconciled.new);
+                self.profile_mut().records.sort();
+                self.import_conflicts = reconciled
+                    .conflicting
+                    .into_iter()
+                    .map(|conflict| (conflict, false))
+                    .collect();
+                self.import_message = Some(message);
+            }
+            PendingImport::Profile(mut profile) => {
+                import::resolve_unknown(&mut profile.records, actions);
+                let diff = ProfileDiff::new(self.profile(), &profile);
+                self.staged_import = Some(StagedImport::Profile(profile, diff));
+            }
+            PendingImport::Restore(mut app) => {
+                for profile in app.profiles.values_mut() {
+                    import::resolve_unknown(&mut profile.records, actions);
+                }
+                self.staged_import = Some(StagedImport::Restore(app));
+            }
+        }
+    }
+
+    // Apply a staged import the way the user chose. A full save can only replace everything.
+    fn confirm_import(&mut self, choice: ImportChoice) {
+        self.commit_record_edit();
+        match (self.staged_import.take(), choice) {
+            (None, _) => {}
+            (Some(StagedImport::Restore(app)), _) => {
+                *self = *app;
+                self.import_message = Some("Restored a full save.".to_owned());
+            }
+            (Some(StagedImport::Profile(profile, _)), ImportChoice::Replace) => {
+                self.import_message = Some(format!(
+                    "Replaced \"{}\" with an imported profile of {} records.",
+                    self.active_profile,
+                    profile.records.len()
+                ));
+                *self.profile_mut() = *profile;
+            }
+            (Some(StagedImport::Profile(profile, _)), ImportChoice::Merge) => {
+                let added = self.profile_mut().merge_records(profile.records);
+                self.import_message = Some(format!(
+                    "Merged {added} new records into \"{}\".",
+                    self.active_profile
+                ));
+            }
+            (Some(StagedImport::Profile(profile, _)), ImportChoice::AddProfile) => {
+                let name = (1..)
+                    .map(|i| match i {
+                        1 => "Imported".to_owned(),
+                        i => format!("Imported {i}"),
+                    })
+                    .find(|name| !self.profiles.contains_key(name))
+                    .expect("a free profile name");
+                self.import_message = Some(format!(
+                    "Imported a profile with {} records as \"{name}\".",
+                    profile.records.len()
+                ));
+                self.profiles.insert(name.clone(), *profile);
+                self.active_profile = name;
+            }
+        }
+    }
+
+    fn import_ron(&mut self, content: &str) {
+        let (parsed, leniency) = match RonImport::parse_lenient(content) {
+            Ok((parsed, leniency)) => (Ok(parsed), leniency),
+            Err(e) => (Err(e), Leniency::default()),
+        };
+        match parsed {
+            Ok(RonImport::App(app)) => self.import(PendingImport::Restore(app)),
+            Ok(RonImport::Profile(profile)) => self.import(PendingImport::Profile(profile)),
+            Ok(RonImport::Records(records)) => self.import(PendingImport::Records(records)),
+            Ok(RonImport::Appointments(appointments)) => {
+                self.import_message = Some(format!(
+                    "This file is a schedule of {} appointments. Schedules are built from records, so there is nothing to import.",
+                    appointments.len()
+                ));
+            }
+            Ok(RonImport::VaccineOrder(order)) => {
+                let skipped = self.profile_mut().apply_vaccine_order(&order);
+                let mut message =
+                    format!("Applied the vaccine order to \"{}\".", self.active_profile);
+                if skipped > 0 {
+                    message.push_str(&format!(" Skipped {skipped} unknown vaccines."));
+                }
+                self.import_message = Some(message);
+            }
+            Err(e) => self.import_message = Some(format!("Import failed: {e}")),
+        }
+        if !leniency.is_empty() {
+            let note = format!("The file is from another version of the app: {leniency}.");
+            self.import_message = Some(match self.import_message.take() {
+                Some(message) => format!("{message} {note}"),
+                None => note,
+            });
+        }
+    }
+
+    fn export_profile(&self) -> Result<()> {
+        download_file(
+            serialize_profile_stable(self.profile())?,
+            "vaccine_profile.ron",
+            "application/ron",
+        )
+    }
+
+    fn export_vaccine_order(&self) -> Result<()> {
+        let order = VaccineOrder::of(self.profile());
+        download_file(
+            ron::ser::to_string_pretty(&order, ron::ser::PrettyConfig::default())?,
+            "vaccine_order.ron",
+            "application/ron",
+        )
+    }
+
+    fn export_vaccine_database(&self) -> Result<()> {
+        download_file(
+            &Vaccine::database_to_ron()?,
+            "vaccine_database.ron",
+            "application/ron",
+        )
+    }
+
+    fn export_plan_csv(&self) {
+        download_file(
+            csv::plan_to_csv(&self.profile().schedule, |appt| {
+                self.export_filter.keeps_appointment(appt)
+            }),
+            "vaccine_plan.csv",
+            csv::MIME_TYPE,
+        )
+        .ok();
+    }
+
+    fn export_calendar(&self) {
+        download_file(
+            ics::schedule_to_ics(&self.profile().schedule, &self.profile().notes, |appt| {
+                self.export_filter.keeps_appointment(appt)
+            }),
+            "vaccine_schedule.ics",
+            ics::MIME_TYPE,
+        )
+        .ok();
+    }
+
+    // Apply files picked in an import dialog since the last frame, whichever sections are shown.
+    fn take_pending_imports(&mut self) {
         {
             let mut guard = RESTORE_CONTENT.lock().unwrap();
             let maybe_restore = guard.take();
             if let Some(restore) = maybe_restore {
-                let new_self: Self = ron::de::from_str(&restore)?;
-                *self = new_self;
+                self.import_ron(&restore);
+            }
+        }
+        {
+            let maybe_import = IMPORT_CSV_CONTENT.lock().unwrap().take();
+            if let Some(content) = maybe_import {
+                let time_zone = self.profile().time_zone();
+                self.import_csv(&content, &time_zone);
             }
         }
+    }
 
+    fn show_records_section(&mut self, ui: &mut egui::Ui) -> Result<()> {
         ui.heading("Vaccine Records");
         ui.label(
             "Put immunizations you've already received here to remove them from the schedule.",
         );
+        let too_close = self.profile().doses_too_close(&self.now())?;
         egui::Grid::new("records_grid")
             .num_columns(5)
             .show(ui, |ui| {
                 let mut deletions = vec![];
-                for (i, record) in self.profile().records.iter().enumerate() {
-                    ui.label(record.date().strftime("%d %b %y").to_string());
-                    ui.label(record.vaccine());
-                    ui.label(record.kind().to_string());
-                    ui.label(record.notes());
+                let mut start_edit = None;
+                let mut commit_edit = false;
+                let editing = self.editing_record;
+                let profile = self.profiles.get_mut(&self.active_profile).unwrap();
+                let in_plan = profile
+                    .records
+                    .iter()
+                    .map(|record| profile.record_in_plan(record))
+                    .collect_vec();
+                for (i, record) in profile.records.iter_mut().enumerate() {
+                    if editing == Some(i) {
+                        ui.horizontal(|ui| {
+                            let date = record.date();
+                            let mut date = NaiveDate::from_ymd_opt(
+                                date.year().into(),
+                                date.month() as u32,
+                                date.day() as u32,
+                            )
+                            .unwrap();
+                            ui.add(
+                                egui_extras::DatePickerButton::new(&mut date)
+                                    .id_salt("records_grid_edit_date")
+                                    .show_icon(true),
+                            );
+                            *record.date_mut() =
+                                jiffdate(date.year() as i16, date.month() as i8, date.day() as i8)
+                                    .to_zoned(record.date().time_zone().clone())
+                                    .expect("a valid date");
+                            precision_picker(
+                                ui,
+                                "records_grid_edit_precision",
+                                record.precision_mut(),
+                            );
+                            commit_edit = ui.small_button("Done").clicked();
+                        });
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.label(record.date_label());
+                            if ui
+                                .small_button("✏")
+                                .on_hover_text("Correct the date or record a reaction")
+                                .clicked()
+                            {
+                                start_edit = Some(i);
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        let source = record.source();
+                        ui.label(source.icon()).on_hover_text(source.to_string());
+                        if in_plan[i] {
+                            ui.label(record.vaccine());
+                        } else {
+                            ui.weak(format!("{} (not in your plan)", record.vaccine()))
+                                .on_hover_text(
+                                    "This vaccine isn't enabled, so this record doesn't change the schedule. Enable it above to plan around it.",
+                                );
+                        }
+                    });
+                    if record.invalid() {
+                        ui.label(
+                            egui::RichText::new(record.kind().label(&self.dose_labels))
+                                .strikethrough(),
+                        )
+                        .on_hover_text(INVALID_DOSE_HELP);
+                    } else if let Some(interval) = too_close[i] {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "⚠ {}",
+                                record.kind().label(&self.dose_labels)
+                            ))
+                            .color(ui.visuals().warn_fg_color),
+                        )
+                        .on_hover_text(format!(
+                            "This dose is less than {interval} months after the previous one. Check the dates, or ask your provider whether it has to be repeated."
+                        ));
+                    } else {
+                        ui.label(record.kind().label(&self.dose_labels));
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(record.notes());
+                        if editing == Some(i) {
+                            reaction_editor(
+                                ui,
+                                "records_grid_edit_reaction",
+                                record.reaction_mut(),
+                            );
+                            ui.checkbox(record.invalid_mut(), "Invalid")
+                                .on_hover_text(INVALID_DOSE_HELP);
+                            source_picker(ui, "records_grid_edit_source", record.source_mut());
+                        } else if let Some(reaction) = record.reaction() {
+                            ui.label("⚠").on_hover_text(format!("Reaction: {reaction}"));
+                        }
+                    });
                     if ui.button("Delete").clicked() {
                         deletions.push(i);
                     }
                     ui.end_row();
                 }
-                for deletion in deletions.iter().rev() {
-                    self.profile_mut().records.remove(*deletion);
-                }
+                self.apply_record_changes(&deletions, commit_edit, start_edit);
             });
         if let Some(mut record) = self.add_record.take() {
+            let time_zone = self.profile().time_zone();
             egui::Grid::new("record_entry_grid")
                 .num_columns(2)
                 .show(ui, |ui| {
@@ -233,7 +2229,7 @@ impl VaccineHelperApp {
                     egui::ComboBox::from_id_salt("record_entry_kind")
                         .wrap_mode(TextWrapMode::Extend)
                         .show_index(ui, &mut current_index, kind_names.len(), |i| {
-                            kind_names[i].0
+                            kind_names[i].1.label(&self.dose_labels)
                         });
                     *record.kind_mut() = kind_names[current_index].1;
                     ui.end_row();
@@ -245,45 +2241,120 @@ impl VaccineHelperApp {
                         record.date().day() as u32,
                     )
                     .unwrap();
-                    ui.add(egui_extras::DatePickerButton::new(&mut date).show_icon(true));
+                    ui.horizontal(|ui| {
+                        ui.add(egui_extras::DatePickerButton::new(&mut date).show_icon(true));
+                        precision_picker(ui, "record_entry_precision", record.precision_mut());
+                    });
                     *record.date_mut() =
                         jiffdate(date.year() as i16, date.month() as i8, date.day() as i8)
-                            .to_zoned(TimeZone::system())
+                            .to_zoned(time_zone.clone())
                             .expect("a valid date");
                     ui.end_row();
 
                     ui.label("Notes:");
                     ui.text_edit_singleline(record.notes_mut());
                     ui.end_row();
+
+                    ui.label("");
+                    reaction_editor(ui, "record_entry_reaction", record.reaction_mut());
+                    ui.end_row();
+
+                    ui.label("");
+                    ui.checkbox(record.invalid_mut(), "Invalid, must be repeated")
+                        .on_hover_text(INVALID_DOSE_HELP);
+                    ui.end_row();
+
+                    ui.label("Source:");
+                    source_picker(ui, "record_entry_source", record.source_mut());
+                    ui.end_row();
                 });
             if ui.button("Add Record").clicked() {
                 // Note: always keep the records sorted by receipt date, not entry time.
+                self.commit_record_edit();
                 self.profile_mut().records.push(record);
                 self.profile_mut().records.sort();
                 self.add_record = None;
             } else {
                 self.add_record = Some(record);
             }
+        } else if let Some(bulk) = self.bulk_add.take() {
+            self.show_bulk_add(ui, bulk);
         } else {
             ui.horizontal(|ui| -> Result<()> {
                 if ui.button("New Record").clicked() {
                     self.add_record = Some(VaccineRecord::default());
                 }
+                if ui
+                    .button("Bulk Add")
+                    .on_hover_text("Type in several records at once")
+                    .clicked()
+                {
+                    self.bulk_add = Some(BulkAdd {
+                        focus: true,
+                        ..BulkAdd::default()
+                    });
+                }
                 if ui.button("Export").clicked() {
-                    let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
-                        .expect("serialize");
-                    download_file(&data, "vaccine_helper.ron", "application/ron").ok();
+                    self.export_records();
                     return Ok(());
                 }
                 if ui.button("Import").clicked() {
-                    create_file_picker(|content| {
-                        *RESTORE_CONTENT.lock().unwrap() = Some(content);
+                    Self::import_records()?;
+                }
+                ui.separator();
+                if ui.button("Download Blank Template").clicked() {
+                    download_file(csv::blank_template(), "vaccine_records.csv", csv::MIME_TYPE)
+                        .ok();
+                }
+                if ui.button("Export CSV").clicked() {
+                    download_file(
+                        csv::records_to_csv(&self.profile().records, |record| {
+                            self.export_filter.keeps_record(record)
+                        }),
+                        "vaccine_records.csv",
+                        csv::MIME_TYPE,
+                    )
+                    .ok();
+                }
+                if ui
+                    .button("Export Card")
+                    .on_hover_text("A printable image of these records")
+                    .clicked()
+                {
+                    self.export_record_cards();
+                }
+                if ui.button("Import CSV").clicked() {
+                    create_file_picker(&["csv"], |content| {
+                        *IMPORT_CSV_CONTENT.lock().unwrap() = Some(content);
                     })?;
                 }
+                ui.separator();
+                if ui.button("Passport QR").clicked() {
+                    self.show_passport = true;
+                }
+                if let Some(url) = share_url("") {
+                    if ui
+                        .button("Copy Share Link")
+                        .on_hover_text("A read-only link to these records")
+                        .clicked()
+                    {
+                        self.import_message =
+                            Some(match share::encode_records(&self.profile().records) {
+                                Ok(fragment) => {
+                                    ui.ctx().copy_text(format!("{url}{fragment}"));
+                                    "Copied a share link to the clipboard.".to_owned()
+                                }
+                                Err(e) => format!("Could not make a share link: {e}"),
+                            });
+                    }
+                }
                 Ok(())
             })
             .inner?;
         }
+        if let Some(message) = &self.import_message {
+            ui.label(message);
+        }
         ui.label("");
 
         Ok(())
@@ -292,20 +2363,77 @@ impl VaccineHelperApp {
     fn show_config_section(&mut self, ui: &mut egui::Ui) -> Result<()> {
         ui.heading("Schedule Configuration");
         ui.label("Select and prioritize the vaccines you want to get");
+        timeline::show_category_legend(ui);
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.hide_completed, "Hide completed")
+                .on_hover_text(format!(
+                    "Hide vaccines that need nothing more in the next {} years",
+                    COMPLETED_HORIZON_MO / 12
+                ));
+            if ui
+                .button("Use Recommended")
+                .on_hover_text(
+                    "Enable the recommended vaccines and disable the rest, keeping your order",
+                )
+                .clicked()
+            {
+                let previous = self.profile_mut().enable_recommended();
+                self.undo_enabled = Some((self.active_profile.clone(), previous));
+            }
+            if self
+                .undo_enabled
+                .as_ref()
+                .is_some_and(|(profile, _)| *profile == self.active_profile)
+                && ui.button("Undo").clicked()
+            {
+                self.undo_enable_recommended();
+            }
+        });
 
         // Order the vaccines and select which ones to enable.
+        let now = self.now();
+        let hide_completed = self.hide_completed;
+        let contraindicated = self.profile().contraindicated();
+        let profile = self.profiles.get_mut(&self.active_profile).unwrap();
+        let records = &profile.records;
+        let schedule = &profile.schedule;
+        let unscheduled = &profile.unscheduled;
+        let labels = &self.dose_labels;
+        let show_dose_gaps = self.show_dose_gaps;
+        let mut visible = Vec::new();
+        for (i, vaccine_cfg) in profile.vaccines.iter().enumerate() {
+            let vaccine = &Vaccine::get_vaccines()[vaccine_cfg.name.as_str()];
+            let vaccine_records = records.iter().filter(|r| r.vaccine() == vaccine.name());
+            if !hide_completed
+                || !vaccine.is_complete(&now, vaccine_records, COMPLETED_HORIZON_MO)?
+            {
+                visible.push(i);
+            }
+        }
         let response = dnd(ui, "dnd_vaccines").show(
-            self.profile_mut().vaccines.iter_mut(),
+            profile
+                .vaccines
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, _)| visible.contains(i))
+                .map(|(_, vaccine_cfg)| vaccine_cfg),
             |ui, vaccine_cfg, handle, _state| {
                 let vaccine = Vaccine::get_vaccines()
                     .get(vaccine_cfg.name.as_str())
                     .expect("valid vaccine name");
-                handle.ui(ui, |ui| {
+                let details_id = ui.make_persistent_id(("vaccine_details", vaccine.name()));
+                let mut details =
+                    CollapsingState::load_with_default_open(ui.ctx(), details_id, false);
+                let row = handle.ui(ui, |ui| {
                     ui.horizontal(|ui| {
-                        ui.add(egui::Image::new(egui::include_image!(
-                            "../assets/icons8-drag-handle-30.png"
-                        )));
+                        drag_handle(
+                            ui,
+                            egui::include_image!("../assets/icons8-drag-handle-30.png"),
+                        );
                         ui.checkbox(&mut vaccine_cfg.enabled, "");
+                        ui.label(timeline::category_dot(ui.visuals(), vaccine.category()))
+                            .on_hover_text(vaccine.category().to_string());
                         let resp = ui.add_enabled(
                             vaccine_cfg.enabled,
                             egui::Label::new(format!(
@@ -315,85 +2443,915 @@ impl VaccineHelperApp {
                             )),
                         );
                         if resp.hovered() {
-                            resp.show_tooltip_text(format!(
-                                "Dose: {}\nBoost: {}\nNotes: {}",
-                                vaccine.dosage_schedule(),
-                                vaccine.booster_schedule(),
-                                vaccine.notes()
-                            ));
+                            resp.show_tooltip_text(vaccine_summary(vaccine));
+                        }
+                        if vaccine.confidence().needs_review() {
+                            ui.weak("⚠").on_hover_text(REVIEW_WARNING);
+                        }
+                        if ui
+                            .small_button("ℹ")
+                            .on_hover_text("Explain this vaccine")
+                            .clicked()
+                        {
+                            details.toggle(ui);
+                        }
+                        if vaccine_cfg.enabled {
+                            ui.checkbox(&mut vaccine_cfg.already_immune, "Immune")
+                                .on_hover_text(
+                                    "Already immune from prior infection; skip the initial \
+                                     series and only schedule boosters",
+                                );
+                            show_snooze(ui, vaccine.name(), &mut vaccine_cfg.snooze_until, &now);
+                            show_titer_check(ui, &mut vaccine_cfg.titer_check_years);
+                            show_booster_override(ui, vaccine, &mut vaccine_cfg.booster_override);
+                            if let Some(reason) = unscheduled.get(vaccine.name()) {
+                                ui.label("❔")
+                                    .on_hover_text(format!("Why is nothing planned? {reason}"));
+                            }
+                            for caution in vaccine.cautions(&contraindicated) {
+                                if let Some(advice) = caution.advice() {
+                                    ui.label("⚠").on_hover_text(advice);
+                                }
+                            }
                         }
                     });
                 });
+                details.show_body_indented(&row, ui, |ui| {
+                    let gaps_from = show_dose_gaps.then_some(&now);
+                    show_vaccine_details(ui, vaccine, records, schedule, labels, gaps_from);
+                });
             },
         );
         if let Some(update) = response.update {
-            self.profile_mut().vaccines.swap(update.from, update.to);
+            // The list may be filtered, so map the positions back onto the full list.
+            if let (Some(&from), Some(&to)) = (
+                visible.get(update.from),
+                visible.get(update.to).or(visible.last()),
+            ) {
+                self.profile_mut().vaccines.swap(from, to);
+            }
         }
 
-        // Select end plan year
+        // Don't plan a combination vaccine alongside its own components.
+        let changes = self.sync_combinations();
+        if !changes.is_empty() {
+            self.combination_notice = Some(
+                changes
+                    .iter()
+                    .map(|(off, kept)| format!("Turned off {off}: covered by {kept}."))
+                    .join(" "),
+            );
+        }
+        if let Some(notice) = &self.combination_notice {
+            let mut dismissed = false;
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, notice)
+                    .on_hover_text(
+                        "Change which is kept under Preferences, \"Combination vaccines\".",
+                    );
+                dismissed = ui.small_button("OK").clicked();
+            });
+            if dismissed {
+                self.combination_notice = None;
+            }
+        }
+
+        // Date of birth, to hold off on vaccines until they are age appropriate.
+        let previous_dob = self.profile().date_of_birth;
         ui.horizontal(|ui| {
-            let year = Zoned::now().year();
-            let r0 = ui.label("End plan year:");
-            let r1 = ui.add(egui::Slider::new(
-                &mut self.profile_mut().end_plan_year,
-                year..=year + 100,
-            ));
-            for resp in [r0, r1].iter() {
-                if resp.hovered() {
-                    resp.show_tooltip_text("When to stop scheduling vaccines.")
+            let mut known = self.profile().date_of_birth.is_some();
+            ui.checkbox(&mut known, "Date of birth:");
+            let dob = &mut self.profile_mut().date_of_birth;
+            match (known, dob.as_mut()) {
+                (true, Some(dob)) => {
+                    let mut date = NaiveDate::from_ymd_opt(
+                        dob.year().into(),
+                        dob.month() as u32,
+                        dob.day() as u32,
+                    )
+                    .unwrap();
+                    ui.add(
+                        egui_extras::DatePickerButton::new(&mut date)
+                            .id_salt("date_of_birth")
+                            .show_icon(true),
+                    );
+                    *dob = jiffdate(date.year() as i16, date.month() as i8, date.day() as i8);
                 }
+                (true, None) => *dob = Some(jiffdate(1990, 1, 1)),
+                (false, _) => *dob = None,
             }
         });
+        let known = self.profile().date_of_birth.is_some();
+        let assume = &mut self.profile_mut().assume_childhood_vaccines;
+        let toggled = ui
+            .add_enabled(
+                known,
+                egui::Checkbox::new(assume, "Assume standard childhood vaccines"),
+            )
+            .on_hover_text(CHILDHOOD_BASELINE_HELP)
+            .on_disabled_hover_text("Needs a date of birth")
+            .changed();
+        if toggled || self.profile().date_of_birth != previous_dob {
+            self.commit_record_edit();
+            self.profile_mut().sync_childhood_baseline()?;
+        }
 
-        ui.separator();
+        ui.label("Notes:");
+        ui.add(
+            egui::TextEdit::multiline(&mut self.profile_mut().notes)
+                .hint_text("Medical context to keep with this profile, e.g. \"immunocompromised, consult before live vaccines\"")
+                .desired_rows(2),
+        );
 
-        // Re-compute the schedule
-        // TODO: only do this if something changed? Probably not worth bothering.
-        self.profile_mut().schedule = Vaccine::schedule(
-            &Zoned::now(),
-            self.profile()
-                .vaccines
-                .iter()
-                .filter(|v| v.enabled)
-                .map(|v| v.name.clone()),
-            self.profile().end_plan_year,
-            &self.profile().records,
-        )?;
+        // Select end plan year
+        let year = self.now().year();
+        self.profile_mut().sync_end_plan_year();
+        if self.profile().end_plan_year < year {
+            self.end_plan_notice = Some(format!(
+                "The end plan year was {}, which is in the past; moved it to {year}.",
+                self.profile().end_plan_year
+            ));
+            self.profile_mut().end_plan_year = year;
+        }
+        if let Some(notice) = &self.end_plan_notice {
+            ui.colored_label(ui.visuals().warn_fg_color, notice);
+        }
+        if let Some(notice) = &self.vaccine_list_notice {
+            let mut dismissed = false;
+            ui.horizontal_wrapped(|ui| {
+                ui.label(notice);
+                dismissed = ui.small_button("OK").clicked();
+            });
+            if dismissed {
+                self.vaccine_list_notice = None;
+            }
+        }
+        let mut end_changed = false;
+        ui.horizontal(|ui| {
+            let profile = self.profile_mut();
+            let end_plan_year = profile.end_plan_year;
+            let (r0, r1) = match (profile.date_of_birth, profile.plan_to_age.as_mut()) {
+                (Some(dob), Some(age)) => (
+                    ui.label("Plan to age:"),
+                    ui.add(
+                        egui::Slider::new(age, (year - dob.year()).max(0)..=120)
+                            .suffix(format!(" (until {end_plan_year})")),
+                    ),
+                ),
+                (dob, _) => {
+                    let mut slider =
+                        egui::Slider::new(&mut profile.end_plan_year, year..=year + 100);
+                    if let Some(dob) = dob {
+                        slider = slider.suffix(format!(" (age {})", end_plan_year - dob.year()));
+                    }
+                    (ui.label("End plan year:"), ui.add(slider))
+                }
+            };
+            end_changed = r1.changed();
+            for resp in [r0, r1].iter() {
+                if resp.hovered() {
+                    resp.show_tooltip_text("When to stop scheduling vaccines.")
+                }
+            }
+            if let Some(dob) = profile.date_of_birth {
+                let mut by_age = profile.plan_to_age.is_some();
+                ui.checkbox(&mut by_age, "By age")
+                    .on_hover_text("Set the end of the plan from an age rather than a year.");
+                match (by_age, profile.plan_to_age) {
+                    (true, None) => profile.plan_to_age = Some(end_plan_year - dob.year()),
+                    (false, Some(_)) => profile.plan_to_age = None,
+                    _ => {}
+                }
+            }
+        });
+        // The notice is about the year that was loaded; once it is set by hand it no longer
+        // applies.
+        if end_changed {
+            self.end_plan_notice = None;
+        }
+
+        // Optionally plan boosters over a shorter window than the rest of the schedule
+        ui.horizontal(|ui| {
+            let horizon = &mut self.profile_mut().booster_horizon_years;
+            let mut limited = horizon.is_some();
+            ui.checkbox(&mut limited, "Only plan boosters for the next")
+                .on_hover_text("Show the full initial series through the end year, but keep boosters to a shorter window.");
+            match (limited, horizon.as_mut()) {
+                (true, Some(years)) => {
+                    ui.add(egui::Slider::new(years, 1..=50).suffix(" years"));
+                }
+                (true, None) => *horizon = Some(10),
+                (false, _) => *horizon = None,
+            }
+        });
+
+        // Optionally pin appointments to a day, e.g. to line up with paydays or clinic hours
+        ui.horizontal(|ui| {
+            let day = &mut self.profile_mut().preferred_day_of_month;
+            let mut pinned = day.is_some();
+            ui.checkbox(&mut pinned, "Plan appointments on day")
+                .on_hover_text("Put appointments on this day of the month instead of just the month. Short months use their last day.");
+            match (pinned, day.as_mut()) {
+                (true, Some(day)) => {
+                    ui.add(egui::Slider::new(day, 1..=31));
+                }
+                (true, None) => *day = Some(1),
+                (false, _) => *day = None,
+            }
+        });
+
+        // Select minimum spacing between visits
+        ui.horizontal(|ui| {
+            let r0 = ui.label("Months between visits:");
+            let r1 = ui.add(egui::Slider::new(
+                &mut self.profile_mut().min_appointment_gap_months,
+                0..=12,
+            ));
+            for resp in [r0, r1].iter() {
+                if resp.hovered() {
+                    resp.show_tooltip_text("Push separate clinic visits apart so that they are at least this many months apart. Doses of the same vaccine move together to keep their intervals.")
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Plan doses:");
+            let mode = &mut self.profile_mut().planning_mode;
+            egui::ComboBox::from_id_salt("planning_mode")
+                .selected_text(mode.to_string())
+                .show_ui(ui, |ui| {
+                    for option in PlanningMode::all() {
+                        ui.selectable_value(mode, option, option.to_string());
+                    }
+                })
+                .response
+                .on_hover_text("Some series allow a range of months between doses. Fewest visits waits as long as allowed and shares visits with other vaccines where it can.");
+        });
+
+        let resp = ui.checkbox(
+            &mut self.profile_mut().no_prior_flu_exposure,
+            "No prior flu vaccination or infection",
+        );
+        if resp.hovered() {
+            resp.show_tooltip_text(
+                "Children under 9 getting their first flu vaccine get a second dose four weeks later.",
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.label("Hemisphere:");
+            let hemisphere = &mut self.profile_mut().hemisphere;
+            egui::ComboBox::from_id_salt("hemisphere")
+                .selected_text(hemisphere.to_string())
+                .show_ui(ui, |ui| {
+                    for option in Hemisphere::all() {
+                        ui.selectable_value(hemisphere, option, option.to_string());
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Seasonal vaccines are planned for the start of the local flu season",
+                );
+        });
+
+        self.show_health_answers(ui);
+        self.show_coadministration(ui);
+        self.show_antibody_events(ui);
+        self.show_exposures(ui);
+
+        ui.separator();
+
+        // Re-compute the schedule if anything it depends on changed.
+        if self.refresh_schedule()? {
+            self.replanned_at = Some(ui.input(|i| i.time));
+        }
         Ok(())
     }
 
-    fn show_schedule_section(&self, ui: &mut egui::Ui) -> Result<()> {
-        // Show the current schedule
-        let profile = &self.profiles[&self.active_profile];
-        let now = Zoned::now();
-        let year = now.year();
-        let month = now.month();
-        for y in year..profile.end_plan_year {
-            if profile.schedule.iter().any(|appt| appt.year() == y) {
-                ui.heading(egui::RichText::new(format!("{}", y)).underline().strong());
-            }
-            for mo in month..month + 12 {
-                if profile
-                    .schedule
-                    .iter()
-                    .any(|appt| appt.year() == y && appt.month() == mo)
+    fn show_coadministration(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Co-administration").show(ui, |ui| {
+            ui.label("Vaccines are assumed safe to give at the same visit unless listed here. Different live vaccines are always kept at least 2 months apart unless they share a visit.");
+            let mut removal = None;
+            for (i, (a, b)) in self.profile().coadministration.pairs().iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button("Remove").clicked() {
+                        removal = Some(i);
+                    }
+                    ui.label(format!("{a} and {b} must be given separately"));
+                });
+            }
+            if let Some(i) = removal {
+                self.profile_mut().coadministration.remove(i);
+            }
+            let vaccine_names = Vaccine::get_vaccines().keys().copied().sorted().collect_vec();
+            ui.horizontal(|ui| {
+                let (a, b) = &mut self.add_separate_pair;
+                egui::ComboBox::from_id_salt("separate_a")
+                    .wrap_mode(TextWrapMode::Extend)
+                    .show_index(ui, a, vaccine_names.len(), |i| vaccine_names[i]);
+                ui.label("and");
+                egui::ComboBox::from_id_salt("separate_b")
+                    .wrap_mode(TextWrapMode::Extend)
+                    .show_index(ui, b, vaccine_names.len(), |i| vaccine_names[i]);
+                if ui.button("Keep Separate").clicked() {
+                    let (a, b) = self.add_separate_pair;
+                    self.profile_mut()
+                        .coadministration
+                        .separate(vaccine_names[a], vaccine_names[b]);
+                }
+            });
+            ui.checkbox(
+                &mut self.profile_mut().spread_within_month,
+                "Separate them by a week or more within the month",
+            )
+            .on_hover_text("Rather than moving one to a later month. Live vaccines still go a month apart. Appointments in those months get a day.");
+        });
+    }
+
+    fn show_antibody_events(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Antibody products").show(ui, |ui| {
+            ui.label("Immune globulin, blood transfusions, and some antibody treatments can keep live vaccines from working. Live vaccines are planned after the wait listed for each.");
+            let mut removal = None;
+            for (i, event) in self.profile().antibody_events.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button("Remove").clicked() {
+                        removal = Some(i);
+                    }
+                    ui.label(format!(
+                        "{} on {}: wait {} months",
+                        event.product, event.date, event.deferral_mo
+                    ));
+                });
+            }
+            if let Some(i) = removal {
+                self.profile_mut().antibody_events.remove(i);
+            }
+            ui.horizontal(|ui| {
+                let event = &mut self.add_antibody_event;
+                ui.add(
+                    egui::TextEdit::singleline(&mut event.product)
+                        .hint_text("Product")
+                        .desired_width(120.0),
+                );
+                let mut date = NaiveDate::from_ymd_opt(
+                    event.date.year().into(),
+                    event.date.month() as u32,
+                    event.date.day() as u32,
+                )
+                .unwrap();
+                ui.add(
+                    egui_extras::DatePickerButton::new(&mut date)
+                        .id_salt("antibody_event_date")
+                        .show_icon(true),
+                );
+                event.date = jiffdate(date.year() as i16, date.month() as i8, date.day() as i8);
+                ui.add(egui::Slider::new(&mut event.deferral_mo, 1..=12).suffix(" months"))
+                    .on_hover_text("The wait depends on the product and dose; ask your provider.");
+                if ui
+                    .add_enabled(!event.product.trim().is_empty(), egui::Button::new("Add"))
+                    .clicked()
                 {
-                    let tmp = jiff::civil::date(y, mo, 1);
-                    ui.heading(format!("{}", tmp.strftime("%B")));
+                    let event = event.clone();
+                    self.profile_mut().antibody_events.push(event);
+                    self.add_antibody_event.product.clear();
                 }
-                for appt in &profile.schedule {
-                    if appt.year() == y && appt.month() == mo {
-                        ui.label(format!("    {} {}", appt.vaccine(), appt.kind()));
+            });
+        });
+    }
+
+    fn show_exposures(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Exposures").show(ui, |ui| {
+            ui.label("Some vaccines should be boosted after an exposure, such as a dirty wound for tetanus. A booster is planned right away for each vaccine that treats the disease, unless you have had one since. Exposures more than a few months old, and seasonal vaccines, are left alone.");
+            let mut removal = None;
+            for (i, exposure) in self.profile().exposures.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button("Remove").clicked() {
+                        removal = Some(i);
                     }
+                    ui.label(format!("{} on {}", exposure.disease, exposure.date));
+                });
+            }
+            if let Some(i) = removal {
+                self.profile_mut().exposures.remove(i);
+            }
+            ui.horizontal(|ui| {
+                let exposure = &mut self.add_exposure;
+                egui::ComboBox::from_id_salt("exposure_disease")
+                    .selected_text(exposure.disease.as_str())
+                    .show_ui(ui, |ui| {
+                        for disease in Exposure::diseases() {
+                            ui.selectable_value(&mut exposure.disease, disease.to_owned(), disease);
+                        }
+                    });
+                let mut date = NaiveDate::from_ymd_opt(
+                    exposure.date.year().into(),
+                    exposure.date.month() as u32,
+                    exposure.date.day() as u32,
+                )
+                .unwrap();
+                ui.add(
+                    egui_extras::DatePickerButton::new(&mut date)
+                        .id_salt("exposure_date")
+                        .show_icon(true),
+                );
+                exposure.date = jiffdate(date.year() as i16, date.month() as i8, date.day() as i8);
+                if ui.button("Add").clicked() {
+                    let exposure = exposure.clone();
+                    self.profile_mut().exposures.push(exposure);
+                }
+            });
+        });
+    }
+
+    // Explain why there is nothing to show in the schedule, if that is the case.
+    fn empty_schedule(&self) -> Option<EmptySchedule> {
+        if !self.profile().vaccines.iter().any(|v| v.enabled) {
+            Some(EmptySchedule::NoneEnabled)
+        } else if !self.schedule_by_month().is_empty() {
+            None
+        } else if self.profile().end_plan_year <= self.now().year() {
+            Some(EmptySchedule::PlanEndsThisYear)
+        } else {
+            Some(EmptySchedule::UpToDate)
+        }
+    }
+
+    // Stop editing the current note, dropping it if it was left blank.
+    fn finish_note_edit(&mut self) {
+        if let Some(key) = self.editing_note.take() {
+            let notes = &mut self.profile_mut().appointment_notes;
+            if notes.get(&key).is_some_and(|note| note.trim().is_empty()) {
+                notes.remove(&key);
+            }
+        }
+    }
+
+    // Enabled vaccines whose series lapsed, so that it starts over.
+    fn restarting_vaccines(&self, now: &Zoned) -> Result<HashSet<String>> {
+        let profile = self.profile();
+        profile
+            .vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .map(|v| {
+                let vaccine = &Vaccine::get_vaccines()[v.name.as_str()];
+                let records = profile.records.iter().filter(|r| r.vaccine() == v.name);
+                Ok((v.name.clone(), vaccine.restart_recommended(now, records)?))
+            })
+            .filter_ok(|(_, restart)| *restart)
+            .map_ok(|(name, _)| name)
+            .collect()
+    }
+
+    // How pressing each planned appointment is, for highlighting.
+    fn appointment_urgencies(
+        &self,
+        now: &Zoned,
+        restarting: &HashSet<String>,
+    ) -> Result<HashMap<AppointmentKey, Urgency>> {
+        let profile = self.profile();
+        let months_overdue = profile
+            .vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .map(|v| {
+                let vaccine = &Vaccine::get_vaccines()[v.name.as_str()];
+                let records = profile.records.iter().filter(|r| r.vaccine() == v.name);
+                Ok((v.name.clone(), vaccine.months_overdue(now, records)?))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(profile
+            .schedule
+            .iter()
+            .map(|appt| {
+                // A restarted series plans doses again that are already on record.
+                let completed = matches!(appt.kind(), DoseKind::Dose(_))
+                    && !restarting.contains(appt.vaccine())
+                    && profile.records.iter().any(|r| {
+                        r.vaccine() == appt.vaccine() && r.counted_kind() == Some(appt.kind())
+                    });
+                let overdue = months_overdue.get(appt.vaccine()).copied().unwrap_or(0);
+                let urgency = appt.urgency(
+                    now,
+                    overdue,
+                    self.overdue_grace_months,
+                    self.reminder_lead_months,
+                    completed,
+                );
+                (appointment_key(appt), urgency)
+            })
+            .collect())
+    }
+
+    // What focus mode shows: planned appointments that are overdue or due within the reminder
+    // lead time, going by `urgencies` from `appointment_urgencies`, and doses on record that have
+    // to be repeated.
+    fn actionable(
+        &self,
+        urgencies: &HashMap<AppointmentKey, Urgency>,
+    ) -> (Vec<&VaccineAppointment>, Vec<&VaccineRecord>) {
+        let appointments = self
+            .schedule_by_month()
+            .into_iter()
+            .flat_map(|(_, appts)| appts)
+            .filter(|appt| {
+                matches!(
+                    urgencies.get(&appointment_key(appt)),
+                    Some(Urgency::Overdue | Urgency::DueSoon)
+                )
+            })
+            .collect();
+        let records = self
+            .profile()
+            .records
+            .iter()
+            .filter(|r| r.invalid())
+            .collect();
+        (appointments, records)
+    }
+
+    // The focus mode layout: just what needs doing soon.
+    fn show_focus_section(&mut self, ui: &mut egui::Ui) -> Result<()> {
+        if self.refresh_schedule()? {
+            self.replanned_at = Some(ui.input(|i| i.time));
+        }
+        ui.heading("Needs attention");
+        let now = self.now();
+        let urgencies = self.appointment_urgencies(&now, &self.restarting_vaccines(&now)?)?;
+        let (appointments, records) = self.actionable(&urgencies);
+        if appointments.is_empty() && records.is_empty() {
+            ui.label("Nothing needs doing right now.");
+        }
+        for appt in appointments {
+            let mut text = egui::RichText::new(format!(
+                "{} — {} {}",
+                appt.date().strftime("%B %Y"),
+                appt.vaccine(),
+                appt.label(&self.dose_labels)
+            ));
+            if let Some(color) = urgencies
+                .get(&appointment_key(appt))
+                .and_then(|urgency| urgency_color(ui.visuals(), *urgency))
+            {
+                text = text.color(color);
+            }
+            ui.label(text);
+        }
+        for record in records {
+            ui.label(format!(
+                "{} {} from {} was invalid and has to be repeated.",
+                record.vaccine(),
+                record.kind().label(&self.dose_labels),
+                record.date_label()
+            ))
+            .on_hover_text(INVALID_DOSE_HELP);
+        }
+        ui.separator();
+        ui.weak("Focus mode hides the rest of the plan and settings.");
+        Ok(())
+    }
+
+    fn show_schedule_section(&mut self, ui: &mut egui::Ui) -> Result<()> {
+        let mut lifted = None;
+        for (contraindication, vaccines) in self.profile().held_off() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Not planned: {}. {}",
+                    vaccines.join(", "),
+                    Unscheduled::Contraindicated(contraindication)
+                ));
+                if ui
+                    .button("No Longer Applies")
+                    .on_hover_text("Answer no and plan these again")
+                    .clicked()
+                {
+                    lifted = Some(contraindication);
+                }
+            });
+        }
+        if let Some(contraindication) = lifted {
+            self.profile_mut()
+                .contraindications
+                .insert(contraindication, false);
+        }
+        match self.empty_schedule() {
+            Some(EmptySchedule::NoneEnabled) => {
+                ui.label("No vaccines selected — enable some above to build a plan.");
+            }
+            Some(EmptySchedule::PlanEndsThisYear) => {
+                ui.label(format!(
+                    "Nothing is due for the selected vaccines before the plan ends in {}. Move the end plan year out to plan further ahead.",
+                    self.profile().end_plan_year
+                ));
+            }
+            Some(EmptySchedule::UpToDate) => {
+                ui.label(format!(
+                    "You're up to date: nothing is due for the selected vaccines before {}.",
+                    self.profile().end_plan_year
+                ));
+            }
+            None => {}
+        }
+
+        let planned = self
+            .schedule_by_month()
+            .into_iter()
+            .flat_map(|(_, appts)| appts)
+            .cloned()
+            .collect_vec();
+        if !planned.is_empty()
+            && ui
+                .button("Copy Plan as Text")
+                .on_hover_text("Copy a short month-by-month summary for pasting into a message")
+                .clicked()
+        {
+            ui.ctx().copy_text(text::plan_to_text(&planned, |appt| {
+                self.export_filter.keeps_appointment(appt)
+            }));
+        }
+        if !planned.is_empty()
+            && ui
+                .button("Show Timeline")
+                .on_hover_text("See the plan laid out over the years, one row per vaccine")
+                .clicked()
+        {
+            self.show_timeline = true;
+        }
+
+        // Show the current schedule
+        let now = self.now();
+        let profile = self.profile();
+        let restarting = self.restarting_vaccines(&now)?;
+        let urgencies = self.appointment_urgencies(&now, &restarting)?;
+
+        // How late each remaining dose can go, for series that leave some slack.
+        let mut windows = HashMap::new();
+        for v in profile.vaccines.iter().filter(|v| v.enabled) {
+            let vaccine = &Vaccine::get_vaccines()[v.name.as_str()];
+            for (kind, earliest, latest) in vaccine.dose_windows(&now, &profile.records)? {
+                if latest != earliest {
+                    windows.insert((v.name.clone(), kind), latest);
                 }
             }
         }
+
+        let shots = visit_injections(&self.profile().schedule)
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let months = self
+            .schedule_by_month()
+            .into_iter()
+            .map(|(ym, appts)| {
+                let day = appts.first().and_then(|a| a.day().map(|_| a.date().day()));
+                // Only worth mentioning when some dose there takes more than one shot.
+                let shots = shots
+                    .get(&ym)
+                    .copied()
+                    .filter(|n| *n as usize != appts.len());
+                (
+                    ym,
+                    day,
+                    shots,
+                    appts
+                        .into_iter()
+                        .map(|a| (appointment_key(a), a.label(&self.dose_labels)))
+                        .collect_vec(),
+                )
+            })
+            .collect_vec();
+        let reacted = self
+            .profile()
+            .records
+            .iter()
+            .filter(|r| r.reaction().is_some())
+            .map(|r| r.vaccine().to_owned())
+            .collect::<HashSet<_>>();
+        let date_of_birth = self.profile().date_of_birth.filter(|_| self.show_ages);
+        let mut current_year = None;
+        let mut heading_age = None;
+        for ((y, mo), day, shots, appts) in months {
+            let age = date_of_birth.and_then(|dob| age_in_month(dob, y, mo));
+            if current_year != Some(y) {
+                let heading = match age {
+                    Some(age) => format!("{y} — age {age}"),
+                    None => format!("{y}"),
+                };
+                ui.heading(egui::RichText::new(heading).underline().strong());
+                current_year = Some(y);
+                heading_age = age;
+            }
+            let tmp = jiff::civil::date(y, mo, 1);
+            let mut heading = tmp.strftime("%B").to_string();
+            if let Some(day) = day {
+                heading.push_str(&format!(" {day}"));
+            }
+            // Only repeat the age once a birthday has passed since the year heading.
+            if let Some(age) = age.filter(|age| Some(*age) != heading_age) {
+                heading.push_str(&format!(" — age {age}"));
+            }
+            if let Some(shots) = shots {
+                heading.push_str(&format!(" — {shots} injections"));
+            }
+            ui.heading(heading);
+            for (key, label) in appts {
+                ui.horizontal(|ui| {
+                    ui.add_space(16.0);
+                    ui.label(
+                        egui::RichText::new("●").color(timeline::vaccine_color(ui.visuals(), &key.0)),
+                    );
+                    let mut text = egui::RichText::new(format!("{} {label}", key.0));
+                    // Not a shot, so set it apart from the doses.
+                    if key.1 == DoseKind::TiterCheck {
+                        text = text.italics();
+                    }
+                    if let Some(color) = urgencies
+                        .get(&key)
+                        .and_then(|urgency| urgency_color(ui.visuals(), *urgency))
+                    {
+                        text = text.color(color);
+                    }
+                    let resp = ui.label(text);
+                    if let Some((ly, lm)) = windows.get(&(key.0.clone(), key.1)) {
+                        let latest = jiff::civil::date(*ly, *lm, 1).strftime("%B %Y");
+                        if (y, mo) <= (*ly, *lm) {
+                            resp.on_hover_text(format!("Can wait until {latest}."));
+                        } else {
+                            resp.on_hover_text(format!(
+                                "Later than the longest recommended gap, which ends {latest}."
+                            ));
+                        }
+                    }
+                    if restarting.contains(&key.0) && key.1 == DoseKind::Dose(0) {
+                        ui.label("↺").on_hover_text(
+                            "It has been too long since your last dose of this series, \
+                             so it starts over from the first dose.",
+                        );
+                    }
+                    if reacted.contains(&key.0) {
+                        ui.label("⚠").on_hover_text(
+                            "You recorded a reaction to an earlier dose of this vaccine. \
+                             Mention it to your provider before getting this one.",
+                        );
+                    }
+                    if self.editing_note.as_ref() == Some(&key) {
+                        let notes = &mut self.profile_mut().appointment_notes;
+                        ui.text_edit_singleline(notes.entry(key.clone()).or_default());
+                        if ui.small_button("Done").clicked() {
+                            self.finish_note_edit();
+                        }
+                    } else {
+                        if let Some(note) = self.profile().appointment_notes.get(&key) {
+                            ui.label(egui::RichText::new(note).italics());
+                        }
+                        if ui
+                            .small_button("📝")
+                            .on_hover_text("Add a note to this appointment")
+                            .clicked()
+                        {
+                            self.finish_note_edit();
+                            self.editing_note = Some(key.clone());
+                        }
+                    }
+                    if ui
+                        .small_button("Skip")
+                        .on_hover_text(
+                            "I'll miss this one: plan it again later and move the rest of the series",
+                        )
+                        .clicked()
+                    {
+                        let skipped = self
+                            .profile()
+                            .schedule
+                            .iter()
+                            .find(|a| appointment_key(a) == key)
+                            .cloned();
+                        self.profile_mut().skipped.extend(skipped);
+                    }
+                });
+            }
+        }
+
+        let skipped = self.profile().skipped.clone();
+        if !skipped.is_empty() {
+            ui.separator();
+            ui.label(egui::RichText::new("Skipped appointments").strong());
+            for (i, appt) in skipped.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} {} ({})",
+                        appt.vaccine(),
+                        appt.kind().label(&self.dose_labels),
+                        jiff::civil::date(appt.year(), appt.month(), 1).strftime("%B %Y")
+                    ));
+                    if ui.small_button("Restore").clicked() {
+                        self.profile_mut().skipped.remove(i);
+                    }
+                });
+            }
+        }
+
+        let orphaned = self.profile().orphaned_notes();
+        if !orphaned.is_empty() {
+            ui.separator();
+            ui.label(egui::RichText::new("Notes for appointments that have moved").strong());
+            for (key, note) in orphaned {
+                let (vaccine, kind, y, mo) = &key;
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{vaccine} {} ({}): {note}",
+                        kind.label(&self.dose_labels),
+                        jiff::civil::date(*y, *mo, 1).strftime("%B %Y")
+                    ));
+                    if let Some(target) = self.profile().moved_appointment(&key) {
+                        let label = format!(
+                            "Move to {}",
+                            jiff::civil::date(target.year(), target.month(), 1).strftime("%B %Y")
+                        );
+                        if ui.small_button(label).clicked() {
+                            self.profile_mut().reattach_note(&key);
+                        }
+                    }
+                    if ui.small_button("Delete").clicked() {
+                        self.profile_mut().appointment_notes.remove(&key);
+                    }
+                });
+            }
+        }
         Ok(())
     }
 
+    // Copy the selected records of the source profile into the active one, skipping any it
+    // already has. Returns how many were copied.
+    fn copy_selected_records(&mut self) -> usize {
+        let Some((source, selected)) = self.copy_records.take() else {
+            return 0;
+        };
+        let Some(from) = self.profiles.get(&source) else {
+            return 0;
+        };
+        let records = from
+            .records
+            .iter()
+            .zip(selected)
+            .filter(|(_, selected)| *selected)
+            .map(|(record, _)| record.clone())
+            .collect_vec();
+        self.commit_record_edit();
+        let copied = self.profile_mut().merge_records(records);
+        self.import_message = Some(format!("Copied {copied} records from {source}."));
+        copied
+    }
+
+    fn show_copy_records(&mut self, ui: &mut egui::Ui) {
+        let Some((source, selected)) = &mut self.copy_records else {
+            return;
+        };
+        let Some(from) = self.profiles.get(source.as_str()) else {
+            self.copy_records = None;
+            return;
+        };
+        let existing = &self.profiles[&self.active_profile].records;
+        ui.separator();
+        ui.label(format!(
+            "Copy records from {source} to {}:",
+            self.active_profile
+        ));
+        egui::ScrollArea::vertical()
+            .id_salt("copy_records_scroll")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for (record, selected) in from.records.iter().zip(selected.iter_mut()) {
+                    let have = existing.contains(record);
+                    let text = format!(
+                        "{} {} {}",
+                        record.date_label(),
+                        record.vaccine(),
+                        record.kind()
+                    );
+                    ui.add_enabled_ui(!have, |ui| {
+                        ui.checkbox(selected, text)
+                            .on_disabled_hover_text("Already in this profile");
+                    });
+                }
+            });
+        let mut copy = false;
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            copy = ui
+                .add_enabled(selected.contains(&true), egui::Button::new("Copy Selected"))
+                .clicked();
+            cancel = ui.button("Cancel").clicked();
+        });
+        if copy {
+            self.copy_selected_records();
+        } else if cancel {
+            self.copy_records = None;
+        }
+    }
+
     fn show_profile_list(&mut self, ctx: &egui::Context) {
+        let mut add = false;
+        let mut open = self.show_profiles;
         egui::Window::new("Profiles")
-            .open(&mut self.show_profiles)
+            .open(&mut open)
             .show(ctx, |ui| {
                 ui.heading("Profile Management");
                 ui.label("Profile data is saved to your machine locally. Deletion is immediate, irreversible, and has no confirmation prompt.");
@@ -404,6 +3362,7 @@ impl VaccineHelperApp {
                     ui.horizontal(|ui| {
                         ui.add_enabled_ui(!is_active_row, |ui| {
                             if ui.button("Activate").clicked() {
+                                self.commit_record_edit();
                                 self.active_profile = name.clone();
                             }
                         });
@@ -412,6 +3371,16 @@ impl VaccineHelperApp {
                                 self.profiles.remove(&name);
                             }
                         });
+                        let records = self.profiles.get(&name).map_or(0, |p| p.records.len());
+                        if !is_active_row
+                            && records > 0
+                            && ui
+                                .button("Copy Records...")
+                                .on_hover_text("Pick records from this profile to add to the active one")
+                                .clicked()
+                        {
+                            self.copy_records = Some((name.clone(), vec![false; records]));
+                        }
                         let mut content = egui::RichText::new(name);
                         if is_active_row {
                             content = content.strong();
@@ -419,21 +3388,105 @@ impl VaccineHelperApp {
                         ui.label(content);
                     });
                 }
+                self.show_copy_records(ui);
                 ui.separator();
-                ui.horizontal(|ui| {
-                    ui.label("Add a profile:");
-                    ui.text_edit_singleline(&mut self.add_profile_name);
-                    if ui.button("Add").clicked() {
-                        self.profiles
-                            .insert(self.add_profile_name.clone(), Profile::default());
-                        self.active_profile = self.add_profile_name.clone();
-                        self.add_profile_name = "".to_owned();
-                    }
-                });
+                ui.label("Add a profile:");
+                egui::Grid::new("add_profile_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut self.add_profile_name);
+                        ui.end_row();
+
+                        ui.label("Date of birth:");
+                        ui.horizontal(|ui| {
+                            let dob = &mut self.add_profile_date_of_birth;
+                            let mut known = dob.is_some();
+                            ui.checkbox(&mut known, "");
+                            match (known, dob.as_mut()) {
+                                (true, Some(dob)) => {
+                                    let mut date = NaiveDate::from_ymd_opt(
+                                        dob.year().into(),
+                                        dob.month() as u32,
+                                        dob.day() as u32,
+                                    )
+                                    .unwrap();
+                                    ui.add(
+                                        egui_extras::DatePickerButton::new(&mut date)
+                                            .id_salt("add_profile_date_of_birth")
+                                            .show_icon(true),
+                                    );
+                                    *dob = jiffdate(
+                                        date.year() as i16,
+                                        date.month() as i8,
+                                        date.day() as i8,
+                                    );
+                                }
+                                (true, None) => *dob = Some(jiffdate(1990, 1, 1)),
+                                (false, _) => *dob = None,
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Time zone:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.add_profile_time_zone)
+                                .hint_text("e.g. America/Denver; blank for this device's"),
+                        );
+                        ui.end_row();
+                    });
+                let name_problem = self.profile_name_problem(&self.add_profile_name);
+                if let Some(problem) = name_problem {
+                    ui.colored_label(ui.visuals().error_fg_color, problem);
+                }
+                let zone = self.add_profile_time_zone.trim();
+                let zone_ok = zone.is_empty() || TimeZone::get(zone).is_ok();
+                if !zone_ok {
+                    ui.colored_label(ui.visuals().error_fg_color, "Unknown time zone.");
+                }
+                add = ui
+                    .add_enabled(zone_ok && name_problem.is_none(), egui::Button::new("Add"))
+                    .clicked();
             });
+        self.show_profiles = open;
+        if add {
+            self.add_profile();
+        }
+    }
+
+    // Why a new profile can't be called `name`, if it can't. Adding a profile under a name that is
+    // taken would replace that profile.
+    fn profile_name_problem(&self, name: &str) -> Option<&'static str> {
+        let name = name.trim();
+        if name.is_empty() {
+            Some("Enter a name.")
+        } else if self.profiles.contains_key(name) {
+            Some("That name already exists.")
+        } else {
+            None
+        }
+    }
+
+    // Create a profile from the add profile form and switch to it.
+    fn add_profile(&mut self) {
+        if self.profile_name_problem(&self.add_profile_name).is_some() {
+            return;
+        }
+        let time_zone = self.add_profile_time_zone.trim();
+        let profile = Profile {
+            date_of_birth: self.add_profile_date_of_birth.take(),
+            time_zone: (!time_zone.is_empty()).then(|| time_zone.to_owned()),
+            ..Profile::default()
+        };
+        let name = std::mem::take(&mut self.add_profile_name).trim().to_owned();
+        self.commit_record_edit();
+        self.profiles.insert(name.clone(), profile);
+        self.active_profile = name;
+        self.add_profile_time_zone.clear();
     }
 
     fn show_preferences(&mut self, ctx: &egui::Context) {
+        let mut preview = None;
         egui::Window::new("Preferences")
             .open(&mut self.show_preferences)
             .show(ctx, |ui| {
@@ -443,28 +3496,883 @@ impl VaccineHelperApp {
                         ui.label("Night Mode:");
                         egui::widgets::global_theme_preference_buttons(ui);
                         ui.end_row();
+
+                        ui.label("Dose label:");
+                        ui.text_edit_singleline(&mut self.dose_labels.dose)
+                            .on_hover_text("{n} is replaced with the dose number");
+                        ui.end_row();
+
+                        ui.label("Booster label:");
+                        ui.text_edit_singleline(&mut self.dose_labels.booster);
+                        ui.end_row();
+
+                        ui.label("");
+                        if ui.button("Reset Labels").clicked() {
+                            self.dose_labels = DoseLabels::default();
+                        }
+                        ui.end_row();
+
+                        ui.label("Overdue after:");
+                        ui.add(
+                            egui::Slider::new(&mut self.overdue_grace_months, 0..=12)
+                                .suffix(" months late"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Due soon:");
+                        ui.add(
+                            egui::Slider::new(&mut self.reminder_lead_months, 0..=6)
+                                .suffix(" months ahead"),
+                        )
+                        .on_hover_text("Highlight appointments this far ahead, to leave time to book them");
+                        ui.end_row();
+
+                        ui.label("New vaccines:");
+                        ui.checkbox(&mut self.enable_new_vaccines, "Enable when recommended")
+                            .on_hover_text(
+                                "Turn on recommended vaccines that an update adds to the list",
+                            );
+                        ui.end_row();
+
+                        ui.label("Exports include:");
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.export_filter.include_boosters, "Boosters")
+                                .on_hover_text("Leave boosters out to share just the initial series");
+                            ui.checkbox(&mut self.export_filter.include_records, "Records")
+                                .on_hover_text("Include doses already received in the records CSV and card; plan exports only have planned doses");
+                        });
+                        ui.end_row();
+
+                        ui.label("Show dose spacing:");
+                        ui.checkbox(&mut self.show_dose_gaps, "").on_hover_text(
+                            "Show how many months apart planned doses are in each vaccine's details",
+                        );
+                        ui.end_row();
+
+                        ui.label("Show ages:");
+                        ui.checkbox(&mut self.show_ages, "").on_hover_text(
+                            "Show the patient's age in the schedule when a date of birth is set",
+                        );
+                        ui.end_row();
+
+                        ui.label("Preview as of:");
+                        ui.horizontal(|ui| {
+                            let mut previewing = self.now_override.is_some();
+                            ui.checkbox(&mut previewing, "").on_hover_text(
+                                "See the plan as of another date. Records are not changed.",
+                            );
+                            match (previewing, &self.now_override) {
+                                (true, Some(now)) => {
+                                    let mut date = NaiveDate::from_ymd_opt(
+                                        now.year().into(),
+                                        now.month() as u32,
+                                        now.day() as u32,
+                                    )
+                                    .unwrap();
+                                    ui.add(
+                                        egui_extras::DatePickerButton::new(&mut date)
+                                            .id_salt("preview_date")
+                                            .show_icon(true),
+                                    );
+                                    let picked = jiffdate(
+                                        date.year() as i16,
+                                        date.month() as i8,
+                                        date.day() as i8,
+                                    );
+                                    if picked != now.date() {
+                                        preview = Some(Some(picked));
+                                    }
+                                }
+                                (true, None) => preview = Some(Some(tz::now().date())),
+                                (false, Some(_)) => preview = Some(None),
+                                (false, None) => {}
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Combination vaccines:");
+                        ui.checkbox(&mut self.prefer_combined, "Prefer")
+                            .on_hover_text(
+                                "When a combination vaccine and its components are both enabled, \
+                             keep the combination. Otherwise keep the components.",
+                            );
+                        ui.end_row();
                     });
             });
+        if let Some(date) = preview {
+            self.set_preview(date);
+        }
     }
 
-    fn show_about(&mut self, ctx: &egui::Context) {
-        egui::Window::new("About")
-            .open(&mut self.show_about)
-            .show(ctx, |ui| {
-                ui.heading("Warning");
-                ui.separator();
-                ui.label("Usage of this (extremely simple) tool does not constitute medical advice. Please consult a doctor or pharmacist before putting anything in your body.");
-                ui.label("");
+    fn show_simulation(&mut self, ctx: &egui::Context) -> Result<()> {
+        let Some(sim) = &self.simulation else {
+            return Ok(());
+        };
+        let span = self.simulation_span();
+        let start = sim.start.clone();
+        let mut months = sim.months;
+        let mut playing = sim.playing;
+        let mut last_step = sim.last_step;
 
-                ui.heading("General Safety Information");
-                ui.separator();
-                ui.label("However, there is overwhelming evidence that vaccines are both safe and effective. Vaccines are so effective that we can't study the long-term effectiveness because there's nobody sick left to study.");
-                ui.label("");
-                ui.label("The odds of a severe or worse vaccine reaction are, like shark attacks and lightning strikes, low enough that computing accurate odds is impossible. Many vaccines are safe to the limits of our ability to detect, with no reported severe reactions on record.");
+        let time = ctx.input(|i| i.time);
+        if playing && time - last_step >= SIMULATION_STEP_SECS {
+            months += 1;
+            last_step = time;
+            playing = months < span;
+        }
+        if playing {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(SIMULATION_STEP_SECS));
+        }
+
+        if self.refresh_schedule()? {
+            self.replanned_at = Some(time);
+        }
+        let now = self.now();
+        let urgencies = self.appointment_urgencies(&now, &self.restarting_vaccines(&now)?)?;
+        let (due, _) = self.actionable(&urgencies);
+        let due = due
+            .iter()
+            .map(|appt| format!("{} {}", appt.vaccine(), appt.label(&self.dose_labels)))
+            .collect_vec();
+
+        let at = start
+            .checked_add(jiff::Span::new().months(months))?
+            .strftime("%B %Y")
+            .to_string();
+        let mut open = true;
+        egui::Window::new("Simulate Aging")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new("Simulation only")
+                        .strong()
+                        .color(ui.visuals().warn_fg_color),
+                );
+                ui.label("Slide through the years to see when each vaccine comes due, assuming no shots are recorded along the way. Your records are unchanged.");
+                ui.add(
+                    egui::Slider::new(&mut months, 0..=span)
+                        .show_value(false)
+                        .text(&at),
+                );
+                ui.horizontal(|ui| {
+                    let label = if playing { "⏸ Pause" } else { "▶ Play" };
+                    if ui.button(label).clicked() {
+                        playing = !playing;
+                        last_step = time;
+                        if playing && months >= span {
+                            months = 0;
+                        }
+                    }
+                    if ui.button("⏮ Today").clicked() {
+                        months = 0;
+                        playing = false;
+                    }
+                });
+                ui.separator();
+                if due.is_empty() {
+                    ui.label("Nothing is due then.");
+                } else {
+                    ui.label("Due then:");
+                    for line in &due {
+                        ui.label(format!("• {line}"));
+                    }
+                }
+            });
+
+        if !open {
+            self.stop_simulation();
+            return Ok(());
+        }
+        if let Some(sim) = self.simulation.as_mut() {
+            sim.playing = playing;
+            sim.last_step = last_step;
+        }
+        self.scrub_simulation(months)
+    }
+
+    fn show_coverage(&mut self, ctx: &egui::Context) {
+        if !self.show_coverage {
+            return;
+        }
+        let report = coverage::disease_coverage(&self.profile().records);
+        egui::Window::new("Disease Coverage")
+            .open(&mut self.show_coverage)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.label("Protection from the records, by disease. A disease is covered once any vaccine for it has a finished initial series.");
+                egui::Grid::new("coverage_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entry in &report {
+                            ui.label(entry.disease);
+                            let (icon, color) = match entry.coverage {
+                                Coverage::Covered => ("✔", urgency_color(ui.visuals(), Urgency::Completed)),
+                                Coverage::Partial => ("◐", Some(ui.visuals().warn_fg_color)),
+                                Coverage::NotCovered => ("✖", Some(ui.visuals().error_fg_color)),
+                            };
+                            let text = egui::RichText::new(format!("{icon} {}", entry.coverage));
+                            ui.label(match color {
+                                Some(color) => text.color(color),
+                                None => text,
+                            });
+                            ui.label(entry.vaccines.join(", "));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn show_household(&mut self, ctx: &egui::Context) {
+        if !self.show_household {
+            return;
+        }
+        let household = self.household_schedule();
+        egui::Window::new("Household Schedule")
+            .open(&mut self.show_household)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                if household.is_empty() {
+                    ui.label("No upcoming appointments for anyone in the household.");
+                }
+                for ((y, mo), appts) in &household
+                    .iter()
+                    .chunk_by(|(_, appt)| (appt.year(), appt.month()))
+                {
+                    let month = jiff::civil::date(y, mo, 1).strftime("%B %Y").to_string();
+                    let entries = appts
+                        .map(|(name, appt)| {
+                            format!(
+                                "{name}: {} {}",
+                                appt.vaccine(),
+                                appt.kind().label(&self.dose_labels)
+                            )
+                        })
+                        .join(", ");
+                    ui.label(format!("{month} — {entries}"));
+                }
+            });
+    }
+
+    fn show_import_review(&mut self, ctx: &egui::Context) {
+        let Some(review) = &mut self.import_review else {
+            return;
+        };
+        let mut finished = None;
+        egui::Window::new("Unrecognized Vaccines")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("Some imported records name vaccines that this tool doesn't know about. Choose what to do with them before they are imported.");
+                egui::Grid::new("import_review_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (name, count) in &review.unknown {
+                            ui.label(format!("\"{name}\" ({count} records)"));
+                            let action = review
+                                .actions
+                                .entry(name.clone())
+                                .or_insert(UnknownVaccineAction::Keep);
+                            egui::ComboBox::from_id_salt(("import_review", name))
+                                .selected_text(action.to_string())
+                                .show_ui(ui, |ui| {
+                                    let choices = [
+                                        UnknownVaccineAction::Keep,
+                                        UnknownVaccineAction::Drop,
+                                    ]
+                                    .into_iter()
+                                    .chain(
+                                        Vaccine::get_vaccines()
+                                            .keys()
+                                            .sorted()
+                                            .map(|v| UnknownVaccineAction::Rename(v)),
+                                    );
+                                    for choice in choices {
+                                        let text = choice.to_string();
+                                        ui.selectable_value(action, choice, text);
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    if ui.button("Import").clicked() {
+                        finished = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        finished = Some(false);
+                    }
+                });
+            });
+        match (finished, self.import_review.take()) {
+            (Some(true), Some(review)) => self.finish_import(review.pending, &review.actions),
+            (Some(false), _) => self.import_message = Some("Import cancelled.".to_owned()),
+            (None, review) => self.import_review = review,
+            (Some(true), None) => {}
+        }
+    }
+
+    // Only a fresh install, with nothing but the untouched default profile, gets the
+    // first-launch questions.
+    fn needs_onboarding(&self) -> bool {
+        !self.onboarded
+            && self.profiles.len() == 1
+            && self
+                .profiles
+                .get("Default")
+                .is_some_and(|p| p.records.is_empty() && p.date_of_birth.is_none())
+    }
+
+    // Apply the answers to the default profile. Returns whether to go on to import records.
+    fn finish_onboarding(&mut self) -> Result<bool> {
+        self.onboarded = true;
+        let Some(answers) = self.onboarding.take() else {
+            return Ok(false);
+        };
+        let today = self.now().date();
+        self.profile_mut().apply_onboarding(&answers, today)?;
+        self.recompute_schedule()?;
+        Ok(answers.has_records)
+    }
+
+    fn show_onboarding(&mut self, ctx: &egui::Context) {
+        let Some(onboarding) = &mut self.onboarding else {
+            return;
+        };
+        let mut finish = false;
+        let mut skip = false;
+        egui::Window::new("Welcome")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "A few questions to set up your plan ({} of {}).",
+                    onboarding.step + 1,
+                    Onboarding::STEPS
+                ));
+                ui.separator();
+                match onboarding.step {
+                    0 => {
+                        ui.label("When were you born? This keeps vaccines from being planned before you are old enough for them.");
+                        let mut known = onboarding.date_of_birth.is_some();
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut known, "Date of birth:");
+                            if known {
+                                let dob = onboarding
+                                    .date_of_birth
+                                    .unwrap_or_else(|| jiffdate(1990, 1, 1));
+                                let mut date = NaiveDate::from_ymd_opt(
+                                    dob.year().into(),
+                                    dob.month() as u32,
+                                    dob.day() as u32,
+                                )
+                                .unwrap();
+                                ui.add(
+                                    egui_extras::DatePickerButton::new(&mut date)
+                                        .id_salt("onboarding_dob")
+                                        .show_icon(true),
+                                );
+                                onboarding.date_of_birth = Some(jiffdate(
+                                    date.year() as i16,
+                                    date.month() as i8,
+                                    date.day() as i8,
+                                ));
+                            } else {
+                                onboarding.date_of_birth = None;
+                            }
+                        });
+                    }
+                    1 => {
+                        ui.label("Where do you live? Flu season, and so the yearly vaccines, come at opposite times of year in the two hemispheres.");
+                        for hemisphere in Hemisphere::all() {
+                            ui.radio_value(
+                                &mut onboarding.hemisphere,
+                                hemisphere,
+                                format!("{hemisphere} hemisphere"),
+                            );
+                        }
+                    }
+                    2 => {
+                        ui.label(Contraindication::Pregnant.question());
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut onboarding.pregnant, true, "Yes");
+                            ui.radio_value(&mut onboarding.pregnant, false, "No");
+                        });
+                    }
+                    _ => {
+                        ui.label("Do you have records of vaccines you have already had? Entering them keeps doses you've had from being planned again.");
+                        ui.checkbox(
+                            &mut onboarding.has_records,
+                            "Import a records file when done",
+                        );
+                        ui.add_enabled(
+                            onboarding.date_of_birth.is_some(),
+                            egui::Checkbox::new(
+                                &mut onboarding.childhood_vaccines,
+                                "I received the standard childhood vaccines",
+                            ),
+                        )
+                        .on_hover_text(CHILDHOOD_BASELINE_HELP)
+                        .on_disabled_hover_text("Needs a date of birth");
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    skip = ui
+                        .button("Skip")
+                        .on_hover_text("Start with the recommended vaccines")
+                        .clicked();
+                    if ui
+                        .add_enabled(onboarding.step > 0, egui::Button::new("Back"))
+                        .clicked()
+                    {
+                        onboarding.step -= 1;
+                    }
+                    if onboarding.step + 1 < Onboarding::STEPS {
+                        if ui.button("Next").clicked() {
+                            onboarding.step += 1;
+                        }
+                    } else {
+                        finish = ui.button("Finish").clicked();
+                    }
+                });
+            });
+        if skip {
+            self.onboarded = true;
+            self.onboarding = None;
+        } else if finish {
+            match self.finish_onboarding() {
+                Ok(true) => {
+                    Self::import_records().ok();
+                }
+                Ok(false) => {}
+                Err(e) => log::error!("failed to apply first-launch answers: {e}"),
+            }
+        }
+    }
+
+    // Unanswered health questions to ask now; see `questions_seen`.
+    fn new_questions(&mut self) -> Vec<Contraindication> {
+        let pending = self.profile().unanswered_questions();
+        let seen = self
+            .questions_seen
+            .entry(self.active_profile.clone())
+            .or_insert_with(|| pending.iter().copied().collect());
+        pending.into_iter().filter(|q| !seen.contains(q)).collect()
+    }
+
+    fn ask_later(&mut self, questions: &[Contraindication]) {
+        self.questions_seen
+            .entry(self.active_profile.clone())
+            .or_default()
+            .extend(questions);
+    }
+
+    fn show_health_questions(&mut self, ctx: &egui::Context) {
+        let questions = self.new_questions();
+        if questions.is_empty() {
+            return;
+        }
+        let mut answers = Vec::new();
+        let mut later = false;
+        egui::Window::new("Before Planning")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("Some of the vaccines you enabled aren't right for everyone. Your answers are kept with this profile and can be changed under \"Health questions\".");
+                egui::Grid::new("health_questions_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for question in &questions {
+                            ui.label(question.question());
+                            ui.horizontal(|ui| {
+                                if ui.button("Yes").clicked() {
+                                    answers.push((*question, true));
+                                }
+                                if ui.button("No").clicked() {
+                                    answers.push((*question, false));
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                later = ui
+                    .button("Ask Later")
+                    .on_hover_text("Plan as if the answers were no for now")
+                    .clicked();
+            });
+        if later {
+            self.ask_later(&questions);
+        }
+        self.profile_mut().contraindications.extend(answers);
+    }
+
+    // The answers given so far, to change or to be asked again.
+    fn show_health_answers(&mut self, ui: &mut egui::Ui) {
+        let answers = &mut self.profile_mut().contraindications;
+        if answers.is_empty() {
+            return;
+        }
+        ui.collapsing("Health questions", |ui| {
+            let mut forget = None;
+            for (question, yes) in answers.iter_mut() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(yes, question.question());
+                    if ui.small_button("Ask Again").clicked() {
+                        forget = Some(*question);
+                    }
+                });
+            }
+            if let Some(question) = forget {
+                answers.remove(&question);
+            }
+        });
+    }
+
+    // Move the records the user chose onto their imported dates.
+    fn resolve_import_conflicts(&mut self) {
+        let conflicts = std::mem::take(&mut self.import_conflicts);
+        let records = &mut self.profile_mut().records;
+        for (conflict, keep_incoming) in conflicts {
+            if !keep_incoming {
+                continue;
+            }
+            if let Some(record) = records.iter_mut().find(|r| **r == conflict.existing) {
+                *record.date_mut() = conflict.incoming.date().clone();
+            }
+        }
+        records.sort();
+    }
+
+    fn show_import_conflicts(&mut self, ctx: &egui::Context) {
+        if self.import_conflicts.is_empty() {
+            return;
+        }
+        let labels = &self.dose_labels;
+        let mut finished = None;
+        egui::Window::new("Possible Duplicates")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("These imported records look like ones already on file, a few days apart. Choose which date to keep for each.");
+                egui::Grid::new("import_conflicts_grid")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for (conflict, keep_incoming) in &mut self.import_conflicts {
+                            let existing = &conflict.existing;
+                            ui.label(format!(
+                                "{} {}",
+                                existing.vaccine(),
+                                existing.kind().label(labels)
+                            ));
+                            ui.radio_value(
+                                keep_incoming,
+                                false,
+                                format!("On file: {}", existing.date_label()),
+                            );
+                            ui.radio_value(
+                                keep_incoming,
+                                true,
+                                format!(
+                                    "Imported: {}",
+                                    conflict.incoming.date_label()
+                                ),
+                            );
+                            ui.end_row();
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        finished = Some(true);
+                    }
+                    if ui
+                        .button("Keep On-File Dates")
+                        .on_hover_text("Leave the existing records as they are")
+                        .clicked()
+                    {
+                        finished = Some(false);
+                    }
+                });
+            });
+        match finished {
+            Some(true) => self.resolve_import_conflicts(),
+            Some(false) => self.import_conflicts.clear(),
+            None => {}
+        }
+    }
+
+    fn show_import_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(staged) = &self.staged_import else {
+            return;
+        };
+        let mut choice = None;
+        let mut cancelled = false;
+        egui::Window::new("Confirm Import")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                match staged {
+                    StagedImport::Profile(_, diff) => {
+                        ui.label(diff.summary());
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Replace")
+                                .on_hover_text("Overwrite the current profile with the import")
+                                .clicked()
+                            {
+                                choice = Some(ImportChoice::Replace);
+                            }
+                            if ui
+                                .button("Merge")
+                                .on_hover_text("Keep your settings and add the new records")
+                                .clicked()
+                            {
+                                choice = Some(ImportChoice::Merge);
+                            }
+                            if ui.button("Add as New Profile").clicked() {
+                                choice = Some(ImportChoice::AddProfile);
+                            }
+                            cancelled = ui.button("Cancel").clicked();
+                        });
+                    }
+                    StagedImport::Restore(app) => {
+                        ui.label(format!(
+                            "This is a full save with {} profiles and {} records. Restoring it replaces all {} of your profiles and {} records.",
+                            app.profiles.len(),
+                            app.profiles.values().map(|p| p.records.len()).sum::<usize>(),
+                            self.profiles.len(),
+                            self.profiles.values().map(|p| p.records.len()).sum::<usize>(),
+                        ));
+                        ui.horizontal(|ui| {
+                            if ui.button("Replace Everything").clicked() {
+                                choice = Some(ImportChoice::Replace);
+                            }
+                            cancelled = ui.button("Cancel").clicked();
+                        });
+                    }
+                }
+            });
+        if let Some(choice) = choice {
+            self.confirm_import(choice);
+        } else if cancelled {
+            self.staged_import = None;
+            self.import_message = Some("Import cancelled.".to_owned());
+        }
+    }
+
+    // The read-only view for records opened from a share link.
+    fn show_shared_view(&mut self, ctx: &egui::Context) {
+        let Some(records) = &self.shared_records else {
+            return;
+        };
+        let now = self.now();
+        let profile = Profile {
+            end_plan_year: now.year() + 5,
+            records: records.clone(),
+            ..Default::default()
+        };
+        let mut close = false;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(warning) = tz::fallback_warning() {
+                ui.colored_label(ui.visuals().warn_fg_color, warning);
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Shared Records");
+                    ui.label("(read-only)");
+                    close = ui.button("Open My Own Plan").clicked();
+                });
+                ui.separator();
+                egui::Grid::new("shared_records_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for record in &profile.records {
+                            ui.label(record.date().date().to_string());
+                            ui.label(record.vaccine());
+                            ui.label(record.kind().label(&self.dose_labels));
+                            ui.label(record.notes());
+                            ui.end_row();
+                        }
+                    });
+                ui.label("");
+                ui.heading(format!(
+                    "Recommended vaccines through {}",
+                    profile.end_plan_year
+                ));
+                ui.separator();
+                match profile.plan(&now) {
+                    Ok(plan) => {
+                        for appt in plan {
+                            ui.label(format!(
+                                "{} — {} {}",
+                                jiff::civil::date(appt.year(), appt.month(), 1).strftime("%B %Y"),
+                                appt.vaccine(),
+                                appt.kind().label(&self.dose_labels)
+                            ));
+                        }
+                    }
+                    Err(e) => {
+                        ui.label(format!("Failed to build a plan: {e}"));
+                    }
+                }
+            });
+        });
+        if close {
+            self.shared_records = None;
+            clear_location_hash();
+        }
+    }
+
+    fn show_timeline(&mut self, ctx: &egui::Context) {
+        if !self.show_timeline {
+            return;
+        }
+        let now = self.now();
+        let profile = &self.profiles[&self.active_profile];
+        let vaccines = profile
+            .vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .map(|v| v.name.as_str())
+            .collect_vec();
+        egui::Window::new("Plan Timeline")
+            .open(&mut self.show_timeline)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                timeline::show_timeline(
+                    ui,
+                    &now,
+                    profile.end_plan_year,
+                    &vaccines,
+                    &profile.schedule,
+                    &self.dose_labels,
+                    &mut self.timeline_month_width,
+                );
+            });
+    }
+
+    fn show_passport(&mut self, ctx: &egui::Context) {
+        let records = &self.profiles[&self.active_profile].records;
+        egui::Window::new("Immunization Passport")
+            .open(&mut self.show_passport)
+            .show(ctx, |ui| {
+                let passport = match Passport::new(records) {
+                    Ok(passport) => passport,
+                    Err(e) => {
+                        ui.label(format!("Failed to build QR code: {e}"));
+                        return;
+                    }
+                };
+                ui.label("A compact copy of your records (vaccine, date, and kind) for travel.");
+                if passport.is_truncated() {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!(
+                            "Too many records to fit in a QR code: only the {} most recent of {} are included.",
+                            passport.included(),
+                            passport.total()
+                        ),
+                    );
+                }
+                passport.show(ui, 320.0);
+                if ui.button("Export SVG").clicked() {
+                    download_file(
+                        passport.to_svg(),
+                        "vaccine_passport.svg",
+                        passport::SVG_MIME_TYPE,
+                    )
+                    .ok();
+                }
+            });
+    }
+
+    fn show_debug_log(&mut self, ctx: &egui::Context) {
+        let now = self.now();
+        let profile = self.profile();
+        let enabled = profile
+            .vaccines
+            .iter()
+            .filter(|v| v.enabled)
+            .map(|v| v.name.clone())
+            .collect_vec();
+        let offsets = self.offsets_vaccine.as_ref().map(|name| {
+            Vaccine::get_vaccines()[name.as_str()].dose_offsets(
+                &now,
+                &profile.records,
+                profile.end_plan_year,
+                &profile.schedule_options(),
+            )
+        });
+        let offsets_vaccine = &mut self.offsets_vaccine;
+        egui::Window::new("Debug Log")
+            .open(&mut self.show_debug_log)
+            .show(ctx, |ui| {
+                ui.label("Details of how the schedule was worked out. Include these when reporting a surprising plan.");
+                let mut inspect = offsets_vaccine.is_some();
+                ui.checkbox(&mut inspect, "Show month offsets").on_hover_text(
+                    "The schedule is worked out in months from now; show the raw offsets for one vaccine, before visits are spaced out",
+                );
+                match (inspect, offsets_vaccine.is_some()) {
+                    (true, false) => *offsets_vaccine = enabled.first().cloned(),
+                    (false, true) => *offsets_vaccine = None,
+                    _ => {}
+                }
+                if let Some(selected) = offsets_vaccine {
+                    show_dose_offsets(ui, selected, &enabled, offsets);
+                    ui.separator();
+                }
+                let lines = debug_log::lines();
+                ui.horizontal(|ui| {
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(lines.join("\n"));
+                    }
+                    if ui.button("Clear").clicked() {
+                        debug_log::clear();
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &lines {
+                            ui.monospace(line);
+                        }
+                    });
+            });
+    }
+
+    fn show_about(&mut self, ctx: &egui::Context) {
+        egui::Window::new("About")
+            .open(&mut self.show_about)
+            .show(ctx, |ui| {
+                ui.heading("Warning");
+                ui.separator();
+                ui.label("Usage of this (extremely simple) tool does not constitute medical advice. Please consult a doctor or pharmacist before putting anything in your body.");
+                ui.label("");
+
+                ui.heading("General Safety Information");
+                ui.separator();
+                ui.label("However, there is overwhelming evidence that vaccines are both safe and effective. Vaccines are so effective that we can't study the long-term effectiveness because there's nobody sick left to study.");
+                ui.label("");
+                ui.label("The odds of a severe or worse vaccine reaction are, like shark attacks and lightning strikes, low enough that computing accurate odds is impossible. Many vaccines are safe to the limits of our ability to detect, with no reported severe reactions on record.");
                 ui.hyperlink_to("WHO Reaction Rates Info Sheets", "https://www.who.int/teams/regulation-prequalification/regulation-and-safety/pharmacovigilance/guidance/reaction-rates-information-sheets");
                 ui.hyperlink_to("CDC Vaccine Information", "https://www.cdc.gov/vaccines/index.html");
                 ui.label("");
 
+                ui.heading("Schedule Data");
+                ui.separator();
+                match Vaccine::data_last_reviewed() {
+                    Some(reviewed) => ui.label(format!("The built-in vaccine schedules were last reviewed {reviewed}. Guidance changes over time, so check the sources for anything that matters to you.")),
+                    None => ui.label("Not all of the built-in vaccine schedules have been reviewed against their sources yet. Guidance changes over time, so check the sources for anything that matters to you."),
+                };
+                egui::Grid::new("about_sources_grid").num_columns(4).show(ui, |ui| {
+                    for vaccine in Vaccine::get_vaccines().values().sorted_by_key(|v| v.name()) {
+                        ui.label(vaccine.name());
+                        ui.label(vaccine.last_reviewed().unwrap_or(NOT_REVIEWED));
+                        ui.label(vaccine.confidence().to_string());
+                        ui.hyperlink(vaccine.source());
+                        ui.end_row();
+                    }
+                });
+                ui.label("");
+
                 ui.heading("About this Tool");
                 ui.separator();
                 ui.label("Vaccine helper is there to give adults (with no a competent vaccine clinic in their area) a simple way to track and schedule immunizations with all the incredible new vaccines that are available since we were children.");
@@ -475,16 +4383,1919 @@ impl VaccineHelperApp {
     }
 }
 
-fn powered_by_egui_and_eframe(ui: &mut egui::Ui) {
-    // Note: right alignment, so add in opposite order.
-    ui.horizontal(|ui| {
-        ui.label(".");
-        ui.hyperlink_to(
-            "eframe",
-            "https://github.com/emilk/egui/tree/master/crates/eframe",
+// A button to put a vaccine on hold for a few months, or a badge with the date it resumes.
+fn show_snooze(ui: &mut egui::Ui, name: &str, snooze_until: &mut Option<Zoned>, now: &Zoned) {
+    match snooze_until {
+        Some(until) => {
+            ui.label(egui::RichText::new("💤 snoozed until").weak());
+            let mut date = NaiveDate::from_ymd_opt(
+                until.year().into(),
+                until.month() as u32,
+                until.day() as u32,
+            )
+            .unwrap();
+            ui.add(
+                egui_extras::DatePickerButton::new(&mut date)
+                    .id_salt(&format!("snooze_until_{name}"))
+                    .show_icon(false),
+            );
+            *until = jiffdate(date.year() as i16, date.month() as i8, date.day() as i8)
+                .to_zoned(until.time_zone().clone())
+                .expect("a valid date");
+            if ui.small_button("Resume").clicked() {
+                *snooze_until = None;
+            }
+        }
+        None => {
+            if ui
+                .small_button("💤")
+                .on_hover_text(
+                    "Snooze: plan nothing for this vaccine for a while. It resumes on its own.",
+                )
+                .clicked()
+            {
+                *snooze_until = now.checked_add(jiff::Span::new().months(3)).ok();
+            }
+        }
+    }
+}
+
+fn show_titer_check(ui: &mut egui::Ui, titer_check_years: &mut Option<i16>) {
+    match titer_check_years {
+        Some(years) => {
+            ui.label(egui::RichText::new("🧪 titer check every").weak());
+            ui.add(egui::DragValue::new(years).range(1..=20).suffix(" years"));
+            if ui.small_button("Stop").clicked() {
+                *titer_check_years = None;
+            }
+        }
+        None => {
+            if ui
+                .small_button("🧪")
+                .on_hover_text(
+                    "Plan a blood test every few years to check that protection is holding up",
+                )
+                .clicked()
+            {
+                *titer_check_years = Some(5);
+            }
+        }
+    }
+}
+
+fn show_booster_override(
+    ui: &mut egui::Ui,
+    vaccine: &Vaccine,
+    booster_override: &mut Option<BoosterSchedule>,
+) {
+    let usual = vaccine.booster_schedule();
+    match booster_override {
+        Some(schedule) => {
+            ui.label(egui::RichText::new("💉 boosters").weak())
+                .on_hover_text(format!("Changed from the usual schedule: {usual}"));
+            let years = match schedule {
+                BoosterSchedule::Years(n) => *n,
+                _ => 1,
+            };
+            let choices = [
+                ("every", BoosterSchedule::Years(years)),
+                (
+                    "every fall",
+                    BoosterSchedule::Seasonal {
+                        second_dose_if_naive: false,
+                    },
+                ),
+                ("rarely", BoosterSchedule::Lifetime),
+            ];
+            let same = |a: &BoosterSchedule, b: &BoosterSchedule| {
+                std::mem::discriminant(a) == std::mem::discriminant(b)
+            };
+            let selected = choices
+                .iter()
+                .find(|(_, choice)| same(choice, schedule))
+                .map_or("", |(label, _)| *label);
+            egui::ComboBox::from_id_salt(("booster_override", vaccine.name()))
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (label, choice) in &choices {
+                        if ui.selectable_label(same(choice, schedule), *label).clicked() {
+                            *schedule = choice.clone();
+                        }
+                    }
+                });
+            if let BoosterSchedule::Years(n) = schedule {
+                ui.add(egui::DragValue::new(n).range(1..=30).suffix(" years"));
+            }
+            if ui
+                .small_button("Reset")
+                .on_hover_text(format!("Go back to boosters {usual}"))
+                .clicked()
+            {
+                *booster_override = None;
+            }
+        }
+        None => {
+            if ui
+                .small_button("💉")
+                .on_hover_text(format!(
+                    "Boosters are usually {usual}. Click to change this for this profile, e.g. on a doctor's advice."
+                ))
+                .clicked()
+            {
+                *booster_override = Some(usual.clone());
+            }
+        }
+    }
+}
+
+fn show_dose_offsets(
+    ui: &mut egui::Ui,
+    selected: &mut String,
+    enabled: &[String],
+    offsets: Option<Result<Vec<DoseOffset>>>,
+) {
+    egui::ComboBox::from_id_salt("dose_offsets_vaccine")
+        .selected_text(selected.as_str())
+        .show_ui(ui, |ui| {
+            for name in enabled {
+                ui.selectable_value(selected, name.clone(), name);
+            }
+        });
+    match offsets {
+        Some(Ok(offsets)) => {
+            egui::Grid::new("dose_offsets_grid")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (kind, mo, (year, month)) in offsets {
+                        ui.monospace(kind.to_string());
+                        ui.monospace(format!("{mo:+}"));
+                        ui.monospace(format!("{year}-{month:02}"));
+                        ui.end_row();
+                    }
+                });
+        }
+        Some(Err(e)) => {
+            ui.label(format!("Failed to work out offsets: {e}"));
+        }
+        None => {}
+    }
+}
+
+// How much of a record's date is known. Only that much of the picked date is kept in mind.
+fn precision_picker(ui: &mut egui::Ui, id_salt: &str, precision: &mut DatePrecision) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(precision.to_string())
+        .show_ui(ui, |ui| {
+            for choice in DatePrecision::all() {
+                ui.selectable_value(precision, choice, choice.to_string());
+            }
+        })
+        .response
+        .on_hover_text(
+            "For old records known only to the month or year. The next dose is timed from the \
+             latest date it could have been.",
         );
-        ui.label(" and ");
-        ui.hyperlink_to("egui", "https://github.com/emilk/egui");
-        ui.label("Powered by ");
-    });
+}
+
+fn source_picker(ui: &mut egui::Ui, id_salt: &str, source: &mut RecordSource) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(format!("{} {source}", source.icon()))
+        .show_ui(ui, |ui| {
+            for choice in RecordSource::all() {
+                ui.selectable_value(source, choice, format!("{} {choice}", choice.icon()));
+            }
+        })
+        .response
+        .on_hover_text(RECORD_SOURCE_HELP);
+}
+
+// An optional reaction: a checkbox to add one, then its severity and description.
+fn reaction_editor(ui: &mut egui::Ui, id_salt: &str, reaction: &mut Option<Reaction>) {
+    ui.horizontal(|ui| {
+        let mut had = reaction.is_some();
+        ui.checkbox(&mut had, "Reaction");
+        match (had, reaction.as_mut()) {
+            (true, Some(reaction)) => {
+                egui::ComboBox::from_id_salt(id_salt)
+                    .selected_text(reaction.severity.to_string())
+                    .show_ui(ui, |ui| {
+                        for severity in Severity::all() {
+                            ui.selectable_value(
+                                &mut reaction.severity,
+                                severity,
+                                severity.to_string(),
+                            );
+                        }
+                    });
+                ui.add(
+                    egui::TextEdit::singleline(&mut reaction.description)
+                        .hint_text("What happened?"),
+                );
+            }
+            (true, None) => *reaction = Some(Reaction::default()),
+            (false, _) => *reaction = None,
+        }
+    });
+}
+
+// Shown in place of the drag handle image if it can't be loaded.
+const DRAG_HANDLE_GLYPH: &str = "☰";
+
+// Draw the drag handle, falling back to a plain glyph if the image fails to load so that the row
+// keeps its layout and can still be dragged.
+fn drag_handle(ui: &mut egui::Ui, source: egui::ImageSource<'_>) {
+    let image = egui::Image::new(source);
+    match image.load_for_size(ui.ctx(), ui.available_size()) {
+        Ok(_) => {
+            ui.add(image);
+        }
+        Err(err) => {
+            log::debug!("drag handle image unavailable: {err}");
+            ui.label(DRAG_HANDLE_GLYPH);
+        }
+    }
+}
+
+// Colors for highlighting appointments, picked from the current theme so that they stay
+// legible in both light and dark mode. Future appointments keep the default text color.
+fn urgency_color(visuals: &egui::Visuals, urgency: Urgency) -> Option<egui::Color32> {
+    match urgency {
+        Urgency::Overdue => Some(visuals.error_fg_color),
+        Urgency::DueSoon => Some(if visuals.dark_mode {
+            egui::Color32::ORANGE
+        } else {
+            egui::Color32::from_rgb(200, 90, 0)
+        }),
+        Urgency::DueThisYear => Some(visuals.warn_fg_color),
+        Urgency::Future => None,
+        Urgency::Completed => Some(if visuals.dark_mode {
+            egui::Color32::LIGHT_GREEN
+        } else {
+            egui::Color32::DARK_GREEN
+        }),
+    }
+}
+
+fn show_vaccine_details(
+    ui: &mut egui::Ui,
+    vaccine: &Vaccine,
+    records: &[VaccineRecord],
+    schedule: &[VaccineAppointment],
+    labels: &DoseLabels,
+    // When set, note the spacing between planned doses, measured from this time.
+    gaps_from: Option<&Zoned>,
+) {
+    egui::Grid::new(("vaccine_details_grid", vaccine.name()))
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Protects against:");
+            ui.label(vaccine.treats_str());
+            ui.end_row();
+
+            ui.label("Initial series:");
+            ui.label(vaccine.dosage_schedule().to_string());
+            ui.end_row();
+
+            ui.label("Boosters:");
+            ui.label(vaccine.booster_schedule().to_string());
+            ui.end_row();
+
+            ui.label("Live vaccine:");
+            ui.label(if vaccine.live() { "yes" } else { "no" });
+            ui.end_row();
+
+            ui.label("Minimum age:");
+            ui.label(match vaccine.minimum_age_mo() {
+                0 => "from birth".to_owned(),
+                mo if mo < 24 => format!("{mo} months"),
+                mo => format!("{} years", mo / 12),
+            });
+            ui.end_row();
+
+            if let Some(mo) = vaccine.restart_after_mo() {
+                ui.label("Restart series:");
+                ui.label(format!("after a gap of more than {mo} months"));
+                ui.end_row();
+            }
+
+            for record in records.iter().filter(|r| r.vaccine() == vaccine.name()) {
+                ui.label(format!("{}:", record.kind().label(labels)));
+                ui.label(format!("received {}", record.date_label()));
+                ui.end_row();
+            }
+            let planned = schedule
+                .iter()
+                .filter(|a| a.vaccine() == vaccine.name())
+                .take(4)
+                .collect_vec();
+            let gaps = match gaps_from {
+                Some(now) => month_gaps(planned.iter().map(|a| a.month_offset(now))),
+                None => vec![None; planned.len()],
+            };
+            for (i, (appt, gap)) in planned.iter().zip(gaps).enumerate() {
+                ui.label(format!("{}:", appt.label(labels)));
+                let mut text = format!(
+                    "planned {}",
+                    jiff::civil::date(appt.year(), appt.month(), 1).strftime("%b %Y")
+                );
+                if let Some(gap) = gap {
+                    let previous = planned[i - 1].kind().label(labels);
+                    text.push_str(&format!(" ({gap} months after {previous})"));
+                }
+                ui.label(text);
+                ui.end_row();
+            }
+        });
+    ui.label(egui::RichText::new(vaccine.notes()).italics());
+    ui.horizontal(|ui| {
+        match vaccine.last_reviewed() {
+            Some(reviewed) => ui.label(format!("Last reviewed {reviewed} against")),
+            None => ui.label("Not yet reviewed against"),
+        };
+        ui.hyperlink(vaccine.source());
+    });
+    if vaccine.confidence().needs_review() {
+        ui.colored_label(ui.visuals().warn_fg_color, REVIEW_WARNING);
+    }
+}
+
+const NOT_REVIEWED: &str = "Not reviewed";
+
+const REVIEW_WARNING: &str =
+    "This schedule was put together informally. Check it with a doctor or pharmacist.";
+
+const INVALID_DOSE_HELP: &str =
+    "Given, but it doesn't count, e.g. because it was stored wrong or given too early. The dose is planned again.";
+
+const RECORD_SOURCE_HELP: &str =
+    "Where the record came from: typed in from memory, imported from a file, or checked against an official record.";
+
+// The hover text for a vaccine in the list.
+fn vaccine_summary(vaccine: &Vaccine) -> String {
+    format!(
+        "Category: {}\nDose: {}\nBoost: {}\nLive: {}\nNotes: {}\nConfidence: {}\nLast reviewed: {}\nSource: {}",
+        vaccine.category(),
+        vaccine.dosage_schedule(),
+        vaccine.booster_schedule(),
+        if vaccine.live() { "yes" } else { "no" },
+        vaccine.notes(),
+        vaccine.confidence(),
+        vaccine.last_reviewed().unwrap_or(NOT_REVIEWED),
+        vaccine.source(),
+    )
+}
+
+fn powered_by_egui_and_eframe(ui: &mut egui::Ui) {
+    // Note: right alignment, so add in opposite order.
+    ui.horizontal(|ui| {
+        ui.label(".");
+        ui.hyperlink_to(
+            "eframe",
+            "https://github.com/emilk/egui/tree/master/crates/eframe",
+        );
+        ui.label(" and ");
+        ui.hyperlink_to("egui", "https://github.com/emilk/egui");
+        ui.label("Powered by ");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::Date;
+
+    const V1_BLOB: &str = r#"(
+        active_profile: "Bob",
+        profiles: {
+            "Alice": (
+                vaccines: [(name: "Tdap", enabled: true)],
+                end_plan_year: 2080,
+                records: [(vaccine: "Tdap", date: "2024-03-01T00:00:00+00:00[UTC]", kind: Dose(0), notes: "")],
+                schedule: [(vaccine: "Tdap", kind: Dose(1), year: 2024, month: 9)],
+            ),
+        },
+        show_profiles: false,
+        show_preferences: false,
+        show_about: true,
+        add_record: None,
+        add_profile_name: "",
+    )"#;
+
+    #[test]
+    fn test_empty_schedule() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.recompute_schedule()?;
+        assert_eq!(None, app.empty_schedule());
+
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Hepatitis B";
+        }
+        let now = app.now();
+        app.profile_mut().records.push(VaccineRecord::new(
+            "Hepatitis B",
+            now.checked_sub(jiff::Span::new().years(1))?,
+            DoseKind::Dose(0),
+            "",
+        ));
+        app.recompute_schedule()?;
+        assert_eq!(Some(EmptySchedule::UpToDate), app.empty_schedule());
+
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = false;
+        }
+        app.recompute_schedule()?;
+        assert_eq!(Some(EmptySchedule::NoneEnabled), app.empty_schedule());
+
+        // The second Hepatitis A dose comes next year. With the plan ending this year that isn't
+        // being up to date.
+        let mut app = app_at(2025, 11, 1)?;
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Hepatitis A";
+        }
+        let now = app.now();
+        app.profile_mut().records.push(VaccineRecord::new(
+            "Hepatitis A",
+            now.checked_sub(jiff::Span::new().months(1))?,
+            DoseKind::Dose(0),
+            "",
+        ));
+        app.profile_mut().end_plan_year = 2025;
+        app.recompute_schedule()?;
+        assert_eq!(Some(EmptySchedule::PlanEndsThisYear), app.empty_schedule());
+        app.profile_mut().end_plan_year = 2027;
+        app.recompute_schedule()?;
+        assert_eq!(None, app.empty_schedule());
+        Ok(())
+    }
+
+    #[test]
+    fn test_appointment_notes_survive_recompute() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Tdap";
+        }
+        app.recompute_schedule()?;
+        let key = appointment_key(&app.profile().schedule[1]);
+        app.profile_mut()
+            .appointment_notes
+            .insert(key.clone(), "Downtown clinic, mornings".to_owned());
+
+        app.recompute_schedule()?;
+        assert!(app.profile().orphaned_notes().is_empty());
+        let app = VaccineHelperApp::from_ron(&ron::ser::to_string(&app)?)?.0;
+        assert_eq!(
+            Some("Downtown clinic, mornings"),
+            app.profile()
+                .appointment_notes
+                .get(&key)
+                .map(String::as_str)
+        );
+
+        // A month later every appointment shifts, so the note no longer matches.
+        let mut app = VaccineHelperApp {
+            now_override: Some(app.now().checked_add(jiff::Span::new().months(1))?),
+            ..app
+        };
+        app.recompute_schedule()?;
+        assert_eq!(
+            vec![(key.clone(), "Downtown clinic, mornings".to_owned())],
+            app.profile().orphaned_notes()
+        );
+        app.profile_mut().reattach_note(&key);
+        assert!(app.profile().orphaned_notes().is_empty());
+        let moved = appointment_key(&app.profile().schedule[1]);
+        assert_eq!((key.0, key.1), (moved.0.clone(), moved.1));
+        assert!(app.profile().appointment_notes.contains_key(&moved));
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_sources() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        app.import_csv("2024-01-01,Tdap,Dose#1\n", &tz);
+        assert_eq!(RecordSource::Imported, app.profile().records[0].source());
+
+        // Entered by hand: the new record form and marking a planned shot done.
+        assert_eq!(
+            RecordSource::SelfReported,
+            VaccineRecord::default().source()
+        );
+        app.recompute_schedule()?;
+        let next = app.next_appointment().cloned().expect("an appointment");
+        app.mark_done(&next)?;
+        let done = app
+            .profile()
+            .records
+            .iter()
+            .find(|r| r.vaccine() == next.vaccine() && *r.kind() == next.kind())
+            .expect("the new record");
+        assert_eq!(RecordSource::SelfReported, done.source());
+
+        // A verified record stays verified through an export and import.
+        let verified = done.clone().with_source(RecordSource::Verified);
+        let data = ron::ser::to_string(&vec![verified.clone()])?;
+        let RonImport::Records(records) = RonImport::parse(&data)? else {
+            panic!("not read as records");
+        };
+        assert_eq!(vec![verified], records);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_profile_from_other_version() -> Result<()> {
+        // An older profile, whose record has no notes, with a field from a newer version. The
+        // appointment notes are keyed by tuples, which unknown fields can't be tracked through.
+        let data = r#"(
+            vaccines: [(name: "Tdap", enabled: true, lot_tracking: true)],
+            records: [(vaccine: "Tdap", date: "2024-03-01T00:00:00+00:00[UTC]", kind: Dose(0))],
+            appointment_notes: {("Tdap", Booster, 2034, 3): "bring card"},
+        )"#;
+        let mut app = app_at(2025, 6, 1)?;
+        app.import_ron(data);
+        let Some(StagedImport::Profile(profile, _)) = &app.staged_import else {
+            panic!("profile not staged: {:?}", app.import_message);
+        };
+        assert_eq!(1, profile.records.len());
+        let message = app.import_message.clone().unwrap_or_default();
+        assert!(message.contains("vaccines.0.lot_tracking"), "{message}");
+        assert!(message.contains("records.0.notes"), "{message}");
+        assert_eq!(1, profile.appointment_notes.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_profile_with_unknown_vaccine() -> Result<()> {
+        let data = r#"(
+            vaccines: [(name: "Smallpox", enabled: true), (name: "Tdap", enabled: true)],
+            records: [],
+        )"#;
+        let mut app = app_at(2025, 6, 1)?;
+        app.import_ron(data);
+        app.confirm_import(ImportChoice::Replace);
+        let names = app.profile().enabled_names();
+        assert_eq!(HashSet::from(["Tdap".to_owned()]), names);
+        assert!(app
+            .profile()
+            .vaccines
+            .iter()
+            .all(|v| Vaccine::get_vaccines().contains_key(v.name.as_str())));
+        app.refresh_schedule()?;
+        app.profile().unanswered_questions();
+        assert!(app.profile().schedule.iter().all(|a| a.vaccine() == "Tdap"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pending_import_in_focus_mode() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.focus_mode = true;
+        *IMPORT_CSV_CONTENT.lock().unwrap() = Some("2024-01-01,Tdap,Dose#1\n".to_owned());
+        app.take_pending_imports();
+        assert_eq!(1, app.profile().records.len());
+        assert!(IMPORT_CSV_CONTENT.lock().unwrap().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_csv_with_unknown_vaccine() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        app.import_csv("2024-01-01,Tdap,Dose#1\n", &tz);
+        assert!(app.import_review.is_none());
+        assert_eq!(1, app.profile().records.len());
+
+        app.import_csv(
+            "2024-02-01,Tetanus shot,Dose#2\n2024-03-01,Yellow Fever,Dose#1\n",
+            &tz,
+        );
+        let review = app.import_review.take().expect("review");
+        assert_eq!(
+            vec![
+                ("Tetanus shot".to_owned(), 1),
+                ("Yellow Fever".to_owned(), 1)
+            ],
+            review.unknown
+        );
+        assert_eq!(1, app.profile().records.len());
+        let mut actions = review.actions;
+        assert_eq!(
+            Some(&UnknownVaccineAction::Rename("Tdap")),
+            actions.get("Tetanus shot")
+        );
+        actions.insert("Yellow Fever".to_owned(), UnknownVaccineAction::Drop);
+        app.finish_import(review.pending, &actions);
+        assert_eq!(
+            vec!["Tdap", "Tdap"],
+            app.profile()
+                .records
+                .iter()
+                .map(|r| r.vaccine())
+                .collect_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_contraindication_answers() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "MMR" || v.name == "Tdap";
+        }
+        assert_eq!(
+            vec![
+                Contraindication::Pregnant,
+                Contraindication::Immunocompromised
+            ],
+            app.profile().unanswered_questions()
+        );
+
+        let profile = app.profile_mut();
+        profile
+            .contraindications
+            .insert(Contraindication::Pregnant, true);
+        profile
+            .contraindications
+            .insert(Contraindication::Immunocompromised, false);
+        assert!(app.profile().unanswered_questions().is_empty());
+        let now = app.now();
+        let plan = app.profile().plan(&now)?;
+        assert!(plan.iter().all(|appt| appt.vaccine() == "Tdap"));
+        assert!(!plan.is_empty());
+        assert_eq!(
+            vec![(Contraindication::Pregnant, vec!["MMR".to_owned()])],
+            app.profile().held_off()
+        );
+
+        // Why MMR isn't planned is worked out along with the plan.
+        app.recompute_schedule()?;
+        assert_eq!(
+            HashMap::from([(
+                "MMR".to_owned(),
+                Unscheduled::Contraindicated(Contraindication::Pregnant)
+            )]),
+            app.profile().unscheduled
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_health_questions_pop_up_for_new_vaccines() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "MMR";
+        }
+        // Questions left over from before, e.g. in a profile saved by an older version, wait for
+        // the health questions section.
+        assert!(!app.profile().unanswered_questions().is_empty());
+        assert!(app.new_questions().is_empty());
+
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Tdap";
+        }
+        app.questions_seen.clear();
+        assert!(app.new_questions().is_empty());
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Tdap" || v.name == "MMR";
+        }
+        let questions = app.new_questions();
+        assert_eq!(
+            vec![
+                Contraindication::Pregnant,
+                Contraindication::Immunocompromised
+            ],
+            questions
+        );
+        app.ask_later(&questions);
+        assert!(app.new_questions().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_reconciles_dates() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        app.import_csv("2024-01-01,Tdap,Dose#1\n2024-07-01,Tdap,Dose#2\n", &tz);
+        app.import_csv(
+            "2024-01-01,Tdap,Dose#1\n2024-07-04,Tdap,Dose#2\n2025-01-01,Tdap,Dose#3\n",
+            &tz,
+        );
+        let dates = |app: &VaccineHelperApp| {
+            app.profile()
+                .records
+                .iter()
+                .map(|r| r.date().date().to_string())
+                .collect_vec()
+        };
+        assert_eq!(vec!["2024-01-01", "2024-07-01", "2025-01-01"], dates(&app));
+        assert_eq!(1, app.import_conflicts.len());
+
+        app.import_conflicts[0].1 = true;
+        app.resolve_import_conflicts();
+        assert_eq!(vec!["2024-01-01", "2024-07-04", "2025-01-01"], dates(&app));
+        assert!(app.import_conflicts.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_ron_shapes() -> Result<()> {
+        let mut source = app_at(2025, 6, 1)?;
+        let now = source.now();
+        source.profile_mut().records.push(VaccineRecord::new(
+            "Tdap",
+            now.checked_sub(jiff::Span::new().years(1))?,
+            DoseKind::Dose(0),
+            "",
+        ));
+        source.recompute_schedule()?;
+
+        let app_data = ron::ser::to_string(&source)?;
+        assert!(matches!(RonImport::parse(&app_data)?, RonImport::App(_)));
+        let profile_data = ron::ser::to_string(source.profile())?;
+        assert!(matches!(
+            RonImport::parse(&profile_data)?,
+            RonImport::Profile(_)
+        ));
+        let records_data = ron::ser::to_string(&source.profile().records)?;
+        assert!(matches!(
+            RonImport::parse(&records_data)?,
+            RonImport::Records(r) if r.len() == 1
+        ));
+        let schedule_data = ron::ser::to_string(&source.profile().schedule)?;
+        assert!(matches!(
+            RonImport::parse(&schedule_data)?,
+            RonImport::Appointments(a) if a.len() == source.profile().schedule.len()
+        ));
+        assert!(RonImport::parse("(color: 3)").is_err());
+
+        let mut app = app_at(2025, 6, 1)?;
+        app.import_ron(&records_data);
+        assert_eq!(1, app.profile().records.len());
+        app.import_ron(&profile_data);
+        app.confirm_import(ImportChoice::AddProfile);
+        assert_eq!("Imported", app.active_profile);
+        assert_eq!(1, app.profile().records.len());
+        app.import_ron(&schedule_data);
+        assert_eq!(2, app.profiles.len());
+        app.import_ron(&app_data);
+        assert_eq!(2, app.profiles.len());
+        app.confirm_import(ImportChoice::Replace);
+        assert_eq!(1, app.profiles.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_notes_round_trip() -> Result<()> {
+        let mut source = app_at(2025, 6, 1)?;
+        source.profile_mut().notes = "Immunocompromised; consult before live vaccines".to_owned();
+        source.recompute_schedule()?;
+        let ics = ics::schedule_to_ics(&source.profile().schedule, &source.profile().notes, |_| {
+            true
+        });
+        assert!(ics.contains("DESCRIPTION:Immunocompromised\\; consult before live vaccines\r\n"));
+
+        let mut app = app_at(2025, 6, 1)?;
+        app.import_ron(&ron::ser::to_string(source.profile())?);
+        app.confirm_import(ImportChoice::Replace);
+        assert_eq!(source.profile().notes, app.profile().notes);
+        app.import_ron(&ron::ser::to_string(&source)?);
+        app.confirm_import(ImportChoice::Replace);
+        assert_eq!(source.profile().notes, app.profile().notes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_staged_profile_import() -> Result<()> {
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        let record = |vaccine, year| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                vaccine,
+                Date::new(year, 1, 1)?.to_zoned(tz.clone())?,
+                DoseKind::Dose(0),
+                "",
+            ))
+        };
+        let mut app = app_at(2025, 6, 1)?;
+        app.profile_mut().records = vec![record("Tdap", 2020)?, record("Flu", 2021)?];
+        let mut incoming: Profile = ron::de::from_str(&ron::ser::to_string(app.profile())?)?;
+        incoming.records = vec![
+            record("Flu", 2021)?,
+            record("MMR", 2022)?,
+            record("Hepatitis B", 2023)?,
+        ];
+        let data = ron::ser::to_string(&incoming)?;
+
+        let expected = ProfileDiff {
+            incoming_records: 3,
+            current_records: 2,
+            added: 2,
+            removed: 1,
+            settings_differ: false,
+        };
+        assert_eq!(expected, ProfileDiff::new(app.profile(), &incoming));
+        incoming.end_plan_year += 1;
+        assert!(ProfileDiff::new(app.profile(), &incoming).settings_differ);
+        assert!(expected.summary().starts_with(
+            "This import has 3 records; your current profile has 2. 2 of the imported records are new, and 1 of yours"
+        ));
+
+        // Nothing changes until the import is confirmed.
+        app.import_ron(&data);
+        assert!(
+            matches!(app.staged_import, Some(StagedImport::Profile(_, ref diff)) if *diff == expected)
+        );
+        assert_eq!(2, app.profile().records.len());
+        app.confirm_import(ImportChoice::Merge);
+        assert_eq!(4, app.profile().records.len());
+        assert!(app.profile().records.is_sorted());
+
+        app.import_ron(&data);
+        app.confirm_import(ImportChoice::Replace);
+        assert_eq!(incoming.records, app.profile().records);
+        assert_eq!(1, app.profiles.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snooze_clears_when_passed() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let until = app.now().checked_add(jiff::Span::new().months(3))?;
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Tdap";
+            v.snooze_until = (v.name == "Tdap").then(|| until.clone());
+        }
+        app.recompute_schedule()?;
+        assert_eq!(9, app.profile().schedule[0].month());
+        assert!(app
+            .profile()
+            .vaccines
+            .iter()
+            .any(|v| v.snooze_until.is_some()));
+
+        app.now_override = Some(until.checked_add(jiff::Span::new().days(1))?);
+        app.recompute_schedule()?;
+        assert!(app
+            .profile()
+            .vaccines
+            .iter()
+            .all(|v| v.snooze_until.is_none()));
+        assert_eq!(9, app.profile().schedule[0].month());
+        Ok(())
+    }
+
+    #[test]
+    fn test_enable_recommended_keeps_order() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let vaccines = app.profile_mut().vaccines.iter_mut();
+        for (i, v) in vaccines.enumerate() {
+            v.enabled = i % 2 == 0;
+        }
+        app.profile_mut().vaccines.reverse();
+        let order = |app: &VaccineHelperApp| {
+            app.profile()
+                .vaccines
+                .iter()
+                .map(|v| (v.name.clone(), v.enabled))
+                .collect_vec()
+        };
+        let before = order(&app);
+
+        let previous = app.profile_mut().enable_recommended();
+        app.undo_enabled = Some((app.active_profile.clone(), previous));
+        for v in &app.profile().vaccines {
+            assert_eq!(
+                Vaccine::get_vaccines()[v.name.as_str()].recommended(),
+                v.enabled
+            );
+        }
+        assert_eq!(
+            before.iter().map(|(name, _)| name).collect_vec(),
+            order(&app).iter().map(|(name, _)| name).collect_vec()
+        );
+
+        app.undo_enable_recommended();
+        assert_eq!(before, order(&app));
+        assert!(app.undo_enabled.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_edit_defers_sort() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        for (year, vaccine) in [(2020, "Tdap"), (2021, "Flu"), (2022, "MMR")] {
+            app.profile_mut().records.push(VaccineRecord::new(
+                vaccine,
+                Date::new(year, 1, 1)?.to_zoned(tz.clone())?,
+                DoseKind::Dose(0),
+                "",
+            ));
+        }
+
+        // Move the oldest record to last; it stays put while the edit is open.
+        app.editing_record = Some(0);
+        *app.profile_mut().records[0].date_mut() = Date::new(2023, 1, 1)?.to_zoned(tz)?;
+        assert_eq!("Tdap", app.profile().records[0].vaccine());
+        app.recompute_schedule()?;
+
+        app.commit_record_edit();
+        assert_eq!(None, app.editing_record);
+        assert_eq!(
+            vec!["Flu", "MMR", "Tdap"],
+            app.profile()
+                .records
+                .iter()
+                .map(|r| r.vaccine())
+                .collect_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_household_schedule() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.profiles.clear();
+        assert!(app.household_schedule().is_empty());
+
+        for name in ["Bob", "Alice"] {
+            let mut profile = Profile {
+                end_plan_year: 2026,
+                ..Default::default()
+            };
+            for v in profile.vaccines.iter_mut() {
+                v.enabled = v.name == "Mpox";
+            }
+            app.profiles.insert(name.to_owned(), profile);
+        }
+        let household = app.household_schedule();
+        let summary = household
+            .iter()
+            .map(|(name, appt)| (name.as_str(), appt.year(), appt.month()))
+            .collect_vec();
+        assert_eq!(
+            vec![
+                ("Alice", 2025, 6),
+                ("Bob", 2025, 6),
+                ("Alice", 2025, 7),
+                ("Bob", 2025, 7)
+            ],
+            summary
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_drag_handle_fallback() {
+        let drawn_text = |ctx: &egui::Context| {
+            let output = ctx.run(Default::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    drag_handle(
+                        ui,
+                        egui::include_image!("../assets/icons8-drag-handle-30.png"),
+                    );
+                });
+            });
+            output
+                .shapes
+                .iter()
+                .filter_map(|clipped| match &clipped.shape {
+                    egui::Shape::Text(text) => Some(text.galley.text().to_owned()),
+                    _ => None,
+                })
+                .collect_vec()
+        };
+        // With no image loaders, the image can't load and the glyph stands in.
+        let ctx = egui::Context::default();
+        assert_eq!(vec![DRAG_HANDLE_GLYPH], drawn_text(&ctx));
+
+        egui_extras::install_image_loaders(&ctx);
+        assert!(drawn_text(&ctx).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_combinations() {
+        let hepatitis = ["Hepatitis A", "Hepatitis B", "Hepatitis A&B"];
+        let enabled = |profile: &Profile| {
+            profile
+                .vaccines
+                .iter()
+                .filter(|v| v.enabled && hepatitis.contains(&v.name.as_str()))
+                .map(|v| v.name.clone())
+                .sorted()
+                .collect_vec()
+        };
+        let all_on = || {
+            let mut profile = Profile::default();
+            for v in profile.vaccines.iter_mut() {
+                v.enabled = hepatitis.contains(&v.name.as_str());
+            }
+            profile
+        };
+
+        let mut profile = all_on();
+        assert_eq!(
+            vec![
+                ("Hepatitis A".to_owned(), "Hepatitis A&B".to_owned()),
+                ("Hepatitis B".to_owned(), "Hepatitis A&B".to_owned())
+            ],
+            profile
+                .resolve_combinations(true, None)
+                .into_iter()
+                .sorted()
+                .collect_vec()
+        );
+        assert_eq!(vec!["Hepatitis A&B"], enabled(&profile));
+        // Nothing left to resolve, either way.
+        assert!(profile.resolve_combinations(true, None).is_empty());
+        assert!(profile.resolve_combinations(false, None).is_empty());
+
+        let mut profile = all_on();
+        profile
+            .vaccines
+            .iter_mut()
+            .find(|v| v.name == "Hepatitis B")
+            .unwrap()
+            .enabled = false;
+        let changes = profile.resolve_combinations(false, None);
+        assert_eq!(1, changes.len());
+        assert_eq!("Hepatitis A&B", changes[0].0);
+        assert_eq!(vec!["Hepatitis A", "Hepatitis B"], enabled(&profile));
+    }
+
+    #[test]
+    fn test_enabling_a_combination_sticks() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.prefer_combined = false;
+        let set_enabled = |app: &mut VaccineHelperApp, names: &[&str]| {
+            for v in app.profile_mut().vaccines.iter_mut() {
+                v.enabled = names.contains(&v.name.as_str());
+            }
+        };
+        let enabled = |app: &VaccineHelperApp| {
+            app.profile()
+                .enabled_names()
+                .into_iter()
+                .sorted()
+                .collect_vec()
+        };
+        set_enabled(&mut app, &["Hepatitis A", "Hepatitis B"]);
+        assert!(app.sync_combinations().is_empty());
+
+        // Turning the combination on wins over the preference for components, and stays on.
+        set_enabled(&mut app, &["Hepatitis A", "Hepatitis B", "Hepatitis A&B"]);
+        assert_eq!(2, app.sync_combinations().len());
+        assert_eq!(vec!["Hepatitis A&B"], enabled(&app));
+        assert!(app.sync_combinations().is_empty());
+        assert_eq!(vec!["Hepatitis A&B"], enabled(&app));
+
+        // Turning a component back on hands over to all of the components.
+        set_enabled(&mut app, &["Hepatitis A", "Hepatitis A&B"]);
+        assert_eq!(1, app.sync_combinations().len());
+        assert_eq!(vec!["Hepatitis A", "Hepatitis B"], enabled(&app));
+
+        // Changing the preference settles overlaps its way.
+        set_enabled(&mut app, &["Hepatitis A", "Hepatitis B", "Hepatitis A&B"]);
+        app.combinations_checked = None;
+        assert!(app.sync_combinations().is_empty());
+        app.prefer_combined = true;
+        assert_eq!(2, app.sync_combinations().len());
+        assert_eq!(vec!["Hepatitis A&B"], enabled(&app));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_to_age() {
+        let mut profile = Profile {
+            end_plan_year: 2040,
+            plan_to_age: Some(90),
+            ..Profile::default()
+        };
+        // Without a date of birth the stored year stands.
+        profile.sync_end_plan_year();
+        assert_eq!(2040, profile.end_plan_year);
+
+        profile.date_of_birth = Some(Date::constant(1990, 4, 15));
+        profile.sync_end_plan_year();
+        assert_eq!(2080, profile.end_plan_year);
+    }
+
+    #[test]
+    fn test_add_profile() -> Result<()> {
+        let mut app = VaccineHelperApp {
+            add_profile_name: "Kid".to_owned(),
+            add_profile_date_of_birth: Some(Date::new(2020, 5, 4)?),
+            add_profile_time_zone: " America/Denver ".to_owned(),
+            ..Default::default()
+        };
+        app.add_profile();
+        assert_eq!("Kid", app.active_profile);
+        assert_eq!(Some(Date::new(2020, 5, 4)?), app.profile().date_of_birth);
+        assert_eq!(TimeZone::get("America/Denver")?, app.profile().time_zone());
+        assert!(app.add_profile_name.is_empty());
+        assert!(app.add_profile_date_of_birth.is_none());
+        assert!(app.add_profile_time_zone.is_empty());
+
+        // A name is still enough.
+        app.add_profile_name = "Grandpa".to_owned();
+        app.add_profile();
+        assert_eq!("Grandpa", app.active_profile);
+        assert_eq!(None, app.profile().date_of_birth);
+        assert_eq!(None, app.profile().time_zone);
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_name_problem() {
+        let mut app = VaccineHelperApp::default();
+        assert_eq!(None, app.profile_name_problem("Kid"));
+        assert_eq!(Some("Enter a name."), app.profile_name_problem(" \t"));
+        assert_eq!(
+            Some("That name already exists."),
+            app.profile_name_problem(" Default ")
+        );
+
+        // Neither empty nor taken names replace anything.
+        app.profile_mut().notes = "keep me".to_owned();
+        for name in ["Default", "  "] {
+            app.add_profile_name = name.to_owned();
+            app.add_profile();
+        }
+        assert_eq!(1, app.profiles.len());
+        assert_eq!("keep me", app.profile().notes);
+    }
+
+    #[test]
+    fn test_renamed_vaccine_config() -> Result<()> {
+        let profile: Profile = ron::de::from_str(
+            r#"(vaccines: [(name: "Shinglex", enabled: true)], records: [(vaccine: "Shinglex", date: "2025-01-01T00:00:00+00:00[UTC]", kind: Dose(0), notes: "")], appointment_notes: {("Shinglex", Dose(1), 2025, 6): "bring card"})"#,
+        )?;
+        assert_eq!("Shingrix", profile.vaccines[0].name);
+        assert_eq!(
+            Some("bring card"),
+            profile
+                .appointment_notes
+                .get(&("Shingrix".to_owned(), DoseKind::Dose(1), 2025, 6))
+                .map(String::as_str)
+        );
+        let now = Date::new(2025, 6, 1)?.to_zoned(TimeZone::UTC)?;
+        let plan = profile.plan(&now)?;
+        assert_eq!(
+            ("Shingrix", DoseKind::Dose(1)),
+            (plan[0].vaccine(), plan[0].kind())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_doses_too_close() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let given = |y, m| -> Result<Zoned> { Ok(Date::new(y, m, 1)?.to_zoned(TimeZone::UTC)?) };
+        let tdap = |y, m, dose| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Tdap",
+                given(y, m)?,
+                DoseKind::Dose(dose),
+                "",
+            ))
+        };
+        app.profile_mut().records = vec![
+            tdap(2024, 1, 0)?,
+            VaccineRecord::new("Flu", given(2024, 1)?, DoseKind::Dose(0), ""),
+            tdap(2024, 2, 1)?,
+        ];
+        let now = app.now();
+        assert_eq!(
+            vec![None, None, Some(6)],
+            app.profile().doses_too_close(&now)?
+        );
+
+        // Six months apart is fine, and so is a date only known to the year.
+        app.profile_mut().records[2] = tdap(2024, 7, 1)?;
+        assert_eq!(vec![None; 3], app.profile().doses_too_close(&now)?);
+        app.profile_mut().records[2] = tdap(2024, 2, 1)?.with_precision(DatePrecision::Year);
+        assert_eq!(vec![None; 3], app.profile().doses_too_close(&now)?);
+
+        // Each dose is held to the gap before it: two rabies doses on the same day are fine, but
+        // the third comes a year later.
+        let rabies = |y, m, dose| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Rabies",
+                given(y, m)?,
+                DoseKind::Dose(dose),
+                "",
+            ))
+        };
+        app.profile_mut().records = vec![
+            rabies(2024, 1, 0)?,
+            rabies(2024, 1, 1)?,
+            rabies(2024, 4, 2)?,
+        ];
+        assert_eq!(
+            vec![None, None, Some(12)],
+            app.profile().doses_too_close(&now)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_in_plan() -> Result<()> {
+        let mut profile = Profile::default();
+        let date = Date::new(2025, 1, 1)?.to_zoned(TimeZone::UTC)?;
+        let tdap = VaccineRecord::new("Tdap", date.clone(), DoseKind::Dose(0), "");
+        let rabies = VaccineRecord::new("Rabies", date.clone(), DoseKind::Dose(0), "");
+        let unknown = VaccineRecord::new("Retired", date, DoseKind::Dose(0), "");
+        assert!(profile.record_in_plan(&tdap));
+        assert!(!profile.record_in_plan(&rabies));
+        assert!(!profile.record_in_plan(&unknown));
+
+        for v in profile.vaccines.iter_mut() {
+            v.enabled = v.name == "Rabies";
+        }
+        assert!(!profile.record_in_plan(&tdap));
+        assert!(profile.record_in_plan(&rabies));
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_profile_stable() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.recompute_schedule()?;
+        let given = |y, m| -> Result<Zoned> { Ok(Date::new(y, m, 1)?.to_zoned(TimeZone::UTC)?) };
+        app.profile_mut().records = vec![
+            VaccineRecord::new("Tdap", given(2024, 6)?, DoseKind::Dose(1), ""),
+            VaccineRecord::new("Tdap", given(2024, 1)?, DoseKind::Dose(0), ""),
+        ];
+        let keys = app
+            .profile()
+            .schedule
+            .iter()
+            .map(appointment_key)
+            .take(8)
+            .collect_vec();
+        for (i, key) in keys.into_iter().enumerate() {
+            app.profile_mut()
+                .appointment_notes
+                .insert(key, format!("note {i}"));
+        }
+
+        let data = serialize_profile_stable(app.profile())?;
+        assert_eq!(data, serialize_profile_stable(app.profile())?);
+        // A copy read back in has its notes in a new hash map, but is written out the same.
+        let copy: Profile = ron::de::from_str(&data)?;
+        assert_eq!(data, serialize_profile_stable(&copy)?);
+        assert_eq!(
+            Some(DoseKind::Dose(0)),
+            copy.records.first().map(|r| *r.kind())
+        );
+        assert!(!data.contains('\r'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_vaccine_order() -> Result<()> {
+        let config = |name: &str, enabled| VaccineConfig {
+            name: name.to_owned(),
+            enabled,
+            ..Default::default()
+        };
+        let mut profile = Profile {
+            vaccines: vec![
+                config("Tdap", true),
+                config("Flu", true),
+                config("Mpox", false),
+                config("Rabies", false),
+            ],
+            ..Default::default()
+        };
+        let source = Profile {
+            vaccines: vec![
+                config("Mpox", true),
+                config("Not a vaccine", true),
+                config("Tdap", false),
+            ],
+            ..Default::default()
+        };
+        let data = ron::ser::to_string(&VaccineOrder::of(&source))?;
+        let RonImport::VaccineOrder(order) = RonImport::parse(&data)? else {
+            panic!("not read as a vaccine order");
+        };
+
+        // Unknown names are skipped, and vaccines missing from the order go at the end.
+        assert_eq!(1, profile.apply_vaccine_order(&order));
+        assert_eq!(
+            vec![
+                ("Mpox", true),
+                ("Tdap", false),
+                ("Flu", true),
+                ("Rabies", false)
+            ],
+            profile
+                .vaccines
+                .iter()
+                .map(|v| (v.name.as_str(), v.enabled))
+                .collect_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reconcile_vaccines() -> Result<()> {
+        let vaccines = Vaccine::get_vaccines();
+        let mut profile = Profile::default();
+        assert!(profile.reconcile_vaccines(vaccines, true).is_empty());
+
+        // Tdap is new in this update, and "Retired" was dropped from it. Shinglex is the old
+        // name of a vaccine that is already in the list.
+        profile
+            .vaccines
+            .retain(|v| v.name != "Tdap" && v.name != "Rabies");
+        profile.vaccines.insert(
+            0,
+            VaccineConfig {
+                name: "Retired".to_owned(),
+                enabled: true,
+                ..Default::default()
+            },
+        );
+        profile.vaccines.push(VaccineConfig {
+            name: "Shinglex".to_owned(),
+            ..Default::default()
+        });
+        let changes = profile.reconcile_vaccines(vaccines, true);
+        assert_eq!(
+            VaccineListChanges {
+                added: vec![("Rabies".to_owned(), false), ("Tdap".to_owned(), true)],
+                removed: vec!["Retired".to_owned()],
+            },
+            changes
+        );
+        assert_eq!(
+            "added Rabies, Tdap (enabled); removed Retired",
+            changes.to_string()
+        );
+        assert_eq!(vaccines.len(), profile.vaccines.len());
+        assert_eq!(
+            1,
+            profile
+                .vaccines
+                .iter()
+                .filter(|v| v.name == "Shingrix")
+                .count()
+        );
+        assert!(profile
+            .vaccines
+            .iter()
+            .all(|v| vaccines.contains_key(v.name.as_str())));
+
+        profile.vaccines.retain(|v| v.name != "Tdap");
+        let changes = profile.reconcile_vaccines(vaccines, false);
+        assert_eq!(vec![("Tdap".to_owned(), false)], changes.added);
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_v1() -> Result<()> {
+        let app = VaccineHelperApp::from_ron(V1_BLOB)?.0;
+        assert_eq!(SCHEMA_VERSION, app.schema_version);
+        assert_eq!("Alice", app.active_profile);
+        assert_eq!(1, app.profile().records.len());
+        assert_eq!(2080, app.profile().end_plan_year);
+        assert!(app.profile().schedule.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_current_schema_round_trips() -> Result<()> {
+        let app = VaccineHelperApp::default();
+        let data = ron::ser::to_string(&app)?;
+        let app = VaccineHelperApp::from_ron(&data)?.0;
+        assert_eq!(SCHEMA_VERSION, app.schema_version);
+        assert_eq!("Default", app.active_profile);
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows_start_closed() -> Result<()> {
+        let app = VaccineHelperApp {
+            show_about: true,
+            show_preferences: true,
+            show_timeline: true,
+            hide_completed: true,
+            ..Default::default()
+        };
+        let data = ron::ser::to_string(&app)?;
+        assert!(!data.contains("show_about"));
+        let app = VaccineHelperApp::from_ron(&data)?.0;
+        assert!(!app.show_about && !app.show_preferences && !app.show_timeline);
+        assert!(app.hide_completed);
+
+        // Older saves that still have the window state are read without it.
+        assert!(!VaccineHelperApp::from_ron(V1_BLOB)?.0.show_about);
+        Ok(())
+    }
+
+    fn app_at(y: i16, m: i8, d: i8) -> Result<VaccineHelperApp> {
+        let mut app = VaccineHelperApp {
+            now_override: Some(
+                Date::new(y, m, d)?.to_zoned(TimeZone::get("America/Los_Angeles")?)?,
+            ),
+            ..Default::default()
+        };
+        app.profile_mut().end_plan_year = y + 2;
+        Ok(app)
+    }
+
+    #[test]
+    fn test_focus_mode_shows_actionable() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.reminder_lead_months = 1;
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Tdap";
+        }
+        let given = |y, m| -> Result<Zoned> { Ok(Date::new(y, m, 1)?.to_zoned(TimeZone::UTC)?) };
+        app.profile_mut().records = vec![
+            VaccineRecord::new("Tdap", given(2024, 1)?, DoseKind::Dose(0), ""),
+            VaccineRecord::new("Tdap", given(2024, 3)?, DoseKind::Dose(1), "").with_invalid(true),
+            VaccineRecord::new("Flu", given(2024, 10)?, DoseKind::Booster, ""),
+        ];
+        app.recompute_schedule()?;
+
+        // The repeat of the invalid dose is due now; the third dose, six months later, is not
+        // due soon yet.
+        let now = app.now();
+        let urgencies = app.appointment_urgencies(&now, &app.restarting_vaccines(&now)?)?;
+        let (appointments, records) = app.actionable(&urgencies);
+        assert_eq!(
+            vec![("Tdap", DoseKind::Dose(1))],
+            appointments
+                .iter()
+                .map(|a| (a.vaccine(), a.kind()))
+                .collect_vec()
+        );
+        assert!(app
+            .profile()
+            .schedule
+            .iter()
+            .any(|a| a.kind() == DoseKind::Dose(2)));
+        assert_eq!(1, records.len());
+        assert!(records[0].invalid());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_skips_are_pruned() -> Result<()> {
+        let mut app = VaccineHelperApp::default();
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Tdap";
+        }
+        app.recompute_schedule()?;
+        let first = app.profile().schedule[0].clone();
+        app.profile_mut().skipped.push(first.clone());
+        app.recompute_schedule()?;
+        assert_eq!(vec![first.clone()], app.profile().skipped);
+
+        // A preview doesn't drop the skips of today.
+        app.now_override = Some(app.now().checked_add(jiff::Span::new().years(2))?);
+        app.recompute_schedule()?;
+        assert_eq!(vec![first.clone()], app.profile().skipped);
+        app.now_override = None;
+
+        // Once the dose is on record, the skip has nothing left to move.
+        let now = app.now();
+        app.profile_mut()
+            .records
+            .push(VaccineRecord::new("Tdap", now, first.kind(), ""));
+        app.recompute_schedule()?;
+        assert!(app.profile().skipped.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_simulation_scrubs_plan() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.profile_mut().end_plan_year = 2040;
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = v.name == "Tdap";
+        }
+        let given = |y, m| -> Result<Zoned> { Ok(Date::new(y, m, 1)?.to_zoned(TimeZone::UTC)?) };
+        app.profile_mut().records = vec![
+            VaccineRecord::new("Tdap", given(2020, 1)?, DoseKind::Dose(0), ""),
+            VaccineRecord::new("Tdap", given(2020, 7)?, DoseKind::Dose(1), ""),
+            VaccineRecord::new("Tdap", given(2021, 1)?, DoseKind::Dose(2), ""),
+        ];
+        let due = |app: &mut VaccineHelperApp| -> Result<Vec<(String, DoseKind)>> {
+            app.refresh_schedule()?;
+            let now = app.now();
+            let urgencies = app.appointment_urgencies(&now, &app.restarting_vaccines(&now)?)?;
+            Ok(app
+                .actionable(&urgencies)
+                .0
+                .iter()
+                .map(|appt| (appt.vaccine().to_owned(), appt.kind()))
+                .collect())
+        };
+        let today = app.now_override.clone();
+
+        app.start_simulation();
+        assert_eq!(14 * 12 + 7, app.simulation_span());
+        assert!(due(&mut app)?.is_empty());
+        // The ten year booster comes due in January 2031.
+        app.scrub_simulation(67)?;
+        assert_eq!(
+            Some((2031, 1)),
+            app.now_override
+                .as_ref()
+                .map(|now| (now.year(), now.month()))
+        );
+        assert_eq!(vec![("Tdap".to_owned(), DoseKind::Booster)], due(&mut app)?);
+        assert_eq!(3, app.profile().records.len());
+
+        // Scrubbing stays within the plan, and closing goes back to the date it started from.
+        app.scrub_simulation(1000)?;
+        assert_eq!(Some(2040), app.now_override.as_ref().map(|now| now.year()));
+        app.stop_simulation();
+        assert_eq!(today, app.now_override);
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview_shifts_plan() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        app.recompute_schedule()?;
+        let first = &app.profile().schedule[0];
+        assert_eq!((2025, 6), (first.year(), first.month()));
+
+        app.set_preview(Some(Date::new(2026, 3, 15)?));
+        app.recompute_schedule()?;
+        let first = &app.profile().schedule[0];
+        assert_eq!((2026, 3), (first.year(), first.month()));
+        assert!(app.profile().records.is_empty());
+
+        app.set_preview(None);
+        assert!(app.now_override.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_appointment() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        // Tdap and Mpox are both due this month; the one listed first wins.
+        for names in [["Tdap", "Mpox"], ["Mpox", "Tdap"]] {
+            let profile = app.profile_mut();
+            profile
+                .vaccines
+                .retain(|v| names.contains(&v.name.as_str()));
+            profile
+                .vaccines
+                .sort_by_key(|v| names.iter().position(|n| *n == v.name));
+            for v in profile.vaccines.iter_mut() {
+                v.enabled = true;
+            }
+            app.recompute_schedule()?;
+            let next = app.next_appointment().expect("something is due");
+            assert_eq!((names[0], DoseKind::Dose(0)), (next.vaccine(), next.kind()));
+            assert_eq!((2025, 6), (next.year(), next.month()));
+        }
+
+        // Marking Mpox's first dose done moves on to Tdap.
+        let next = app.next_appointment().cloned().expect("something is due");
+        app.mark_done(&next)?;
+        let next = app.next_appointment().expect("something is due");
+        assert_eq!(("Tdap", DoseKind::Dose(0)), (next.vaccine(), next.kind()));
+        assert_eq!(1, app.profile().records.len());
+
+        for v in app.profile_mut().vaccines.iter_mut() {
+            v.enabled = false;
+        }
+        app.recompute_schedule()?;
+        assert!(app.next_appointment().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_onboarding_configures_profile() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        assert!(app.needs_onboarding());
+        app.onboarding = Some(Onboarding {
+            step: Onboarding::STEPS - 1,
+            date_of_birth: Some(Date::new(2024, 12, 1)?),
+            hemisphere: Hemisphere::Southern,
+            pregnant: false,
+            has_records: true,
+            childhood_vaccines: false,
+        });
+        assert!(app.finish_onboarding()?);
+        assert!(!app.needs_onboarding());
+        assert!(app.onboarding.is_none());
+
+        let profile = app.profile();
+        assert_eq!(Some(Date::new(2024, 12, 1)?), profile.date_of_birth);
+        assert_eq!(Hemisphere::Southern, profile.hemisphere);
+        assert_eq!(
+            Some(&false),
+            profile.contraindications.get(&Contraindication::Pregnant)
+        );
+        let enabled = |name: &str| profile.vaccines.iter().any(|v| v.name == name && v.enabled);
+        // Six months old: old enough for MMR within the year, but not for Tdap or Shingrix.
+        assert!(enabled("Hepatitis B") && enabled("Flu") && enabled("MMR"));
+        assert!(!enabled("Tdap") && !enabled("Shingrix") && !enabled("Rabies"));
+        assert!(!profile.schedule.is_empty());
+
+        // Anyone who already has data is not asked.
+        let mut app = app_at(2025, 6, 1)?;
+        app.profile_mut().date_of_birth = Some(Date::new(1980, 1, 1)?);
+        assert!(!app.needs_onboarding());
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_changes_in_one_pass() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let tz = app.profile().time_zone();
+        let record = |year| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Flu",
+                Date::new(year, 10, 1)?.to_zoned(tz.clone())?,
+                DoseKind::Booster,
+                &year.to_string(),
+            ))
+        };
+        app.profile_mut().records = vec![record(2020)?, record(2021)?, record(2022)?];
+
+        // The first row's date is moved past the others, then the second row is deleted while the
+        // edit is still open.
+        app.editing_record = Some(0);
+        *app.profile_mut().records[0].date_mut() = Date::new(2023, 10, 1)?.to_zoned(tz.clone())?;
+        app.apply_record_changes(&[1], false, None);
+        assert_eq!(None, app.editing_record);
+        assert_eq!(
+            vec!["2022", "2020"],
+            app.profile()
+                .records
+                .iter()
+                .map(|r| r.notes())
+                .collect_vec()
+        );
+
+        // Opening another row while one is being edited opens the row that was clicked.
+        app.editing_record = Some(0);
+        *app.profile_mut().records[0].date_mut() = Date::new(2024, 10, 1)?.to_zoned(tz.clone())?;
+        app.apply_record_changes(&[], false, Some(1));
+        assert_eq!(
+            vec!["2020", "2022"],
+            app.profile()
+                .records
+                .iter()
+                .map(|r| r.notes())
+                .collect_vec()
+        );
+        assert_eq!(Some(0), app.editing_record);
+
+        // An import while the edit is open closes it before the records are re-sorted.
+        *app.profile_mut().records[0].date_mut() = Date::new(2025, 1, 1)?.to_zoned(tz.clone())?;
+        app.import_csv("2019-05-01,Tdap,Booster,2019\n", &tz);
+        assert_eq!(None, app.editing_record);
+        assert_eq!(
+            vec!["2019", "2022", "2020"],
+            app.profile()
+                .records
+                .iter()
+                .map(|r| r.notes())
+                .collect_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_add_records() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let errors = app.bulk_add_records(
+            "2020-06-01, Tdap, Dose#1\n\
+             2020-06-01, Tdap\n\
+             \n\
+             2021-10-15, Flu, Booster\n\
+             2021-02-30, Flu, Booster\n",
+        );
+        assert_eq!(2, errors.len());
+        assert!(errors[0].starts_with("line 2:"), "{errors:?}");
+        assert!(errors[1].starts_with("line 5:"), "{errors:?}");
+        assert!(app.profile().records.is_empty());
+
+        let errors = app.bulk_add_records("2021-10-15, Flu, Booster\n2020-06-01, Tdap, Dose#1\n");
+        assert!(errors.is_empty());
+        let records = &app.profile().records;
+        assert_eq!(
+            vec!["Tdap", "Flu"],
+            records.iter().map(|r| r.vaccine()).collect_vec()
+        );
+        assert!(records
+            .iter()
+            .all(|r| r.source() == RecordSource::SelfReported));
+
+        // Lines already on file are skipped rather than added twice.
+        assert!(app
+            .bulk_add_records("2020-06-01, Tdap, Dose#1\n")
+            .is_empty());
+        assert_eq!(2, app.profile().records.len());
+        assert_eq!(
+            Some("Imported 0 records. Skipped 1 already on file."),
+            app.import_message.as_deref()
+        );
+
+        // A misspelled name goes to review instead of creating a record for it.
+        assert!(app
+            .bulk_add_records("2022-01-01, Tdpa, Booster\n")
+            .is_empty());
+        assert_eq!(2, app.profile().records.len());
+        assert!(app.import_review.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_assume_childhood_vaccines() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let tz = app.profile().time_zone();
+        app.profile_mut().date_of_birth = Some(Date::new(1990, 7, 15)?);
+        app.profile_mut().assume_childhood_vaccines = true;
+        app.profile_mut().sync_childhood_baseline()?;
+        app.recompute_schedule()?;
+
+        let seeded = app
+            .profile()
+            .records
+            .iter()
+            .map(|r| (r.vaccine(), r.date().date().to_string(), *r.kind()))
+            .collect_vec();
+        assert_eq!(
+            vec![
+                ("Hepatitis B", "1990-07-15".to_owned(), DoseKind::Dose(0)),
+                ("IPV", "1990-09-15".to_owned(), DoseKind::Dose(0)),
+                ("IPV", "1991-01-15".to_owned(), DoseKind::Dose(1)),
+                ("IPV", "1991-07-15".to_owned(), DoseKind::Dose(2)),
+                ("MMR", "1991-07-15".to_owned(), DoseKind::Dose(0)),
+                ("IPV", "1994-07-15".to_owned(), DoseKind::Dose(3)),
+                ("MMR", "1996-07-15".to_owned(), DoseKind::Dose(1)),
+            ],
+            seeded
+        );
+        assert!(app
+            .profile()
+            .records
+            .iter()
+            .all(|r| r.source() == RecordSource::Assumed));
+        let planned = |app: &VaccineHelperApp, name: &str| {
+            app.profile()
+                .schedule
+                .iter()
+                .any(|appt| appt.vaccine() == name && appt.kind() != DoseKind::Booster)
+        };
+        assert!(!planned(&app, "MMR") && !planned(&app, "IPV"));
+
+        // A real record of one vaccine takes the place of its assumed ones.
+        let mmr = VaccineRecord::new(
+            "MMR",
+            Date::new(1991, 8, 1)?.to_zoned(tz.clone())?,
+            DoseKind::Dose(0),
+            "",
+        );
+        app.profile_mut().records.push(mmr.clone());
+        app.profile_mut().sync_childhood_baseline()?;
+        assert_eq!(
+            vec![&mmr],
+            app.profile()
+                .records
+                .iter()
+                .filter(|r| r.vaccine() == "MMR")
+                .collect_vec()
+        );
+
+        // Taking the assumption back out leaves only the real records, even one whose notes
+        // happen to match.
+        let copied = VaccineRecord::new(
+            "Tdap",
+            Date::new(2001, 8, 1)?.to_zoned(tz)?,
+            DoseKind::Dose(0),
+            ASSUMED_RECORD_NOTE,
+        );
+        app.profile_mut().records.push(copied.clone());
+        app.profile_mut().assume_childhood_vaccines = false;
+        app.profile_mut().sync_childhood_baseline()?;
+        assert_eq!(vec![mmr, copied], app.profile().records);
+        app.recompute_schedule()?;
+        assert!(planned(&app, "IPV"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_low_confidence_is_surfaced() {
+        let vaccines = Vaccine::get_vaccines();
+        let flagged = &vaccines["Meningitis"];
+        assert!(flagged.confidence().needs_review());
+        assert!(vaccine_summary(flagged).contains("Confidence: needs review"));
+        let established = &vaccines["Hepatitis B"];
+        assert!(!established.confidence().needs_review());
+        assert!(vaccine_summary(established).contains("Confidence: well-established"));
+    }
+
+    #[test]
+    fn test_copy_selected_records() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        let now = app.now();
+        let record = |vaccine: &str, kind| VaccineRecord::new(vaccine, now.clone(), kind, "");
+        let mut sibling = Profile {
+            records: vec![
+                record("MMR", DoseKind::Dose(0)),
+                record("Chickenpox", DoseKind::Dose(0)),
+                record("Tdap", DoseKind::Dose(0)),
+            ],
+            ..Profile::default()
+        };
+        sibling.records.sort();
+        app.profiles.insert("Sibling".to_owned(), sibling);
+        // Already here, though checked against a registry.
+        app.profile_mut()
+            .records
+            .push(record("MMR", DoseKind::Dose(0)).with_source(RecordSource::Verified));
+
+        // Pick MMR, which is already here, and Tdap, but not Chickenpox.
+        let picks = app.profiles["Sibling"]
+            .records
+            .iter()
+            .map(|r| r.vaccine() != "Chickenpox")
+            .collect();
+        app.copy_records = Some(("Sibling".to_owned(), picks));
+        assert_eq!(1, app.copy_selected_records());
+        assert!(app.copy_records.is_none());
+        let vaccines = app
+            .profile()
+            .records
+            .iter()
+            .map(|r| r.vaccine())
+            .sorted()
+            .collect_vec();
+        assert_eq!(vec!["MMR", "Tdap"], vaccines);
+        assert_eq!(3, app.profiles["Sibling"].records.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_refresh_schedule_only_on_change() -> Result<()> {
+        let mut app = app_at(2025, 6, 1)?;
+        assert!(app.refresh_schedule()?);
+        assert!(!app.refresh_schedule()?);
+        assert!(!app.refresh_schedule()?);
+
+        // Anything the plan depends on makes it again.
+        let now = app.now();
+        app.profile_mut().records.push(VaccineRecord::new(
+            "Tdap",
+            now.clone(),
+            DoseKind::Dose(0),
+            "",
+        ));
+        assert!(app.refresh_schedule()?);
+        assert!(!app.refresh_schedule()?);
+        app.profile_mut().vaccines.swap(0, 1);
+        assert!(app.refresh_schedule()?);
+        app.profile_mut().end_plan_year += 1;
+        assert!(app.refresh_schedule()?);
+        app.profile_mut().vaccines[0].enabled ^= true;
+        assert!(app.refresh_schedule()?);
+        app.set_preview(Some(Date::new(2025, 6, 2)?));
+        assert!(app.refresh_schedule()?);
+        assert!(!app.refresh_schedule()?);
+
+        // A stale plan, e.g. one loaded from disk, is made again too.
+        app.profile_mut().schedule.clear();
+        assert!(app.refresh_schedule()?);
+        assert!(!app.profile().schedule.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_uses_injected_clock() -> Result<()> {
+        let mut app = app_at(2025, 12, 31)?;
+        app.recompute_schedule()?;
+        let first = &app.profile().schedule[0];
+        assert_eq!((2025, 12), (first.year(), first.month()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_by_month_year_rollover() -> Result<()> {
+        let mut app = app_at(2025, 12, 31)?;
+        app.recompute_schedule()?;
+        let months = app
+            .schedule_by_month()
+            .into_iter()
+            .map(|(ym, _)| ym)
+            .collect_vec();
+        // COVID-19's second dose lands in January of the following year.
+        assert_eq!(Some(&(2025, 12)), months.first());
+        assert!(months.contains(&(2026, 1)));
+        assert!(months.is_sorted());
+        assert!(months.iter().all(|(y, _)| *y < 2027));
+        Ok(())
+    }
 }
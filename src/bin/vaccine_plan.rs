@@ -0,0 +1,17 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+use std::process::ExitCode;
+
+// Print the plan for a saved profile, for scripts and other headless use. See `cli::USAGE`.
+fn main() -> ExitCode {
+    match vaccine_helper::cli::run(std::env::args().skip(1)) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
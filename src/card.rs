@@ -0,0 +1,254 @@
+use crate::schedule::VaccineRecord;
+use ab_glyph::{point, Font, FontRef, Glyph, ScaleFont};
+use anyhow::{Context, Result};
+use egui::{pos2, vec2, Align2, Color32, FontDefinitions, FontFamily, Pos2, Rect};
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+
+pub const PNG_MIME_TYPE: &str = "image/png";
+
+// Records per card. Longer lists are split over several cards rather than shrunk to fit, so
+// that every card stays legible when printed.
+pub const ROWS_PER_CARD: usize = 25;
+
+const WIDTH: f32 = 560.0;
+const MARGIN: f32 = 24.0;
+const HEADER_HEIGHT: f32 = 96.0;
+const ROW_HEIGHT: f32 = 22.0;
+const FOOTER_HEIGHT: f32 = 36.0;
+// Pixels per point, so that the text stays sharp when printed.
+const SCALE: f32 = 2.0;
+
+const PAPER: Color32 = Color32::from_rgb(250, 240, 200);
+const INK: Color32 = Color32::from_rgb(30, 30, 30);
+const RULE: Color32 = Color32::from_rgb(150, 140, 110);
+
+// A wallet-card style summary of the records, as one PNG per card: the app title, the
+// profile's name, and a vaccine / date / dose table, oldest first.
+pub fn record_cards(profile: &str, records: &[VaccineRecord]) -> Result<Vec<Vec<u8>>> {
+    let chunks = records.chunks(ROWS_PER_CARD).collect::<Vec<_>>();
+    let pages = chunks.len().max(1);
+    (0..pages)
+        .map(|page| {
+            let rows = chunks.get(page).copied().unwrap_or_default();
+            encode_png(&render_card(profile, rows, page, pages)?)
+        })
+        .collect()
+}
+
+// File names for the cards from `record_cards`, numbered if there is more than one.
+pub fn card_filename(page: usize, pages: usize) -> String {
+    if pages > 1 {
+        format!("immunization_card_{}.png", page + 1)
+    } else {
+        "immunization_card.png".to_owned()
+    }
+}
+
+fn render_card(
+    profile: &str,
+    records: &[VaccineRecord],
+    page: usize,
+    pages: usize,
+) -> Result<RgbaImage> {
+    let height = HEADER_HEIGHT + (records.len().max(1) as f32 + 1.0) * ROW_HEIGHT + FOOTER_HEIGHT;
+    let columns = [MARGIN, MARGIN + 220.0, MARGIN + 360.0];
+
+    // Draw with egui's own proportional font, so the card matches the app.
+    let fonts = FontDefinitions::default();
+    let data = fonts
+        .families
+        .get(&FontFamily::Proportional)
+        .and_then(|names| names.first())
+        .and_then(|name| fonts.font_data.get(name))
+        .context("no proportional font")?;
+    let font = FontRef::try_from_slice_and_index(&data.font, data.index)?;
+
+    let mut image = RgbaImage::from_pixel(
+        (WIDTH * SCALE).round() as u32,
+        (height * SCALE).round() as u32,
+        rgba(PAPER),
+    );
+    let border = Rect::from_min_size(Pos2::ZERO, vec2(WIDTH, height)).shrink(6.0);
+    for side in [
+        Rect::from_min_max(border.min, pos2(border.max.x, border.min.y + 2.0)),
+        Rect::from_min_max(pos2(border.min.x, border.max.y - 2.0), border.max),
+        Rect::from_min_max(border.min, pos2(border.min.x + 2.0, border.max.y)),
+        Rect::from_min_max(pos2(border.max.x - 2.0, border.min.y), border.max),
+    ] {
+        fill(&mut image, side, RULE);
+    }
+    text(
+        &mut image,
+        &font,
+        pos2(MARGIN, MARGIN),
+        Align2::LEFT_TOP,
+        "Vaccine Helper — Immunization Record",
+        20.0,
+        INK,
+    );
+    text(
+        &mut image,
+        &font,
+        pos2(MARGIN, MARGIN + 36.0),
+        Align2::LEFT_TOP,
+        &format!("Name: {profile}"),
+        16.0,
+        INK,
+    );
+
+    let mut y = HEADER_HEIGHT;
+    for (x, title) in columns.iter().zip(["Vaccine", "Date", "Dose"]) {
+        let at = pos2(*x, y + ROW_HEIGHT / 2.0);
+        text(&mut image, &font, at, Align2::LEFT_CENTER, title, 14.0, INK);
+    }
+    y += ROW_HEIGHT;
+    fill(&mut image, rule(y, 1.0), RULE);
+    for record in records {
+        let cells = [
+            record.vaccine().to_owned(),
+            record.date().date().to_string(),
+            record.kind().to_string(),
+        ];
+        for (x, cell) in columns.iter().zip(cells) {
+            let at = pos2(*x, y + ROW_HEIGHT / 2.0);
+            text(&mut image, &font, at, Align2::LEFT_CENTER, &cell, 13.0, INK);
+        }
+        y += ROW_HEIGHT;
+        fill(&mut image, rule(y, 0.5), RULE);
+    }
+    if records.is_empty() {
+        let at = pos2(MARGIN, y + ROW_HEIGHT / 2.0);
+        text(
+            &mut image,
+            &font,
+            at,
+            Align2::LEFT_CENTER,
+            "No records.",
+            13.0,
+            INK,
+        );
+    }
+    if pages > 1 {
+        text(
+            &mut image,
+            &font,
+            pos2(WIDTH - MARGIN, height - MARGIN),
+            Align2::RIGHT_BOTTOM,
+            &format!("Card {} of {pages}", page + 1),
+            12.0,
+            RULE,
+        );
+    }
+    Ok(image)
+}
+
+fn rgba(color: Color32) -> Rgba<u8> {
+    Rgba(color.to_srgba_unmultiplied())
+}
+
+// A horizontal rule across the card, `width` points thick.
+fn rule(y: f32, width: f32) -> Rect {
+    Rect::from_min_max(
+        pos2(MARGIN, y - width / 2.0),
+        pos2(WIDTH - MARGIN, y + width / 2.0),
+    )
+}
+
+// Fill a rectangle, given in points, with an opaque color.
+fn fill(image: &mut RgbaImage, rect: Rect, color: Color32) {
+    let [x0, y0, x1, y1] = [rect.min.x, rect.min.y, rect.max.x, rect.max.y]
+        .map(|v| (v * SCALE).round().max(0.0) as u32);
+    for y in y0..y1.min(image.height()) {
+        for x in x0..x1.min(image.width()) {
+            image.put_pixel(x, y, rgba(color));
+        }
+    }
+}
+
+// Draw a line of text, placed by `align` around `pos`, in points; `size` is the font size.
+fn text(
+    image: &mut RgbaImage,
+    font: &FontRef<'_>,
+    pos: Pos2,
+    align: Align2,
+    text: &str,
+    size: f32,
+    color: Color32,
+) {
+    let font = font.as_scaled(size * SCALE);
+    let mut glyphs = vec![];
+    let mut width = 0.0;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = glyphs.last().map(|g: &Glyph| g.id) {
+            width += font.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(font.scale(), point(width, 0.0)));
+        width += font.h_advance(id);
+    }
+    let origin = align
+        .anchor_size(pos * SCALE, vec2(width, font.ascent() - font.descent()))
+        .min;
+    for mut glyph in glyphs {
+        glyph.position += point(origin.x, origin.y + font.ascent());
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|x, y, coverage| {
+            let (x, y) = (
+                bounds.min.x as i64 + x as i64,
+                bounds.min.y as i64 + y as i64,
+            );
+            let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+                return;
+            };
+            if let Some(pixel) = image.get_pixel_mut_checked(x, y) {
+                let ink = rgba(color);
+                for i in 0..3 {
+                    let mixed = pixel[i] as f32 * (1.0 - coverage) + ink[i] as f32 * coverage;
+                    pixel[i] = mixed.round() as u8;
+                }
+            }
+        });
+    }
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let mut png = Cursor::new(vec![]);
+    image.write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::DoseKind;
+    use jiff::{civil::Date, tz::TimeZone};
+
+    fn records(count: usize) -> Result<Vec<VaccineRecord>> {
+        let date = Date::new(2024, 3, 1)?.to_zoned(TimeZone::UTC)?;
+        Ok((0..count)
+            .map(|_| VaccineRecord::new("Tdap", date.clone(), DoseKind::Dose(0), "left arm"))
+            .collect())
+    }
+
+    #[test]
+    fn test_record_cards() -> Result<()> {
+        let cards = record_cards("Alice", &records(3)?)?;
+        assert_eq!(1, cards.len());
+        let image = image::load_from_memory(&cards[0])?.to_rgba8();
+        assert_eq!(WIDTH * SCALE, image.width() as f32);
+        // The text is actually drawn, not just the paper.
+        assert!(image.pixels().any(|p| p[0] < 100));
+
+        assert_eq!(
+            2,
+            record_cards("Alice", &records(ROWS_PER_CARD + 1)?)?.len()
+        );
+        assert_eq!(1, record_cards("Alice", &[])?.len());
+        assert_eq!("immunization_card_2.png", card_filename(1, 2));
+        Ok(())
+    }
+}
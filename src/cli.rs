@@ -0,0 +1,98 @@
+use crate::{app, ics, schedule::VaccineAppointment, text, tz};
+use anyhow::{bail, Result};
+use jiff::Zoned;
+use std::str::FromStr;
+
+pub const USAGE: &str = "usage: vaccine_plan <profile.ron> [--format text|json|ics|ron]";
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Ics,
+    Ron,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "text" => Self::Text,
+            "json" => Self::Json,
+            "ics" => Self::Ics,
+            "ron" => Self::Ron,
+            _ => bail!("unknown format: {s}"),
+        })
+    }
+}
+
+// Plan a saved profile, or the active profile of a saved app, as of `now`, and render it.
+pub fn plan_from_ron(content: &str, now: &Zoned, format: Format) -> Result<String> {
+    let (appointments, notes) = app::plan_saved(content, now)?;
+    Ok(match format {
+        Format::Text => text::plan_to_text(&appointments, |_| true),
+        Format::Json => appointments_to_json(&appointments),
+        Format::Ics => ics::schedule_to_ics(&appointments, &notes, |_| true),
+        Format::Ron => {
+            ron::ser::to_string_pretty(&appointments, ron::ser::PrettyConfig::default())? + "\n"
+        }
+    })
+}
+
+// Run with the command line arguments, not including the program name, and return what should
+// be printed.
+pub fn run(args: impl Iterator<Item = String>) -> Result<String> {
+    let mut path = None;
+    let mut format = Format::default();
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let Some(value) = args.next() else {
+                    bail!("--format needs a value\n{USAGE}");
+                };
+                format = value.parse()?;
+            }
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => bail!("unexpected argument: {arg}\n{USAGE}"),
+        }
+    }
+    let Some(path) = path else {
+        bail!("{USAGE}");
+    };
+    let content = std::fs::read_to_string(&path)?;
+    plan_from_ron(&content, &tz::now(), format)
+}
+
+fn appointments_to_json(appointments: &[VaccineAppointment]) -> String {
+    let entries = appointments
+        .iter()
+        .map(|appt| {
+            format!(
+                "  {{\"vaccine\": \"{}\", \"kind\": \"{}\", \"date\": \"{}\"}}",
+                escape_json(appt.vaccine()),
+                appt.kind(),
+                appt.date()
+            )
+        })
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return "[]\n".to_owned();
+    }
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+fn escape_json(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,213 @@
+use crate::schedule::{DoseKind, Vaccine, VaccineAppointment, VaccineRecord};
+use itertools::Itertools;
+use jiff::Zoned;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Coverage {
+    NotCovered,
+    // Some doses on record, but no vaccine for the disease has a finished initial series.
+    Partial,
+    Covered,
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotCovered => write!(f, "Not covered"),
+            Self::Partial => write!(f, "Partially covered"),
+            Self::Covered => write!(f, "Covered"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiseaseCoverage {
+    pub disease: &'static str,
+    pub coverage: Coverage,
+    // The vaccines on record that protect against the disease.
+    pub vaccines: Vec<&'static str>,
+}
+
+// How well the records protect against each disease any vaccine treats, sorted by disease. A
+// disease is covered if any vaccine for it, combination or not, has its initial series done.
+pub fn disease_coverage(records: &[VaccineRecord]) -> Vec<DiseaseCoverage> {
+    let vaccines = Vaccine::get_vaccines();
+    vaccines
+        .values()
+        .flat_map(|v| v.treats().iter().copied())
+        .unique()
+        .sorted()
+        .map(|disease| {
+            let mut coverage = Coverage::NotCovered;
+            let mut covering = Vec::new();
+            for vaccine in vaccines.values().filter(|v| v.treats().contains(&disease)) {
+                let vaccine_records = records
+                    .iter()
+                    .filter(|r| r.vaccine() == vaccine.name())
+                    .collect_vec();
+                if vaccine_records.is_empty() {
+                    continue;
+                }
+                covering.push(vaccine.name());
+                coverage = coverage.max(
+                    if vaccine.initial_series_complete(vaccine_records.into_iter()) {
+                        Coverage::Covered
+                    } else {
+                        Coverage::Partial
+                    },
+                );
+            }
+            covering.sort();
+            DiseaseCoverage {
+                disease,
+                coverage,
+                vaccines: covering,
+            }
+        })
+        .collect()
+}
+
+// Totals over the records and the plan, for the summary card.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+    // Shots on record, as doses of an initial series and boosters. Titer checks aren't shots.
+    pub doses: usize,
+    pub boosters: usize,
+    pub diseases_covered: usize,
+    pub upcoming_this_year: usize,
+    pub upcoming: usize,
+}
+
+impl Stats {
+    pub fn new(records: &[VaccineRecord], schedule: &[VaccineAppointment], now: &Zoned) -> Self {
+        let count = |kind: fn(&DoseKind) -> bool| records.iter().filter(|r| kind(r.kind())).count();
+        let upcoming = schedule
+            .iter()
+            .filter(|appt| appt.kind() != DoseKind::TiterCheck)
+            .collect_vec();
+        Self {
+            doses: count(|kind| matches!(kind, DoseKind::Dose(_))),
+            boosters: count(|kind| *kind == DoseKind::Booster),
+            diseases_covered: disease_coverage(records)
+                .iter()
+                .filter(|c| c.coverage == Coverage::Covered)
+                .count(),
+            upcoming_this_year: upcoming
+                .iter()
+                .filter(|appt| appt.year() == now.year())
+                .count(),
+            upcoming: upcoming.len(),
+        }
+    }
+
+    pub fn shots(&self) -> usize {
+        self.doses + self.boosters
+    }
+}
+
+// "1 shot", "2 shots".
+fn count(n: usize, what: &str) -> String {
+    match n {
+        1 => format!("1 {what}"),
+        n => format!("{n} {what}s"),
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "You've received {} protecting against {}; {} upcoming this year.",
+            count(self.shots(), "shot"),
+            count(self.diseases_covered, "disease"),
+            count(self.upcoming_this_year, "shot"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use jiff::{civil::Date, tz::TimeZone};
+
+    #[test]
+    fn test_disease_coverage() -> Result<()> {
+        let date = Date::new(2024, 1, 1)?.to_zoned(TimeZone::UTC)?;
+        let record = |vaccine, kind| VaccineRecord::new(vaccine, date.clone(), kind, "");
+        let records = [
+            record("Hepatitis A&B", DoseKind::Dose(0)),
+            record("Hepatitis A&B", DoseKind::Dose(1)),
+            record("Hepatitis A&B", DoseKind::Dose(2)),
+            record("Tdap", DoseKind::Dose(0)),
+        ];
+        let coverage = disease_coverage(&records);
+        let status = |disease: &str| {
+            coverage
+                .iter()
+                .find(|c| c.disease == disease)
+                .map(|c| (c.coverage, c.vaccines.clone()))
+                .unwrap()
+        };
+        assert_eq!(
+            (Coverage::Covered, vec!["Hepatitis A&B"]),
+            status("Hepatitis A")
+        );
+        assert_eq!(
+            (Coverage::Covered, vec!["Hepatitis A&B"]),
+            status("Hepatitis B")
+        );
+        assert_eq!((Coverage::Partial, vec!["Tdap"]), status("Tetanus"));
+        assert_eq!((Coverage::NotCovered, vec![]), status("Measles"));
+        assert!(coverage
+            .iter()
+            .tuple_windows()
+            .all(|(a, b)| a.disease < b.disease));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats() -> Result<()> {
+        let date = Date::new(2024, 1, 1)?.to_zoned(TimeZone::UTC)?;
+        let record = |vaccine, kind| VaccineRecord::new(vaccine, date.clone(), kind, "");
+        // Hepatitis A and B from the combination vaccine, which the Hepatitis B series adds
+        // nothing to, and measles, mumps, and rubella. A partial Tdap series and a flu booster
+        // without a first dose don't finish a series.
+        let records = [
+            record("Hepatitis A&B", DoseKind::Dose(0)),
+            record("Hepatitis A&B", DoseKind::Dose(1)),
+            record("Hepatitis A&B", DoseKind::Dose(2)),
+            record("Hepatitis B", DoseKind::Dose(0)),
+            record("Hepatitis B", DoseKind::Dose(1)),
+            record("Hepatitis B", DoseKind::Dose(2)),
+            record("MMR", DoseKind::Dose(0)),
+            record("MMR", DoseKind::Dose(1)),
+            record("Tdap", DoseKind::Dose(0)),
+            record("Flu", DoseKind::Booster),
+            record("Hepatitis B", DoseKind::TiterCheck),
+        ];
+        let schedule = [
+            VaccineAppointment::from_month_offset("Tdap", DoseKind::Dose(1), &date, 6),
+            VaccineAppointment::from_month_offset("Flu", DoseKind::Booster, &date, 9),
+            VaccineAppointment::from_month_offset("Tdap", DoseKind::Dose(2), &date, 12),
+            VaccineAppointment::from_month_offset("Hepatitis B", DoseKind::TiterCheck, &date, 3),
+        ];
+        let stats = Stats::new(&records, &schedule, &date);
+        assert_eq!(
+            Stats {
+                doses: 9,
+                boosters: 1,
+                diseases_covered: 5,
+                upcoming_this_year: 2,
+                upcoming: 3,
+            },
+            stats
+        );
+        assert_eq!(
+            "You've received 10 shots protecting against 5 diseases; 2 shots upcoming this year.",
+            stats.to_string()
+        );
+        Ok(())
+    }
+}
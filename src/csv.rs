@@ -0,0 +1,326 @@
+use crate::schedule::{DoseKind, Reaction, RecordSource, VaccineAppointment, VaccineRecord};
+use anyhow::{anyhow, bail, Result};
+use jiff::{civil::Date, tz::TimeZone};
+
+// Column names shared by the exporter, the blank template, and the importer.
+pub const HEADER: [&str; 6] = ["date", "vaccine", "kind", "notes", "reaction", "source"];
+
+// Columns of the exported plan. Other tools read these by name, so keep them stable.
+pub const PLAN_HEADER: [&str; 4] = ["year", "month", "vaccine", "dose_kind"];
+
+pub const MIME_TYPE: &str = "text/csv";
+
+// A header row and an example row for people transcribing a paper card by hand.
+pub fn blank_template() -> String {
+    let mut out = header_line();
+    out.push_str("2020-06-01,Tdap,Dose#1,Example row; replace with your own records,,Imported\n");
+    out
+}
+
+// Only records that `keep` accepts are written.
+pub fn records_to_csv(records: &[VaccineRecord], keep: impl Fn(&VaccineRecord) -> bool) -> String {
+    let mut out = header_line();
+    for record in records.iter().filter(|record| keep(record)) {
+        push_row(
+            &mut out,
+            &[
+                record.date().date().to_string(),
+                record.vaccine().to_owned(),
+                record.kind().to_string(),
+                record.notes().to_owned(),
+                record.reaction().map(|r| r.to_string()).unwrap_or_default(),
+                record.source().to_string(),
+            ],
+        );
+    }
+    out
+}
+
+// The planned appointments that `keep` accepts, one row each.
+pub fn plan_to_csv(
+    appointments: &[VaccineAppointment],
+    keep: impl Fn(&VaccineAppointment) -> bool,
+) -> String {
+    let mut out = format!("{}\n", PLAN_HEADER.join(","));
+    for appt in appointments.iter().filter(|appt| keep(appt)) {
+        push_row(
+            &mut out,
+            &[
+                appt.year().to_string(),
+                appt.month().to_string(),
+                appt.vaccine().to_owned(),
+                appt.kind().to_string(),
+            ],
+        );
+    }
+    out
+}
+
+fn push_row(out: &mut String, fields: &[String]) {
+    out.push_str(
+        &fields
+            .iter()
+            .map(|f| escape_field(f))
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    out.push('\n');
+}
+
+// Parse records in the format written by `records_to_csv`. The header row is optional, as are
+// the trailing notes, reaction, and source columns, and blank lines are ignored. Dates are
+// interpreted in the given timezone. Records without a source are marked as imported.
+pub fn parse_records(content: &str, tz: &TimeZone) -> Result<Vec<VaccineRecord>> {
+    let mut records = Vec::new();
+    for (i, line) in data_lines(content) {
+        let record = parse_line(&line, tz).map_err(|e| anyhow!("line {}: {e}", i + 1))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+// Like `parse_records`, but goes on past bad lines: the records from the lines that parse, and
+// an error naming each line that doesn't.
+pub fn parse_each(content: &str, tz: &TimeZone) -> (Vec<VaccineRecord>, Vec<String>) {
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in data_lines(content) {
+        match parse_line(&line, tz) {
+            Ok(record) => records.push(record),
+            Err(e) => errors.push(format!("line {}: {e}", i + 1)),
+        }
+    }
+    (records, errors)
+}
+
+// The trimmed rows that hold records, with the 0-based line numbers they start on. A quoted
+// field can go on over several lines, as notes with line breaks are written that way.
+fn data_lines(content: &str) -> impl Iterator<Item = (usize, String)> {
+    let mut rows = Vec::new();
+    let mut open: Option<(usize, String)> = None;
+    for (i, line) in content.lines().enumerate() {
+        let (start, row) = match open.take() {
+            Some((start, row)) => (start, format!("{row}\n{line}")),
+            None => (i, line.to_owned()),
+        };
+        // Escaped quotes come in pairs, so an odd count means a quoted field is still open.
+        if row.matches('"').count() % 2 == 1 {
+            open = Some((start, row));
+        } else {
+            rows.push((start, row));
+        }
+    }
+    // Left for `split_line` to report as unterminated.
+    rows.extend(open);
+    rows.into_iter()
+        .map(|(i, row)| (i, row.trim().to_owned()))
+        .filter(|(i, line)| {
+            // Files from before the reaction column have a shorter header.
+            let is_header = *i == 0
+                && line
+                    .to_ascii_lowercase()
+                    .starts_with(&HEADER[..3].join(","));
+            !line.is_empty() && !is_header
+        })
+}
+
+fn parse_line(line: &str, tz: &TimeZone) -> Result<VaccineRecord> {
+    let fields = split_line(line)?;
+    if fields.len() < 3 || fields.len() > HEADER.len() {
+        bail!("expected {} columns, found {}", HEADER.len(), fields.len());
+    }
+    let date: Date = fields[0].trim().parse()?;
+    let vaccine = fields[1].trim();
+    if vaccine.is_empty() {
+        bail!("missing vaccine name");
+    }
+    let kind: DoseKind = fields[2].parse()?;
+    let notes = fields.get(3).map(|s| s.trim()).unwrap_or_default();
+    let source = match fields.get(5).filter(|s| !s.trim().is_empty()) {
+        Some(source) => source.parse()?,
+        None => RecordSource::Imported,
+    };
+    let mut record =
+        VaccineRecord::new(vaccine, date.to_zoned(tz.clone())?, kind, notes).with_source(source);
+    if let Some(reaction) = fields.get(4).filter(|s| !s.trim().is_empty()) {
+        record = record.with_reaction(reaction.parse::<Reaction>()?);
+    }
+    Ok(record)
+}
+
+fn header_line() -> String {
+    format!("{}\n", HEADER.join(","))
+}
+
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+// Split a single line on commas, honoring double-quoted fields with "" escapes.
+fn split_line(line: &str) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (',', false) => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    if in_quotes {
+        bail!("unterminated quoted field");
+    }
+    fields.push(current);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::ExportFilter;
+
+    fn tz() -> Result<TimeZone> {
+        Ok(TimeZone::get("America/Los_Angeles")?)
+    }
+
+    #[test]
+    fn test_blank_template_round_trips() -> Result<()> {
+        let records = parse_records(&blank_template(), &tz()?)?;
+        assert_eq!(1, records.len());
+        assert_eq!("Tdap", records[0].vaccine());
+        assert_eq!(&DoseKind::Dose(0), records[0].kind());
+        assert_eq!(records_to_csv(&records, |_| true), blank_template());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_records() -> Result<()> {
+        let content = "date,vaccine,kind,notes\n\
+                       2021-03-04,COVID-19,Dose#2,\"Pharmacy, downtown\"\n\
+                       \n\
+                       2022-10-01,Flu,Booster\n\
+                       2023-10-01,Flu,Booster,,Moderate: fever\n";
+        let records = parse_records(content, &tz()?)?;
+        assert_eq!(3, records.len());
+        assert_eq!(None, records[0].reaction());
+        assert_eq!(
+            "Moderate: fever",
+            records[2].reaction().expect("reaction").to_string()
+        );
+        assert_eq!("Pharmacy, downtown", records[0].notes());
+        assert_eq!(&DoseKind::Dose(1), records[0].kind());
+        assert_eq!(&DoseKind::Booster, records[1].kind());
+        assert_eq!("", records[1].notes());
+        assert!(records.iter().all(|r| r.source() == RecordSource::Imported));
+        assert_eq!(
+            records,
+            parse_records(&records_to_csv(&records, |_| true), &tz()?)?
+        );
+
+        // The source column is kept, in any case.
+        let verified = parse_records("2021-03-04,COVID-19,Dose#2,,,verified\n", &tz()?)?;
+        assert_eq!(RecordSource::Verified, verified[0].source());
+        let csv = records_to_csv(&verified, |_| true);
+        assert!(csv.ends_with(",Verified\n"), "{csv}");
+        assert_eq!(verified, parse_records(&csv, &tz()?)?);
+        assert!(parse_records("2021-03-04,COVID-19,Dose#2,,,Hearsay\n", &tz()?).is_err());
+
+        let filter = ExportFilter {
+            include_boosters: false,
+            ..ExportFilter::default()
+        };
+        let csv = records_to_csv(&records, |r| filter.keeps_record(r));
+        assert_eq!(1, parse_records(&csv, &tz()?)?.len());
+        assert!(!csv.contains("Booster"));
+
+        // Settings saved before the records option was renamed keep their choice.
+        let saved: ExportFilter =
+            ron::from_str("(include_boosters: true, include_completed: false)")?;
+        assert_eq!(
+            header_line(),
+            records_to_csv(&records, |r| saved.keeps_record(r))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_to_csv() -> Result<()> {
+        let now = Date::new(2025, 6, 1)?.to_zoned(tz()?)?;
+        let plan = [
+            VaccineAppointment::from_month_offset("Tdap", DoseKind::Dose(1), &now, 2),
+            VaccineAppointment::from_month_offset("Flu", DoseKind::Booster, &now, 4),
+            VaccineAppointment::from_month_offset("Hep A, \"Hep B\"", DoseKind::Dose(0), &now, 7),
+        ];
+        assert_eq!(
+            "year,month,vaccine,dose_kind\n\
+             2025,8,Tdap,Dose#2\n\
+             2026,1,\"Hep A, \"\"Hep B\"\"\",Dose#1\n",
+            plan_to_csv(&plan, |appt| appt.kind() != DoseKind::Booster)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_each() -> Result<()> {
+        let content = "2021-03-04, COVID-19, Dose#2\n\
+                       \n\
+                       2021-13-01, Flu, Booster\n\
+                       2022-10-01, Flu, Booster\n\
+                       2023-10-01, Flu\n";
+        let (records, errors) = parse_each(content, &tz()?);
+        assert_eq!(
+            vec![("COVID-19", DoseKind::Dose(1)), ("Flu", DoseKind::Booster)],
+            records
+                .iter()
+                .map(|r| (r.vaccine(), *r.kind()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, errors.len());
+        assert!(errors[0].starts_with("line 3:"), "{errors:?}");
+        assert!(errors[1].starts_with("line 5:"), "{errors:?}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_records_reports_line() -> Result<()> {
+        let err =
+            parse_records("date,vaccine,kind,notes\n2021-13-01,Flu,Booster\n", &tz()?).unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_notes_with_line_breaks_round_trip() -> Result<()> {
+        let date = Date::new(2021, 3, 4)?.to_zoned(tz()?)?;
+        let records = vec![
+            VaccineRecord::new(
+                "COVID-19",
+                date.clone(),
+                DoseKind::Dose(0),
+                "Left arm\n\"sore\"",
+            ),
+            VaccineRecord::new("Flu", date, DoseKind::Booster, ""),
+        ]
+        .into_iter()
+        .map(|r| r.with_source(RecordSource::Imported))
+        .collect::<Vec<_>>();
+        let csv = records_to_csv(&records, |_| true);
+        assert_eq!(records, parse_records(&csv, &tz()?)?);
+
+        // Lines are still counted from the top of the file after a multi-line row.
+        let err = parse_records(&format!("{csv}2021-13-01,Flu,Booster\n"), &tz()?).unwrap_err();
+        assert!(err.to_string().starts_with("line 5:"), "{err}");
+        assert!(parse_records("2021-03-04,Flu,Booster,\"open\n", &tz()?).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::{collections::VecDeque, sync::Mutex};
+
+// Oldest lines are dropped once the panel holds this many.
+const MAX_LINES: usize = 500;
+
+static LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+// Forwards everything to the platform logger and also keeps recent debug output from this crate
+// so that it can be shown in the app, where people reporting scheduling surprises can copy it.
+pub struct CaptureLogger {
+    inner: Box<dyn Log>,
+}
+
+impl CaptureLogger {
+    pub fn new(inner: Box<dyn Log>) -> Self {
+        Self { inner }
+    }
+
+    // Install as the global logger. Our own debug messages are always captured, whatever level
+    // the inner logger is filtering at.
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        log::set_max_level(LevelFilter::Debug);
+        log::set_boxed_logger(Box::new(self))
+    }
+
+    fn captures(metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            && metadata.level() <= LevelFilter::Debug
+    }
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        Self::captures(metadata) || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if Self::captures(record.metadata()) {
+            let mut lines = LINES.lock().unwrap();
+            if lines.len() == MAX_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(format!(
+                "{} [{}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+pub fn lines() -> Vec<String> {
+    LINES.lock().unwrap().iter().cloned().collect()
+}
+
+pub fn clear() {
+    LINES.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NullLogger;
+
+    impl Log for NullLogger {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            false
+        }
+        fn log(&self, _record: &Record<'_>) {}
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_capture_logger() {
+        let logger = CaptureLogger::new(Box::new(NullLogger));
+        let log = |target: &str, level, message: &str| {
+            logger.log(
+                &Record::builder()
+                    .target(target)
+                    .level(level)
+                    .args(format_args!("{message}"))
+                    .build(),
+            );
+        };
+        log("vaccine_helper::schedule", log::Level::Debug, "captured");
+        log("vaccine_helper::schedule", log::Level::Trace, "too verbose");
+        log("eframe", log::Level::Warn, "not ours");
+        assert_eq!(vec!["DEBUG [vaccine_helper::schedule] captured"], lines());
+
+        for i in 0..MAX_LINES {
+            log("vaccine_helper::app", log::Level::Info, &i.to_string());
+        }
+        let lines = lines();
+        assert_eq!(MAX_LINES, lines.len());
+        assert_eq!("INFO [vaccine_helper::app] 0", lines[0]);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::schedule::VaccineAppointment;
+
+pub const MIME_TYPE: &str = "text/calendar";
+
+// Render the plan as an iCalendar file with one all-day event per appointment. Appointments
+// planned without a preferred day land on the first of the month. Non-empty `notes` go in
+// each event's description so that they are at hand at the appointment. Only appointments that
+// `keep` accepts are written.
+pub fn schedule_to_ics(
+    appointments: &[VaccineAppointment],
+    notes: &str,
+    keep: impl Fn(&VaccineAppointment) -> bool,
+) -> String {
+    let mut out = String::new();
+    out.push_str("BEGIN:VCALENDAR\r\n");
+    out.push_str("VERSION:2.0\r\n");
+    out.push_str("PRODID:-//vaccine_helper//EN\r\n");
+    for appt in appointments.iter().filter(|appt| keep(appt)) {
+        let date = appt.date().strftime("%Y%m%d").to_string();
+        out.push_str("BEGIN:VEVENT\r\n");
+        out.push_str(&format!(
+            "UID:{}-{}-{}@vaccine_helper\r\n",
+            date,
+            escape(appt.vaccine()).replace(' ', "_"),
+            appt.kind()
+        ));
+        out.push_str(&format!("DTSTAMP:{date}T000000Z\r\n"));
+        out.push_str(&format!("DTSTART;VALUE=DATE:{date}\r\n"));
+        out.push_str(&format!(
+            "SUMMARY:{} {}\r\n",
+            escape(appt.vaccine()),
+            appt.kind()
+        ));
+        if !notes.is_empty() {
+            out.push_str(&format!("DESCRIPTION:{}\r\n", escape(notes)));
+        }
+        out.push_str("END:VEVENT\r\n");
+    }
+    out.push_str("END:VCALENDAR\r\n");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::{DoseKind, ExportFilter, ScheduleOptions, Vaccine};
+    use anyhow::Result;
+    use jiff::{civil::Date, tz::TimeZone};
+
+    #[test]
+    fn test_schedule_to_ics() -> Result<()> {
+        let now = Date::new(2025, 6, 1)?.to_zoned(TimeZone::UTC)?;
+        let appts = Vaccine::schedule(
+            &now,
+            ["Hepatitis A&B".to_owned()].into_iter(),
+            2026,
+            &[],
+            &ScheduleOptions::default(),
+        )?;
+        let ics = schedule_to_ics(&appts, "", |_| true);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(appts.len(), ics.matches("BEGIN:VEVENT").count());
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250601\r\n"));
+        assert!(ics.contains("SUMMARY:Hepatitis A&B Dose#2\r\n"));
+        assert!(!ics.contains("DESCRIPTION"));
+
+        let options = ScheduleOptions {
+            preferred_day_of_month: Some(15),
+            ..ScheduleOptions::default()
+        };
+        let appts = Vaccine::schedule(
+            &now,
+            ["Hepatitis A&B".to_owned()].into_iter(),
+            2026,
+            &[],
+            &options,
+        )?;
+        let ics = schedule_to_ics(&appts, "", |_| true);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250615\r\n"));
+        assert!(!ics.contains("DTSTART;VALUE=DATE:20250601\r\n"));
+        assert!(schedule_to_ics(&appts, "Line one\nLine two", |_| true)
+            .contains("DESCRIPTION:Line one\\nLine two\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_to_ics_without_boosters() -> Result<()> {
+        let now = Date::new(2025, 6, 1)?.to_zoned(TimeZone::UTC)?;
+        let appts = Vaccine::schedule(
+            &now,
+            ["Tdap".to_owned()].into_iter(),
+            2040,
+            &[],
+            &ScheduleOptions::default(),
+        )?;
+        assert!(appts.iter().any(|a| a.kind() == DoseKind::Booster));
+        let filter = ExportFilter {
+            include_boosters: false,
+            ..ExportFilter::default()
+        };
+        let ics = schedule_to_ics(&appts, "", |a| filter.keeps_appointment(a));
+        assert_eq!(3, ics.matches("BEGIN:VEVENT").count());
+        assert!(!ics.contains("Booster"));
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+use crate::schedule::{current_vaccine_name, Vaccine, VaccineRecord};
+use anyhow::Result;
+use itertools::Itertools;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+// What to do with imported records that name a vaccine we don't know about.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UnknownVaccineAction {
+    // Treat them as records of this built-in vaccine.
+    Rename(&'static str),
+    // Keep them as they are. They are listed, but do not affect the schedule.
+    Keep,
+    Drop,
+}
+
+impl fmt::Display for UnknownVaccineAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rename(name) => write!(f, "Treat as {name}"),
+            Self::Keep => write!(f, "Keep as-is (not scheduled)"),
+            Self::Drop => write!(f, "Drop these records"),
+        }
+    }
+}
+
+// Vaccine names in the records that are not in the built-in database, with how many records
+// use each.
+pub fn unknown_vaccines<'a>(
+    records: impl IntoIterator<Item = &'a VaccineRecord>,
+) -> Vec<(String, usize)> {
+    let vaccines = Vaccine::get_vaccines();
+    records
+        .into_iter()
+        .map(|record| record.vaccine())
+        .filter(|name| !vaccines.contains_key(name))
+        .counts()
+        .into_iter()
+        .map(|(name, count)| (name.to_owned(), count))
+        .sorted()
+        .collect()
+}
+
+// A best guess at the built-in vaccine an unrecognized name refers to: first one whose name
+// appears in it, then the most specific one that protects against a disease named in it.
+pub fn suggest_vaccine(name: &str) -> Option<&'static str> {
+    if let Some(vaccine) = Vaccine::get_vaccines().get(current_vaccine_name(name)) {
+        return Some(vaccine.name());
+    }
+    let name = name.to_lowercase();
+    let vaccines = Vaccine::get_vaccines()
+        .values()
+        .sorted_by_key(|v| v.name())
+        .collect_vec();
+    vaccines
+        .iter()
+        .filter(|v| name.contains(&v.name().to_lowercase()))
+        .max_by_key(|v| v.name().len())
+        .or_else(|| {
+            vaccines
+                .iter()
+                .filter(|v| {
+                    v.treats()
+                        .iter()
+                        .any(|disease| name.contains(&disease.to_lowercase()))
+                })
+                .min_by_key(|v| v.treats().len())
+        })
+        .map(|v| v.name())
+}
+
+// Apply the user's choices to records for unknown vaccines. Names without a choice are kept.
+pub fn resolve_unknown(
+    records: &mut Vec<VaccineRecord>,
+    actions: &HashMap<String, UnknownVaccineAction>,
+) {
+    records.retain(|record| actions.get(record.vaccine()) != Some(&UnknownVaccineAction::Drop));
+    for record in records.iter_mut() {
+        if let Some(UnknownVaccineAction::Rename(name)) = actions.get(record.vaccine()) {
+            *record.vaccine_mut() = name.to_string();
+        }
+    }
+    records.sort();
+}
+
+// An imported record that looks like one already on file, but is dated a few days apart. Which
+// date to keep is up to the user.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DateConflict {
+    pub existing: VaccineRecord,
+    pub incoming: VaccineRecord,
+}
+
+// How imported records line up with the ones already on file.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Reconciled {
+    // Already on file with the same date; there is nothing to import.
+    pub matched: Vec<VaccineRecord>,
+    pub new: Vec<VaccineRecord>,
+    pub conflicting: Vec<DateConflict>,
+}
+
+// Sort incoming records against existing ones. Records of the same vaccine and dose within
+// `tolerance_days` of each other are taken to be the same shot, since different sources often
+// disagree by a few days.
+pub fn reconcile(
+    existing: &[VaccineRecord],
+    incoming: Vec<VaccineRecord>,
+    tolerance_days: i32,
+) -> Reconciled {
+    let mut out = Reconciled::default();
+    for record in incoming {
+        let closest = existing
+            .iter()
+            .filter(|r| r.vaccine() == record.vaccine() && r.kind() == record.kind())
+            .map(|r| (r, (r.date().date() - record.date().date()).get_days().abs()))
+            .min_by_key(|(_, days)| *days);
+        match closest {
+            Some((_, 0)) => out.matched.push(record),
+            Some((existing, days)) if days <= tolerance_days => {
+                out.conflicting.push(DateConflict {
+                    existing: existing.clone(),
+                    incoming: record,
+                })
+            }
+            _ => out.new.push(record),
+        }
+    }
+    out
+}
+
+// What had to give to read a file from another version of the app.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Leniency {
+    // Fields this version doesn't know, by path, e.g. "records.0.lot".
+    pub dropped: Vec<String>,
+    // Fields this version has that the file doesn't, filled in with their defaults.
+    pub defaulted: Vec<String>,
+}
+
+impl Leniency {
+    pub fn is_empty(&self) -> bool {
+        self.dropped.is_empty() && self.defaulted.is_empty()
+    }
+}
+
+impl fmt::Display for Leniency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if !self.dropped.is_empty() {
+            parts.push(format!(
+                "ignored unknown fields {}",
+                self.dropped.join(", ")
+            ));
+        }
+        if !self.defaulted.is_empty() {
+            parts.push(format!(
+                "filled in missing fields {}",
+                self.defaulted.join(", ")
+            ));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+// Read RON written by an older or newer version of the app. Unknown fields are skipped, and
+// missing fields take their `serde(default)`. Everything that was skipped or filled in is
+// reported, so the user can check it.
+pub fn parse_lenient<T: DeserializeOwned + Serialize>(content: &str) -> Result<(T, Leniency)> {
+    let read = ron::from_str::<ron::Value>(content)?;
+    let (value, dropped) = match parse_noting_unknown::<T>(content)? {
+        (value, Some(dropped)) => (value, dropped),
+        // Paths can't be tracked through maps keyed by anything but strings, such as the
+        // appointment notes. Fall back to the fields of the file that didn't make it into what
+        // was read, which also counts fields written out at a value we'd leave out.
+        (value, None) => {
+            let written = ron::from_str::<ron::Value>(&ron::to_string(&value)?)?;
+            let mut dropped = vec![];
+            missing_fields(&written, &read, "", &mut dropped);
+            (value, dropped)
+        }
+    };
+    let written = ron::from_str::<ron::Value>(&ron::to_string(&value)?)?;
+    let mut defaulted = vec![];
+    missing_fields(&read, &written, "", &mut defaulted);
+    Ok((value, Leniency { dropped, defaulted }))
+}
+
+// Parse, listing the fields that were skipped, or None if they couldn't be tracked.
+fn parse_noting_unknown<T: DeserializeOwned>(content: &str) -> Result<(T, Option<Vec<String>>)> {
+    let mut de = ron::Deserializer::from_str(content)?;
+    let mut dropped = vec![];
+    let result = serde_ignored::deserialize(&mut de, |path| dropped.push(path.to_string()))
+        .and_then(|value| de.end().map(|_| value));
+    match result {
+        Ok(value) => Ok((value, Some(dropped))),
+        Err(ron::Error::Message(msg)) if msg == "non-string key" => {
+            Ok((ron::from_str(content)?, None))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+// Collect the paths of map entries in `to` that `from` doesn't have, e.g. "records.0.notes".
+fn missing_fields(from: &ron::Value, to: &ron::Value, path: &str, out: &mut Vec<String>) {
+    let join = |key: &str| match path {
+        "" => key.to_owned(),
+        _ => format!("{path}.{key}"),
+    };
+    match (from, to) {
+        (ron::Value::Map(from), ron::Value::Map(to)) => {
+            let from: BTreeMap<_, _> = from.iter().collect();
+            for (key, value) in to.iter() {
+                let key_path = match key {
+                    ron::Value::String(key) => join(key),
+                    key => join(&ron::to_string(key).unwrap_or_default()),
+                };
+                match from.get(key) {
+                    Some(from) => missing_fields(from, value, &key_path, out),
+                    None => out.push(key_path),
+                }
+            }
+        }
+        (ron::Value::Seq(from), ron::Value::Seq(to)) => {
+            for (i, (from, to)) in from.iter().zip(to).enumerate() {
+                missing_fields(from, to, &join(&i.to_string()), out);
+            }
+        }
+        (ron::Value::Option(Some(from)), ron::Value::Option(Some(to))) => {
+            missing_fields(from, to, path, out)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::DoseKind;
+    use jiff::{civil::Date, tz::TimeZone};
+
+    fn record(vaccine: &str) -> Result<VaccineRecord> {
+        record_on(vaccine, DoseKind::Dose(0), 1)
+    }
+
+    fn record_on(vaccine: &str, kind: DoseKind, day: i8) -> Result<VaccineRecord> {
+        let date = Date::new(2024, 3, day)?.to_zoned(TimeZone::UTC)?;
+        Ok(VaccineRecord::new(vaccine, date, kind, ""))
+    }
+
+    #[test]
+    fn test_parse_lenient() -> Result<()> {
+        // From an older version: the first record has no notes field.
+        let old = r#"[
+            (vaccine: "Tdap", date: "2024-03-01T00:00:00+00:00[UTC]", kind: Dose(0)),
+            (vaccine: "Flu", date: "2024-10-01T00:00:00+00:00[UTC]", kind: Booster, notes: "ok"),
+        ]"#;
+        let (records, leniency) = parse_lenient::<Vec<VaccineRecord>>(old)?;
+        assert_eq!(record("Tdap")?, records[0]);
+        assert_eq!("ok", records[1].notes());
+        assert!(leniency.dropped.is_empty());
+        assert_eq!(vec!["0.notes"], leniency.defaulted);
+
+        // From a newer version: an unknown field is skipped and named.
+        let new = r#"[(vaccine: "Tdap", date: "2024-03-01T00:00:00+00:00[UTC]", kind: Dose(0), notes: "", lot: "AB12")]"#;
+        let (records, leniency) = parse_lenient::<Vec<VaccineRecord>>(new)?;
+        assert_eq!(vec![record("Tdap")?], records);
+        assert_eq!(vec!["0.lot"], leniency.dropped);
+        assert!(leniency.defaulted.is_empty());
+
+        // A dose kind has no default.
+        let broken = r#"[(vaccine: "Tdap", date: "2024-03-01T00:00:00+00:00[UTC]", notes: "")]"#;
+        assert!(parse_lenient::<Vec<VaccineRecord>>(broken).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reconcile() -> Result<()> {
+        let existing = [
+            record_on("Tdap", DoseKind::Dose(0), 3)?,
+            record_on("Flu", DoseKind::Booster, 3)?,
+        ];
+        let same = record_on("Tdap", DoseKind::Dose(0), 3)?;
+        // Within tolerance: the pharmacy says the 5th, the manual entry the 3rd.
+        let close = record_on("Flu", DoseKind::Booster, 5)?;
+        // Outside tolerance, so a separate shot.
+        let far = record_on("Tdap", DoseKind::Dose(0), 20)?;
+        // A different dose of the same vaccine on the same day is not a duplicate.
+        let other_dose = record_on("Tdap", DoseKind::Dose(1), 3)?;
+        let reconciled = reconcile(
+            &existing,
+            vec![same.clone(), close.clone(), far.clone(), other_dose.clone()],
+            7,
+        );
+        assert_eq!(vec![same], reconciled.matched);
+        assert_eq!(
+            vec![DateConflict {
+                existing: existing[1].clone(),
+                incoming: close.clone(),
+            }],
+            reconciled.conflicting
+        );
+        assert_eq!(vec![far, other_dose], reconciled.new);
+
+        // With no tolerance, any difference in date is a new record.
+        let strict = reconcile(&existing, vec![close.clone()], 0);
+        assert_eq!(vec![close], strict.new);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_vaccines_are_reported() -> Result<()> {
+        let mut records = vec![
+            record("Tdap")?,
+            record("Tetanus shot")?,
+            record("Tetanus shot")?,
+            record("flu shot")?,
+            record("Yellow Fever")?,
+        ];
+        assert_eq!(
+            vec![
+                ("Tetanus shot".to_owned(), 2),
+                ("Yellow Fever".to_owned(), 1),
+                ("flu shot".to_owned(), 1)
+            ],
+            unknown_vaccines(&records)
+        );
+        assert_eq!(Some("Tdap"), suggest_vaccine("Tetanus shot"));
+        assert_eq!(Some("Flu"), suggest_vaccine("flu shot"));
+        assert_eq!(Some("Hepatitis A"), suggest_vaccine("Hepatitis A (Havrix)"));
+        assert_eq!(None, suggest_vaccine("Yellow Fever"));
+        assert_eq!(Some("Shingrix"), suggest_vaccine("Shinglex"));
+
+        let actions = HashMap::from([
+            (
+                "Tetanus shot".to_owned(),
+                UnknownVaccineAction::Rename("Tdap"),
+            ),
+            ("flu shot".to_owned(), UnknownVaccineAction::Drop),
+            ("Yellow Fever".to_owned(), UnknownVaccineAction::Keep),
+        ]);
+        resolve_unknown(&mut records, &actions);
+        assert_eq!(4, records.len());
+        assert_eq!(3, records.iter().filter(|r| r.vaccine() == "Tdap").count());
+        assert_eq!(
+            vec![("Yellow Fever".to_owned(), 1)],
+            unknown_vaccines(&records)
+        );
+        Ok(())
+    }
+}
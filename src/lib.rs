@@ -1,16 +1,35 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+mod card;
+pub mod cli;
+mod coverage;
+mod csv;
+pub mod debug_log;
+mod ics;
+mod import;
+mod passport;
 mod schedule;
+mod share;
+mod text;
+mod timeline;
+mod tz;
 
 #[cfg(target_arch = "wasm32")]
 mod ser_web;
 #[cfg(target_arch = "wasm32")]
-pub use ser_web::{create_file_picker, download_file};
+pub use ser_web::{
+    clear_location_hash, create_file_picker, download_file, download_files, location_hash,
+    poll_saves, share_url,
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod ser_native;
 #[cfg(not(target_arch = "wasm32"))]
-pub use ser_native::{create_file_picker, download_file};
+pub use ser_native::{
+    clear_location_hash, create_file_picker, download_file, download_files, location_hash,
+    poll_saves, share_url,
+};
 
 pub use app::VaccineHelperApp;
+pub use schedule::{is_due_now, DoseKind, VaccineRecord};
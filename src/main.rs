@@ -4,7 +4,12 @@
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
-    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    // Log to stderr (if you run with `RUST_LOG=debug`), and keep our own debug output for the
+    // in-app log window.
+    let logger = env_logger::Builder::from_default_env().build();
+    vaccine_helper::debug_log::CaptureLogger::new(Box::new(logger))
+        .init()
+        .ok();
 
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -30,7 +35,10 @@ fn main() {
     use eframe::wasm_bindgen::JsCast as _;
 
     // Redirect `log` message to `console.log` and friends:
-    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+    let logger = eframe::WebLogger::new(log::LevelFilter::Debug);
+    vaccine_helper::debug_log::CaptureLogger::new(Box::new(logger))
+        .init()
+        .ok();
 
     let web_options = eframe::WebOptions::default();
 
@@ -0,0 +1,147 @@
+use crate::schedule::VaccineRecord;
+use anyhow::Result;
+use qrcode::{render::svg, Color, EcLevel, QrCode};
+
+// Byte capacity of the largest (version 40) QR code at error correction level M.
+pub const MAX_PAYLOAD_BYTES: usize = 2331;
+
+pub const SVG_MIME_TYPE: &str = "image/svg+xml";
+
+// A compact, human-readable encoding of the records: one `date vaccine kind` entry per line,
+// most recent first, with the severity of any reaction. Notes and reaction details are dropped. Entries that would push the payload past the QR
+// capacity are left off; the second value is the number of records that were included.
+pub fn payload(records: &[VaccineRecord]) -> (String, usize) {
+    let mut out = String::from("VH1\n");
+    let mut count = 0;
+    for record in records.iter().rev() {
+        let reaction = record
+            .reaction()
+            .map(|r| format!(" ({} reaction)", r.severity))
+            .unwrap_or_default();
+        let line = format!(
+            "{} {} {}{reaction}\n",
+            record.date().date(),
+            record.vaccine(),
+            record.kind()
+        );
+        if out.len() + line.len() > MAX_PAYLOAD_BYTES {
+            break;
+        }
+        out.push_str(&line);
+        count += 1;
+    }
+    (out, count)
+}
+
+pub struct Passport {
+    code: QrCode,
+    included: usize,
+    total: usize,
+}
+
+impl Passport {
+    pub fn new(records: &[VaccineRecord]) -> Result<Self> {
+        let (data, included) = payload(records);
+        Ok(Self {
+            code: QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)?,
+            included,
+            total: records.len(),
+        })
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.included < self.total
+    }
+
+    pub fn included(&self) -> usize {
+        self.included
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn width(&self) -> usize {
+        self.code.width()
+    }
+
+    // Row-major module colors, true for dark.
+    pub fn modules(&self) -> impl Iterator<Item = bool> + '_ {
+        self.code.to_colors().into_iter().map(|c| c == Color::Dark)
+    }
+
+    pub fn to_svg(&self) -> String {
+        self.code
+            .render::<svg::Color<'_>>()
+            .min_dimensions(256, 256)
+            .build()
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui, size: f32) {
+        let (response, painter) =
+            ui.allocate_painter(egui::Vec2::splat(size), egui::Sense::hover());
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, egui::Color32::WHITE);
+        // Leave a quiet zone of 4 modules on each side so the code stays scannable.
+        let width = self.width();
+        let module = size / (width + 8) as f32;
+        for (i, dark) in self.modules().enumerate() {
+            if dark {
+                let min =
+                    rect.min + egui::vec2((i % width + 4) as f32, (i / width + 4) as f32) * module;
+                painter.rect_filled(
+                    egui::Rect::from_min_size(min, egui::Vec2::splat(module)),
+                    0.0,
+                    egui::Color32::BLACK,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::{DoseKind, Reaction, Severity};
+    use jiff::{civil::Date, tz::TimeZone};
+
+    fn record(vaccine: &str, kind: DoseKind) -> Result<VaccineRecord> {
+        let date = Date::new(2024, 3, 1)?.to_zoned(TimeZone::UTC)?;
+        Ok(VaccineRecord::new(vaccine, date, kind, "left arm"))
+    }
+
+    #[test]
+    fn test_payload_is_compact() -> Result<()> {
+        let records = vec![
+            record("Tdap", DoseKind::Dose(0))?,
+            record("Flu", DoseKind::Booster)?.with_reaction(Reaction {
+                severity: Severity::Mild,
+                description: "sore arm".to_owned(),
+            }),
+        ];
+        let (data, count) = payload(&records);
+        assert_eq!(
+            "VH1\n2024-03-01 Flu Booster (Mild reaction)\n2024-03-01 Tdap Dose#1\n",
+            data
+        );
+        assert_eq!(2, count);
+        let passport = Passport::new(&records)?;
+        assert!(!passport.is_truncated());
+        assert!(passport.to_svg().starts_with("<?xml"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_is_capped() -> Result<()> {
+        let records = (0..500)
+            .map(|_| record("Hepatitis A&B", DoseKind::Dose(2)))
+            .collect::<Result<Vec<_>>>()?;
+        let (data, count) = payload(&records);
+        assert!(data.len() <= MAX_PAYLOAD_BYTES);
+        assert!(count < records.len());
+        let passport = Passport::new(&records)?;
+        assert!(passport.is_truncated());
+        assert_eq!(count, passport.included());
+        Ok(())
+    }
+}
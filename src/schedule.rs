@@ -1,15 +1,17 @@
-use anyhow::Result;
-use jiff::{SpanRound, Unit, Zoned};
-use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, bail, Result};
+use itertools::Itertools;
+use jiff::{civil::Date, RoundMode, Span, SpanRound, Unit, Zoned};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{
     cmp::Ordering,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt,
+    str::FromStr,
     sync::OnceLock,
 };
 
 // Record the number of months between doses.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub enum DoseSchedule {
     Single,
     Repeated {
@@ -21,6 +23,10 @@ pub enum DoseSchedule {
         minimum: i16,
         maximum: i16,
     },
+    // Explicit month offsets from the first dose, for series with a tight loading cluster and a
+    // widely spaced final dose. Offsets must not decrease. Doses less than a month apart share
+    // an offset and land in the same month.
+    Custom(Vec<i16>),
 }
 
 impl DoseSchedule {
@@ -35,6 +41,29 @@ impl DoseSchedule {
             } => (0u8..*number)
                 .map(|i| (DoseKind::Dose(i), i as i16 * minimum))
                 .collect(),
+            Self::Custom(offsets) => (0u8..)
+                .zip(offsets)
+                .map(|(i, mo)| (DoseKind::Dose(i), *mo))
+                .collect(),
+        }
+    }
+
+    // Each dose as (kind, earliest, latest) months from the first dose. Only a range of intervals
+    // leaves any slack; the other schedules put each dose in a single month.
+    fn all_dose_windows(&self) -> Vec<(DoseKind, i16, i16)> {
+        match self {
+            Self::RepeatedRange {
+                number,
+                minimum,
+                maximum,
+            } => (0u8..*number)
+                .map(|i| (DoseKind::Dose(i), i as i16 * minimum, i as i16 * maximum))
+                .collect(),
+            _ => self
+                .all_doses()
+                .into_iter()
+                .map(|(kind, mo)| (kind, mo, mo))
+                .collect(),
         }
     }
 
@@ -43,33 +72,131 @@ impl DoseSchedule {
             Self::Single => 0,
             Self::Repeated { interval, .. } => *interval,
             Self::RepeatedRange { minimum, .. } => *minimum,
+            Self::Custom(offsets) => offsets
+                .iter()
+                .tuple_windows()
+                .map(|(a, b)| b - a)
+                .min()
+                .unwrap_or(0),
+        }
+    }
+
+    // The months to wait after the previous dose before getting `kind`.
+    fn interval_before(&self, kind: DoseKind) -> i16 {
+        match (self, kind) {
+            (Self::Custom(offsets), DoseKind::Dose(i)) if i > 0 => {
+                let i = i as usize;
+                match (offsets.get(i - 1), offsets.get(i)) {
+                    (Some(prev), Some(next)) => next - prev,
+                    _ => self.minimum_dose_interval(),
+                }
+            }
+            _ => self.minimum_dose_interval(),
         }
     }
 
-    // Return the month offsets for all doses we still need to get
+    // The longest the previous dose may be before `kind`, while staying within the schedule.
+    fn maximum_interval_before(&self, kind: DoseKind) -> i16 {
+        match self {
+            Self::RepeatedRange { maximum, .. } => *maximum,
+            _ => self.interval_before(kind),
+        }
+    }
+
+    // Whether the series was left unfinished for more than `restart_after_mo` months since the
+    // last dose, so that it should be started over rather than resumed.
+    fn lapsed(
+        &self,
+        now: &Zoned,
+        dose_records: &[&VaccineRecord],
+        restart_after_mo: Option<i16>,
+    ) -> Result<bool> {
+        let (Some(threshold), Some(last)) = (restart_after_mo, dose_records.iter().max()) else {
+            return Ok(false);
+        };
+        if self.complete(dose_records.iter().copied()) {
+            return Ok(false);
+        }
+        Ok(months_since(&last.latest_date(now), now)? > threshold)
+    }
+
+    // Whether every dose of the series is among `records`, which may include boosters.
+    fn complete<'a>(&self, records: impl Iterator<Item = &'a VaccineRecord>) -> bool {
+        let received = records
+            .filter_map(|record| record.counted_kind())
+            .collect::<HashSet<_>>();
+        self.all_doses()
+            .iter()
+            .all(|(kind, _)| received.contains(kind))
+    }
+
+    // Return the month offsets for all doses we still need to get, at the start or the end of
+    // each dose's window depending on `mode`.
     fn all_months<'a>(
         &self,
         now: &Zoned,
         dose_records: impl Iterator<Item = &'a &'a VaccineRecord>,
+        restart_after_mo: Option<i16>,
+        mode: PlanningMode,
     ) -> Result<Vec<(DoseKind, i16)>> {
+        Ok(self
+            .all_month_windows(now, dose_records, restart_after_mo)?
+            .into_iter()
+            .map(|(kind, earliest, latest)| match mode {
+                PlanningMode::Soonest => (kind, earliest),
+                PlanningMode::FewestVisits => (kind, latest),
+            })
+            .collect())
+    }
+
+    // How many months a dose after the first may come early and still keep to the schedule.
+    fn slack(&self) -> i16 {
+        match self {
+            Self::RepeatedRange {
+                minimum, maximum, ..
+            } => maximum - minimum,
+            _ => 0,
+        }
+    }
+
+    // The window for each dose we still need to get, as (kind, earliest, latest) months from
+    // now. Doses are planned at the earliest month; the latest is as late as each can go and
+    // still keep to the schedule's longest interval. A dose already past its latest is due now.
+    fn all_month_windows<'a>(
+        &self,
+        now: &Zoned,
+        dose_records: impl Iterator<Item = &'a &'a VaccineRecord>,
+        restart_after_mo: Option<i16>,
+    ) -> Result<Vec<(DoseKind, i16, i16)>> {
         // If no doses have been received yet, just start with the first dose and go from there.
         let dose_records = dose_records.collect::<Vec<_>>();
         if dose_records.is_empty() {
-            return Ok(self.all_doses());
+            return Ok(self.all_dose_windows());
         }
 
-        // Build a collection of all the doses we've received.
+        // After too long a gap, the doses on record no longer count and the series starts over.
+        let records = dose_records.iter().map(|record| **record).collect_vec();
+        if self.lapsed(now, &records, restart_after_mo)? {
+            log::debug!(
+                "{}: more than {} months since the last dose, restarting the series",
+                records[0].vaccine(),
+                restart_after_mo.unwrap_or_default(),
+            );
+            return Ok(self.all_dose_windows());
+        }
+
+        // Build a collection of all the doses we've received. Invalid doses have to be repeated,
+        // but they still count as the last dose for timing.
         let dose_record_kinds = dose_records
             .iter()
-            .map(|record| *record.kind())
+            .filter_map(|record| record.counted_kind())
             .collect::<HashSet<_>>();
 
         // Filter all_doses to remove any doses that are already in the records.
-        let mut required_doses: Vec<(DoseKind, i16)> = self
-            .all_doses()
-            .iter()
-            .filter(|(kind, _)| !dose_record_kinds.contains(kind))
-            .cloned()
+        let mut required_doses: Vec<(DoseKind, i16, i16)> = self
+            .all_dose_windows()
+            .into_iter()
+            .filter(|(kind, _, _)| !dose_record_kinds.contains(kind))
             .collect();
 
         // We might already have all our doses, in which case we have nothing to schedule here.
@@ -82,17 +209,13 @@ impl DoseSchedule {
         assert!(!required_doses.is_empty());
 
         // Get the offset from now to the first does we need. We will need to move all doses forward by this amount.
-        let next_dose_mo = required_doses[0].1;
+        let (next_dose_kind, next_dose_mo, next_dose_latest_mo) = required_doses[0];
 
         // Find the offset from our last dose to now. We may need to push doses forward, if the recommended interval
         // has not yet been reached for subsequent doses. Note: we assert non-empty above.
-        let last_dose_to_now = &dose_records.last().unwrap().date - now;
-        let last_dose_mo: i16 = last_dose_to_now
-            .round(SpanRound::new().smallest(Unit::Month).relative(now))?
-            .get_months()
-            .try_into()?;
+        let last_dose_mo = -months_since(&dose_records.last().unwrap().latest_date(now), now)?;
         assert!(last_dose_mo <= 0, "dose record in future in dose schedule");
-        let min_interval = self.minimum_dose_interval();
+        let min_interval = self.interval_before(next_dose_kind);
         let min_dose_offset = if -last_dose_mo > min_interval {
             // We took our last dose long enough ago that we need to resume right now.
             0
@@ -101,10 +224,22 @@ impl DoseSchedule {
             min_interval + last_dose_mo
         };
         assert!(min_dose_offset >= 0);
+        log::debug!(
+            "{}: last dose {} months ago, interval {min_interval} months, next dose in {min_dose_offset} months",
+            dose_records[0].vaccine(),
+            -last_dose_mo,
+        );
 
-        for (_, mo) in required_doses.iter_mut() {
+        // The latest the next dose can go while keeping to the longest interval, but no sooner
+        // than it can be given at all.
+        let max_dose_offset =
+            (self.maximum_interval_before(next_dose_kind) + last_dose_mo).max(min_dose_offset);
+
+        for (_, mo, latest_mo) in required_doses.iter_mut() {
             *mo = *mo - next_dose_mo + min_dose_offset;
+            *latest_mo = *latest_mo - next_dose_latest_mo + max_dose_offset;
             assert!(*mo >= 0);
+            assert!(*latest_mo >= *mo);
         }
 
         Ok(required_doses)
@@ -121,18 +256,169 @@ impl fmt::Display for DoseSchedule {
                 minimum,
                 maximum,
             } => write!(f, "{number}x every {minimum}-{maximum}mo"),
+            Self::Custom(offsets) => write!(
+                f,
+                "{}x at months {}",
+                offsets.len(),
+                offsets.iter().join(", ")
+            ),
+        }
+    }
+}
+
+// Whole calendar months that have passed from `date` to `now`, negative if `date` is later.
+// Partial months are dropped rather than rounded: jiff rounds half away from zero by default,
+// which counts a dose from 5 months and 20 days ago as 6 months old and so would book a
+// 6 month follow-up before the interval has actually elapsed.
+//
+// Months are counted on the calendar of `now`'s zone: a dose recorded in another zone is moved
+// into it first, as otherwise a record from the first of the month in Tokyo would count from a
+// different month than the same instant does in Los Angeles.
+fn months_since(date: &Zoned, now: &Zoned) -> Result<i16> {
+    let date = date.with_time_zone(now.time_zone().clone());
+    let span = (now - &date).round(
+        SpanRound::new()
+            .smallest(Unit::Month)
+            .mode(RoundMode::Trunc)
+            .relative(&date),
+    )?;
+    Ok(span.get_months().try_into()?)
+}
+
+// How many months, from when the seasonal vaccines come out, a booster can go in this season.
+const SEASON_LENGTH_MO: i16 = 3;
+
+// The shortest wait between the last dose of an initial series and a seasonal booster.
+const SEASONAL_MIN_BOOSTER_GAP_MO: i16 = 2;
+
+// A first-season second dose is for children who are not yet this old at the first dose.
+const SEASONAL_SECOND_DOSE_MAX_AGE_YEARS: i16 = 9;
+
+// The second dose of a first season follows the first by at least four weeks.
+const SEASONAL_SECOND_DOSE_GAP_MO: i16 = 1;
+
+// A booster for an exposure only helps soon after it. Older exposures are history.
+const EXPOSURE_WINDOW_MO: i16 = 3;
+
+// Which half of the world the patient lives in, which decides when flu season comes around.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum Hemisphere {
+    #[default]
+    Northern,
+    Southern,
+}
+
+impl Hemisphere {
+    pub fn all() -> [Self; 2] {
+        [Self::Northern, Self::Southern]
+    }
+
+    // Months from `now` until the seasonal vaccines come out: September in the north, March in
+    // the south. A season that started less than `SEASON_LENGTH_MO` months ago is still on, so
+    // it comes out negative.
+    fn season_start_mo(&self, now: &Zoned) -> i16 {
+        let month = match self {
+            Self::Northern => 9,
+            Self::Southern => 3,
+        };
+        let mo = (month - now.month() as i16).rem_euclid(12);
+        if mo > 12 - SEASON_LENGTH_MO {
+            mo - 12
+        } else {
+            mo
+        }
+    }
+}
+
+impl fmt::Display for Hemisphere {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Northern => write!(f, "Northern"),
+            Self::Southern => write!(f, "Southern"),
+        }
+    }
+}
+
+// How to place doses that may go anywhere in a range of months.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum PlanningMode {
+    // Each dose at the start of its window, to be protected as soon as possible.
+    #[default]
+    Soonest,
+    // Each dose at the end of its window, pulled forward to share a visit where one is nearby.
+    FewestVisits,
+}
+
+impl PlanningMode {
+    pub fn all() -> [Self; 2] {
+        [Self::Soonest, Self::FewestVisits]
+    }
+}
+
+impl fmt::Display for PlanningMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Soonest => write!(f, "As soon as possible"),
+            Self::FewestVisits => write!(f, "Fewest visits"),
         }
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+// What seasonal boosters need to know about the patient.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Season {
+    // Never exposed to the seasonal vaccines before.
+    pub naive: bool,
+    pub hemisphere: Hemisphere,
+    // Decides whether the patient is young enough for a second dose in their first season. When
+    // it is not known, anyone naive gets one.
+    pub date_of_birth: Option<Date>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "UncheckedBoosterSchedule")]
 pub enum BoosterSchedule {
-    Seasonal,
+    // A yearly booster in the fall. Some vaccines also recommend a second dose four weeks into
+    // the first season for young children with no prior exposure.
+    Seasonal { second_dose_if_naive: bool },
+    // Must be positive; see `BoosterSchedule::years`.
+    Years(i16),
+    Lifetime,
+}
+
+// The serialized form of a booster schedule, checked on the way in so that a bad vaccine
+// database can't plan boosters zero years apart.
+#[derive(Deserialize)]
+enum UncheckedBoosterSchedule {
+    Seasonal { second_dose_if_naive: bool },
     Years(i16),
     Lifetime,
 }
 
+impl TryFrom<UncheckedBoosterSchedule> for BoosterSchedule {
+    type Error = anyhow::Error;
+
+    fn try_from(schedule: UncheckedBoosterSchedule) -> Result<Self> {
+        Ok(match schedule {
+            UncheckedBoosterSchedule::Seasonal {
+                second_dose_if_naive,
+            } => Self::Seasonal {
+                second_dose_if_naive,
+            },
+            UncheckedBoosterSchedule::Years(n) => Self::years(n)?,
+            UncheckedBoosterSchedule::Lifetime => Self::Lifetime,
+        })
+    }
+}
+
 impl BoosterSchedule {
+    pub fn years(n: i16) -> Result<Self> {
+        if n <= 0 {
+            bail!("boosters must be at least a year apart, not {n} years");
+        }
+        Ok(Self::Years(n))
+    }
+
     // Return the month offsets for all shots
     fn all_months(
         &self,
@@ -140,6 +426,7 @@ impl BoosterSchedule {
         limit_mo: i16,
         planned_last_dose_mo: Option<i16>,
         vaccine_records: &[&VaccineRecord],
+        season: Season,
     ) -> Result<Vec<(DoseKind, i16)>> {
         let next_booster_mo = if let Some(last_dose_mo) = planned_last_dose_mo {
             // If the last dose is scheduled in the future, start boosting after that.
@@ -149,17 +436,15 @@ impl BoosterSchedule {
             last_dose_mo + self.duration()
         } else {
             // Otherwise, we need to find the last received dose or booster. This is just the
-            // last entry in the vaccine records.
+            // last entry in the vaccine records. With neither there is nothing to count the
+            // interval from, so plan no boosters.
             assert!(vaccine_records.is_sorted());
-            let last = vaccine_records
-                .last()
-                .expect("no vaccine records and no scheduled last dose of initial series");
-
-            let last_dose_to_now = &last.date - now;
-            let last_dose_mo: i16 = last_dose_to_now
-                .round(SpanRound::new().smallest(Unit::Month).relative(now))?
-                .get_months()
-                .try_into()?;
+            let Some(last) = vaccine_records.last() else {
+                log::warn!("no vaccine records and no scheduled last dose of initial series");
+                return Ok(vec![]);
+            };
+
+            let last_dose_mo = -months_since(&last.latest_date(now), now)?;
             assert!(
                 last_dose_mo <= 0,
                 "dose record in future in booster schedule"
@@ -173,57 +458,139 @@ impl BoosterSchedule {
                 interval + last_dose_mo
             };
             assert!(offset >= 0);
-            println!(
-                "last dose: {:?} interval: {}, offset: {}",
-                last_dose_mo, interval, offset
+            log::debug!(
+                "{}: last dose or booster {} months ago, interval {interval} months, next booster in {offset} months",
+                vaccine_records[0].vaccine(),
+                -last_dose_mo,
             );
             offset
         };
 
         fn push_stepped(start_mo: i16, end_mo: i16, step_y: usize, out: &mut Vec<(DoseKind, i16)>) {
+            // A zero step would never advance; plan no boosters rather than panic.
+            if step_y == 0 {
+                log::warn!("ignoring boosters planned 0 years apart");
+                return;
+            }
             for mo in (start_mo..=end_mo).step_by(12 * step_y) {
                 out.push((DoseKind::Booster, mo));
             }
         }
 
         let mut out = Vec::new();
+        let mut second_dose_mo = None;
         match self {
-            Self::Seasonal => {
-                // Note: 0-based month indexing is weird, but correct here.
-                let next_booster_mo = if next_booster_mo < 8 {
-                    // Delay until the seasonal vaccines are available in sept.
-                    8
-                } else if next_booster_mo > 10 {
+            Self::Seasonal {
+                second_dose_if_naive,
+            } => {
+                let start_mo = season.hemisphere.season_start_mo(now);
+                let mut next_booster_mo = if next_booster_mo < start_mo {
+                    // Delay until the seasonal vaccines are available in sept (march in the
+                    // southern hemisphere).
+                    start_mo
+                } else if next_booster_mo >= start_mo + SEASON_LENGTH_MO {
                     // Wrap around to next year rather than trying to get a booster before our
                     // series is finished. We'll probably be getting a dose in sept/oct anyway.
-                    12 + 8
+                    12 + start_mo
                 } else {
                     next_booster_mo
                 };
+                if *second_dose_if_naive && season.naive {
+                    // A child under 9 who has never been exposed gets a second dose four weeks
+                    // after their very first one, whether that is still to come or was given
+                    // this season. Any other record means they've had it.
+                    let first_dose = match (planned_last_dose_mo, vaccine_records) {
+                        (Some(mo), []) if mo >= 0 => {
+                            Some((mo, VaccineAppointment::mo_to_ym(now, mo)))
+                        }
+                        (None, [only]) if only.counted_kind() == Some(DoseKind::Dose(0)) => {
+                            let mo = -months_since(&only.latest_date(now), now)?;
+                            let date = only.date().date();
+                            (mo >= start_mo).then_some((mo, (date.year(), date.month())))
+                        }
+                        _ => None,
+                    };
+                    if let Some((first_mo, (year, month))) = first_dose {
+                        let young_enough = season.date_of_birth.is_none_or(|dob| {
+                            age_in_month(dob, year, month)
+                                .is_some_and(|age| age < SEASONAL_SECOND_DOSE_MAX_AGE_YEARS)
+                        });
+                        if young_enough {
+                            second_dose_mo = Some((first_mo + SEASONAL_SECOND_DOSE_GAP_MO).max(0));
+                        }
+                    }
+                }
+                // Normalizing can pull the booster ahead of an initial series that finishes
+                // late; push it out a season at a time until it follows the last dose.
+                if let Some(series_end_mo) = second_dose_mo.or(planned_last_dose_mo) {
+                    while next_booster_mo < series_end_mo + SEASONAL_MIN_BOOSTER_GAP_MO {
+                        next_booster_mo += 12;
+                    }
+                }
+                second_dose_mo = second_dose_mo.filter(|mo| *mo <= limit_mo);
                 push_stepped(next_booster_mo, limit_mo, 1, &mut out);
             }
             Self::Years(n) => {
-                push_stepped(next_booster_mo, limit_mo, (*n).try_into()?, &mut out);
+                push_stepped(next_booster_mo, limit_mo, (*n).max(0) as usize, &mut out);
             }
             Self::Lifetime => {
                 push_stepped(next_booster_mo, limit_mo, 25, &mut out);
             }
         }
+        self.collapse_close_boosters(&mut out);
+        // The second dose is meant to be close to the first, so it goes in after collapsing. It
+        // finishes the first season's series rather than boosting it.
+        if let Some(mo) = second_dose_mo {
+            out.insert(0, (DoseKind::Dose(1), mo));
+        }
         Ok(out)
     }
 
+    // Boosters closer together than this are the same booster planned twice.
+    fn min_booster_gap(&self) -> i16 {
+        self.duration() / 4
+    }
+
+    // Drop any booster that lands too soon after the one before it, keeping the earlier one.
+    // Expects the boosters in order.
+    fn collapse_close_boosters(&self, boosters: &mut Vec<(DoseKind, i16)>) {
+        let min_gap = self.min_booster_gap();
+        let mut previous: Option<i16> = None;
+        boosters.retain(|(_, mo)| {
+            if previous.is_some_and(|prev| mo - prev < min_gap) {
+                log::debug!("dropping booster at {mo} months, too close to the one before it");
+                return false;
+            }
+            previous = Some(*mo);
+            true
+        });
+    }
+
     fn duration(&self) -> i16 {
         match self {
-            Self::Seasonal => 12,
+            Self::Seasonal { .. } => 12,
             Self::Years(n) => 12 * n,
             Self::Lifetime => 12 * 25,
         }
     }
+
+    // Breaks ties between schedules of the same duration, e.g. `Years(25)` and `Lifetime`.
+    fn rank(&self) -> (u8, bool) {
+        match self {
+            Self::Seasonal {
+                second_dose_if_naive,
+            } => (0, *second_dose_if_naive),
+            Self::Years(_) => (1, false),
+            Self::Lifetime => (2, false),
+        }
+    }
 }
 
 impl Ord for BoosterSchedule {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.duration().cmp(&other.duration())
+        self.duration()
+            .cmp(&other.duration())
+            .then_with(|| self.rank().cmp(&other.rank()))
     }
 }
 impl PartialOrd for BoosterSchedule {
@@ -235,26 +602,184 @@ impl PartialOrd for BoosterSchedule {
 impl fmt::Display for BoosterSchedule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Seasonal => write!(f, "every year in the fall"),
+            Self::Seasonal {
+                second_dose_if_naive: false,
+            } => write!(f, "every year in the fall"),
+            Self::Seasonal {
+                second_dose_if_naive: true,
+            } => write!(
+                f,
+                "every year in the fall, plus a second dose in the first season for children never exposed"
+            ),
             Self::Years(n) => write!(f, "every {n} years"),
             Self::Lifetime => write!(f, "every 25-30 years or when exposed"),
         }
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+// Built-in vaccines that have been renamed, as (old name, current name). Saved records and
+// settings may still use the old names.
+const RENAMED_VACCINES: &[(&str, &str)] = &[("Shinglex", "Shingrix"), ("Gardacil-9", "Gardasil-9")];
+
+// The current name for a vaccine that may have been saved under an older one.
+pub fn current_vaccine_name(name: &str) -> &str {
+    RENAMED_VACCINES
+        .iter()
+        .find(|(old, _)| *old == name)
+        .map_or(name, |(_, current)| current)
+}
+
+// For `#[serde(deserialize_with)]` on saved vaccine names.
+pub fn deserialize_vaccine_name<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    Ok(current_vaccine_name(&String::deserialize(deserializer)?).to_owned())
+}
+
+fn deserialize_vaccine_pairs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(String, String)>, D::Error> {
+    Ok(Vec::<(String, String)>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(a, b)| {
+            (
+                current_vaccine_name(&a).to_owned(),
+                current_vaccine_name(&b).to_owned(),
+            )
+        })
+        .collect())
+}
+
+fn one_injection() -> u8 {
+    1
+}
+
+fn is_one_injection(injections: &u8) -> bool {
+    *injections == 1
+}
+
+// The built-in data is static. Vaccines read back in, e.g. from an exported database, live for
+// the rest of the program too, so their strings are leaked to match. Fields spelled `&str` are
+// always borrowed from the input by serde, so they go through this alias instead.
+type StaticStr = &'static str;
+
+fn deserialize_static_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<&'static str, D::Error> {
+    Ok(String::deserialize(deserializer)?.leak())
+}
+
+fn deserialize_optional_static_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<&'static str>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(|s| &*s.leak()))
+}
+
+fn deserialize_static_strs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<&'static str>, D::Error> {
+    Ok(Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|s| &*s.leak())
+        .collect())
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub struct Vaccine {
-    name: &'static str,
+    #[serde(deserialize_with = "deserialize_static_str")]
+    name: StaticStr,
+    #[serde(deserialize_with = "deserialize_static_strs")]
     treats: Vec<&'static str>,
     initial_schedule: DoseSchedule,
     booster_schedule: BoosterSchedule,
-    notes: &'static str,
+    #[serde(deserialize_with = "deserialize_static_str")]
+    notes: StaticStr,
     recommended: bool,
+    live: bool,
+    // The youngest age at which the first dose may be given.
+    minimum_age_mo: i16,
+    // Shots given at the same visit for each dose, for regimens that split a dose in two.
+    #[serde(default = "one_injection")]
+    injections_per_dose: u8,
+    // Questions to ask before planning it.
+    #[serde(default)]
+    contraindications: Vec<Contraindication>,
+    // Start the series over if more than this many months pass between doses. Most series can
+    // be resumed however long the gap, so this is usually None.
+    restart_after_mo: Option<i16>,
+    // When the schedule data was last checked against the source, as YYYY-MM. None until
+    // someone has checked it.
+    #[serde(default, deserialize_with = "deserialize_optional_static_str")]
+    last_reviewed: Option<StaticStr>,
+    #[serde(deserialize_with = "deserialize_static_str")]
+    source: StaticStr,
+    // How much to trust the schedule above. Shown with the review date.
+    #[serde(default)]
+    confidence: Confidence,
+    #[serde(default)]
+    category: Category,
+}
+
+// Who a vaccine is for, broadly, to tell them apart at a glance.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Deserialize, Serialize,
+)]
+pub enum Category {
+    // For everyone, on the usual schedule.
+    #[default]
+    Routine,
+    // For trips to where the disease is common.
+    Travel,
+    // For people with a particular risk, e.g. from their health, work, or age.
+    AtRisk,
+}
+
+impl Category {
+    pub fn all() -> [Self; 3] {
+        [Self::Routine, Self::Travel, Self::AtRisk]
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Routine => write!(f, "Routine"),
+            Self::Travel => write!(f, "Travel"),
+            Self::AtRisk => write!(f, "At risk"),
+        }
+    }
+}
+
+// How well-founded a vaccine's built-in schedule is. Some were put together quickly and lean on
+// "why not?" reasoning rather than a formal recommendation; those are worth checking with a
+// professional.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum Confidence {
+    #[default]
+    Established,
+    NeedsReview,
+}
+
+impl Confidence {
+    pub fn needs_review(&self) -> bool {
+        *self == Self::NeedsReview
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Established => write!(f, "well-established"),
+            Self::NeedsReview => write!(f, "needs review"),
+        }
+    }
 }
 
 impl Ord for Vaccine {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.booster_schedule.cmp(&other.booster_schedule)
+        self.booster_schedule
+            .cmp(&other.booster_schedule)
+            .then_with(|| self.name.cmp(other.name))
     }
 }
 
@@ -295,37 +820,299 @@ impl Vaccine {
         self.recommended
     }
 
+    pub fn live(&self) -> bool {
+        self.live
+    }
+
+    pub fn contraindications(&self) -> &[Contraindication] {
+        &self.contraindications
+    }
+
+    // The first of this vaccine's questions that was answered in a way that rules it out for now.
+    pub fn held_off_by(
+        &self,
+        contraindicated: &HashSet<Contraindication>,
+    ) -> Option<Contraindication> {
+        self.contraindications
+            .iter()
+            .find(|c| c.holds_off() && contraindicated.contains(c))
+            .copied()
+    }
+
+    // This vaccine's questions answered "yes" that don't hold it off, but that the provider
+    // should hear about.
+    pub fn cautions(&self, contraindicated: &HashSet<Contraindication>) -> Vec<Contraindication> {
+        self.contraindications
+            .iter()
+            .filter(|c| !c.holds_off() && contraindicated.contains(c))
+            .copied()
+            .collect()
+    }
+
+    pub fn minimum_age_mo(&self) -> i16 {
+        self.minimum_age_mo
+    }
+
+    pub fn restart_after_mo(&self) -> Option<i16> {
+        self.restart_after_mo
+    }
+
+    // Whether the gap since the last dose is long enough that the initial series should be
+    // started over at the first dose, going by the records.
+    pub fn restart_recommended<'a>(
+        &self,
+        now: &Zoned,
+        records: impl Iterator<Item = &'a VaccineRecord>,
+    ) -> Result<bool> {
+        let dose_records = records
+            .filter(|record| matches!(record.kind(), DoseKind::Dose(_)))
+            .collect_vec();
+        self.initial_schedule
+            .lapsed(now, &dose_records, self.restart_after_mo)
+    }
+
+    // Months from now until someone born on `date_of_birth` is old enough for the first dose.
+    // Zero if they already are, or if we don't know their age.
+    fn eligible_mo(&self, now: &Zoned, date_of_birth: Option<Date>) -> Result<i16> {
+        let Some(dob) = date_of_birth else {
+            return Ok(0);
+        };
+        let eligible = dob.checked_add(Span::new().months(self.minimum_age_mo))?;
+        Ok(month_offset(eligible, now).max(0))
+    }
+
+    pub fn last_reviewed(&self) -> Option<&'static str> {
+        self.last_reviewed
+    }
+
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    pub fn confidence(&self) -> Confidence {
+        self.confidence
+    }
+
+    pub fn category(&self) -> Category {
+        self.category
+    }
+
+    // The review date of the stalest vaccine in the database, or None if any have yet to be
+    // reviewed.
+    pub fn data_last_reviewed() -> Option<&'static str> {
+        Self::get_vaccines()
+            .values()
+            .map(|v| v.last_reviewed)
+            .min()
+            .flatten()
+    }
+
+    // The whole built-in database as pretty RON, keyed and sorted by name, so that people can
+    // check the assumptions behind their plan.
+    pub fn database_to_ron() -> Result<String> {
+        let vaccines = Self::get_vaccines().iter().collect::<BTreeMap<_, _>>();
+        Ok(ron::ser::to_string_pretty(
+            &vaccines,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn treats(&self) -> &[&'static str] {
+        &self.treats
+    }
+
     pub fn treats_str(&self) -> String {
         self.treats.join(", ")
     }
 
+    // Whether every dose of the initial series is on record. Boosters are not considered.
+    pub fn initial_series_complete<'a>(
+        &self,
+        records: impl Iterator<Item = &'a VaccineRecord>,
+    ) -> bool {
+        self.initial_schedule.complete(records)
+    }
+
+    // Doses of the initial series recorded sooner after the previous dose than the schedule
+    // allows for that dose, each with the interval it needed in months. Usually a typo in a date. Dates that
+    // are only known to the month or year are given the benefit of the doubt.
+    pub fn doses_too_close<'a>(
+        &self,
+        now: &Zoned,
+        records: impl Iterator<Item = &'a VaccineRecord>,
+    ) -> Result<Vec<(&'a VaccineRecord, i16)>> {
+        let mut too_close = vec![];
+        for (prev, next) in records
+            .filter(|r| matches!(r.counted_kind(), Some(DoseKind::Dose(_))))
+            .sorted_by(|a, b| a.date().cmp(b.date()))
+            .tuple_windows()
+        {
+            let interval = self.initial_schedule.interval_before(*next.kind());
+            if months_since(prev.date(), &next.latest_date(now))? < interval {
+                too_close.push((next, interval));
+            }
+        }
+        Ok(too_close)
+    }
+
+    // Whether this is a combination vaccine that protects against everything `other` does.
+    pub fn covers(&self, other: &Vaccine) -> bool {
+        self.name != other.name && other.treats.iter().all(|t| self.treats.contains(t))
+    }
+
     // Return all doses that are needed, starting at `now` and taking into account the vaccine
     // history in records. All records are for the current vaccine, but may contain both doses
     // and boosters. When a Dose has been taken in the past, the next dose should be scheduled
     // at the time when it is due, if in the future, or as soon as possible if it is past due,
     // but preserving offsets between subsequent doses after.
+    //
+    // Nothing is given before the patient reaches the vaccine's minimum age, if their date of
+    // birth is known.
     pub fn all_doses<'a>(
         &self,
         now: &Zoned,
         records: impl Iterator<Item = &'a VaccineRecord>,
         end_plan_mo: i16,
+        options: &ScheduleOptions,
     ) -> Result<Vec<(DoseKind, i16)>> {
-        let vaccine_records: Vec<&VaccineRecord> = records.collect();
+        let immune = options.already_immune.contains(self.name);
+        let booster_schedule = options
+            .booster_overrides
+            .get(self.name)
+            .unwrap_or(&self.booster_schedule);
+        let eligible_mo = self.eligible_mo(now, options.date_of_birth)?;
+        // Records may be briefly out of order while the user is editing them.
+        // Titer checks are not doses, so they have no say in when the next one is due.
+        let vaccine_records: Vec<&VaccineRecord> = records
+            .filter(|r| *r.kind() != DoseKind::TiterCheck)
+            .sorted()
+            .collect();
         let dose_records = vaccine_records
             .iter()
             .filter(|record| matches!(record.kind(), DoseKind::Dose(_)));
 
-        let mut initial = self.initial_schedule.all_months(now, dose_records)?;
-        let booster = self.booster_schedule.all_months(
+        // Someone who is already immune skips the initial series. With nothing on record, their
+        // first booster is due now; otherwise it is timed from their last record as usual.
+        let (mut initial, planned_last_dose_mo) = if immune {
+            let planned = vaccine_records
+                .is_empty()
+                .then(|| -booster_schedule.duration());
+            (Vec::new(), planned)
+        } else {
+            let mut initial = self.initial_schedule.all_months(
+                now,
+                dose_records,
+                self.restart_after_mo,
+                options.planning_mode,
+            )?;
+            // Start the series at eligibility; the boosters then follow from the clamped series.
+            let delay = initial
+                .first()
+                .map_or(0, |(_, first_mo)| eligible_mo - first_mo);
+            if delay > 0 {
+                for (_, mo) in initial.iter_mut() {
+                    *mo += delay;
+                }
+            }
+            let planned = initial.last().map(|(_, v)| *v);
+            (initial, planned)
+        };
+        let mut booster = booster_schedule.all_months(
             now,
             end_plan_mo,
-            initial.last().map(|(_, v)| *v),
+            planned_last_dose_mo,
             &vaccine_records,
+            options.season(),
         )?;
+        // Boosters that are not timed from a planned series must wait for eligibility too.
+        // Seasonal boosters come around again next season, so just drop the early ones.
+        let delay = booster
+            .first()
+            .map_or(0, |(_, first_mo)| eligible_mo - first_mo);
+        if delay > 0 {
+            if matches!(booster_schedule, BoosterSchedule::Seasonal { .. }) {
+                booster.retain(|(_, mo)| *mo >= eligible_mo);
+            } else {
+                for (_, mo) in booster.iter_mut() {
+                    *mo += delay;
+                }
+            }
+        }
         initial.extend(booster);
         Ok(initial)
     }
 
+    // Whether nothing more is needed within `horizon_mo` months: the initial series is done and
+    // no booster falls due. Seasonal vaccines are never complete for a horizon of a year or more.
+    pub fn is_complete<'a>(
+        &self,
+        now: &Zoned,
+        records: impl Iterator<Item = &'a VaccineRecord>,
+        horizon_mo: i16,
+    ) -> Result<bool> {
+        let doses = self.all_doses(now, records, horizon_mo, &ScheduleOptions::default())?;
+        Ok(doses.iter().all(|(_, mo)| *mo > horizon_mo))
+    }
+
+    // How many months late the next dose or booster is, going by the records. A series that was
+    // never started is not late, just due.
+    pub fn months_overdue<'a>(
+        &self,
+        now: &Zoned,
+        records: impl Iterator<Item = &'a VaccineRecord>,
+    ) -> Result<i16> {
+        let records = records
+            .filter(|r| *r.kind() != DoseKind::TiterCheck)
+            .sorted()
+            .collect_vec();
+        let Some(last) = records.last() else {
+            return Ok(0);
+        };
+        let received = records
+            .iter()
+            .filter_map(|r| r.counted_kind())
+            .collect::<HashSet<_>>();
+        let next_dose = self
+            .initial_schedule
+            .all_doses()
+            .into_iter()
+            .find(|(kind, _)| !received.contains(kind));
+        let interval = match next_dose {
+            Some((kind, _)) => self.initial_schedule.interval_before(kind),
+            None => self.booster_schedule.duration(),
+        };
+        Ok((months_since(&last.latest_date(now), now)? - interval).max(0))
+    }
+
+    // Push doses later for each appointment the user said they would miss. The skipped dose is
+    // re-planned as though the skipped slot anchored it, the minimum dose interval after the
+    // slot, and every later dose moves along with it. Skips that no longer match the plan, or
+    // that would not move anything, are ignored.
+    fn apply_skips(
+        &self,
+        doses: &mut [(DoseKind, i16)],
+        skips: impl Iterator<Item = (DoseKind, i16)>,
+    ) {
+        for (kind, skip_mo) in skips.sorted_by_key(|(_, mo)| *mo) {
+            let Some(i) = doses
+                .iter()
+                .rposition(|(k, mo)| *k == kind && *mo <= skip_mo)
+            else {
+                continue;
+            };
+            let gap = match kind {
+                DoseKind::Dose(_) => self.initial_schedule.minimum_dose_interval().max(1),
+                DoseKind::Booster | DoseKind::TiterCheck => 1,
+            };
+            let delta = skip_mo + gap - doses[i].1;
+            if delta > 0 {
+                for (_, mo) in doses[i..].iter_mut() {
+                    *mo += delta;
+                }
+            }
+        }
+    }
+
     pub fn get_vaccines() -> &'static HashMap<&'static str, Vaccine> {
         static VACCINES: OnceLock<HashMap<&'static str, Vaccine>> = OnceLock::new();
         VACCINES.get_or_init(|| HashMap::from_iter([
@@ -333,17 +1120,35 @@ impl Vaccine {
                 name: "COVID-19",
                 treats: vec!["COVID-19"],
                 initial_schedule: DoseSchedule::RepeatedRange { number: 2, minimum: 1, maximum: 2 },
-                booster_schedule: BoosterSchedule::Seasonal,
+                booster_schedule: BoosterSchedule::Seasonal { second_dose_if_naive: false },
                 notes: "Get a booster in Sept/Oct to catch any new variants.",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 6,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/covid/vaccines/",
+                confidence: Confidence::Established,
+                category: Category::Routine,
             }),
             ("Flu", Vaccine {
                 name: "Flu",
                 treats: vec!["Flu"],
                 initial_schedule: DoseSchedule::Single,
-                booster_schedule: BoosterSchedule::Seasonal,
-                notes: "Get a booster in Sept/Oct to catch any new variants. Get a second dose in the middle of the season if you have no prior exposure.",
+                booster_schedule: BoosterSchedule::Seasonal { second_dose_if_naive: true },
+                notes: "Get a booster in Sept/Oct to catch any new variants. Children under 9 with no prior exposure get a second dose four weeks after their first.",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 6,
+                injections_per_dose: 1,
+                contraindications: vec![Contraindication::EggAllergy],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/flu/vaccines/",
+                confidence: Confidence::Established,
+                category: Category::Routine,
             }),
             ("Tdap", Vaccine {
                 name: "Tdap",
@@ -352,6 +1157,15 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Years(10),
                 notes: "Tuberculosis is humanity's greatest adversary; please do your part by getting vaccinated and staying up to date with boosters!",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 7 * 12,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::Established,
+                category: Category::Routine,
             }),
             ("Mpox", Vaccine {
                 name: "Mpox",
@@ -360,6 +1174,15 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Years(5),
                 notes: "The 'M' is for both \"Monkey\" and Small",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 18 * 12,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/mpox/vaccines/",
+                confidence: Confidence::Established,
+                category: Category::AtRisk,
             }),
             ("Meningitis", Vaccine {
                 name: "Meningitis",
@@ -368,6 +1191,15 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Years(5),
                 notes: "Only recommended for adults that are exposed regularly, but low risk to get it so why not?",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 2,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::NeedsReview,
+                category: Category::AtRisk,
             }),
             ("MMR", Vaccine {
                 name: "MMR",
@@ -376,14 +1208,32 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Years(5),
                 notes: "Recommended for children and immuno-compromised, but again low risk so why not? Note: measles and rubella are lifetime immunity, but mumps requires a 5 year booster.",
                 recommended: true,
+                live: true,
+                minimum_age_mo: 12,
+                injections_per_dose: 1,
+                contraindications: vec![Contraindication::Pregnant, Contraindication::Immunocompromised],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::NeedsReview,
+                category: Category::Routine,
             }),
-            ("Shinglex", Vaccine {
-                name: "Shinglex",
+            ("Shingrix", Vaccine {
+                name: "Shingrix",
                 treats: vec!["Shingles"],
                 initial_schedule: DoseSchedule::RepeatedRange { number: 2, minimum: 2, maximum: 6 },
                 booster_schedule: BoosterSchedule::Years(7),
                 notes: "Recommended for children and immuno-compromised, but again low risk so why not?",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 50 * 12,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/shingles/vaccines/",
+                confidence: Confidence::NeedsReview,
+                category: Category::Routine,
             }),
             ("PCV20", Vaccine {
                 name: "PCV20",
@@ -392,14 +1242,32 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Lifetime,
                 notes: "Recommended for at risk and 50+, but no risk to get it sooner, so why not?",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 2,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::NeedsReview,
+                category: Category::AtRisk,
             }),
-            ("Gardacil-9", Vaccine {
-                name: "Gardacil-9",
+            ("Gardasil-9", Vaccine {
+                name: "Gardasil-9",
                 treats: vec!["Human Papillomavirus (HPV)"],
                 initial_schedule: DoseSchedule::Repeated { number: 3, interval: 6 },
                 booster_schedule: BoosterSchedule::Lifetime,
                 notes: "HPV causes cancer in men and women both. Don't ignore it just because you haven't been specifically advertised to.",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 9 * 12,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::Established,
+                category: Category::Routine,
             }),
             ("Hepatitis B", Vaccine {
                 name: "Hepatitis B",
@@ -408,6 +1276,15 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Lifetime,
                 notes: "Greater than 30 years proven durability. Definitely worth it.",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 0,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::Established,
+                category: Category::Routine,
             }),
             ("Hepatitis A", Vaccine {
                 name: "Hepatitis A",
@@ -416,6 +1293,15 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Lifetime,
                 notes: "Greater than 25 years proven durability. Definitely worth it.",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 12,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::Established,
+                category: Category::Travel,
             }),
             ("Hepatitis A&B", Vaccine {
                 name: "Hepatitis A&B",
@@ -424,6 +1310,15 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Lifetime,
                 notes: "Not recommended for adults despite hepA/hepB being individually recommended. 🤷",
                 recommended: false,
+                live: false,
+                minimum_age_mo: 18 * 12,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::Established,
+                category: Category::Travel,
             }),
             ("IPV", Vaccine {
                 name: "IPV",
@@ -432,6 +1327,15 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Lifetime,
                 notes: "No recommendation for adults, but get a booster if you're at risk or risk averse.",
                 recommended: true,
+                live: false,
+                minimum_age_mo: 2,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::NeedsReview,
+                category: Category::AtRisk,
             }),
             ("Chickenpox", Vaccine {
                 name: "Chickenpox",
@@ -440,6 +1344,32 @@ impl Vaccine {
                 booster_schedule: BoosterSchedule::Lifetime,
                 notes: "Recommended if at risk or haven't had chickenpox yet, but low risk so why not?",
                 recommended: true,
+                live: true,
+                minimum_age_mo: 12,
+                injections_per_dose: 1,
+                contraindications: vec![Contraindication::Pregnant, Contraindication::Immunocompromised],
+                restart_after_mo: None,
+                last_reviewed: Some("2025-05"),
+                source: "https://www.cdc.gov/vaccines/hcp/imz-schedules/adult-age.html",
+                confidence: Confidence::NeedsReview,
+                category: Category::Routine,
+            }),
+            ("Rabies", Vaccine {
+                name: "Rabies",
+                treats: vec!["Rabies"],
+                initial_schedule: DoseSchedule::Custom(vec![0, 0, 12]),
+                booster_schedule: BoosterSchedule::Lifetime,
+                notes: "Pre-exposure series for travelers and people who work with animals: two doses a week apart. The third dose is only for ongoing risk and can be given from 3 weeks to 3 years after the first.",
+                recommended: false,
+                live: false,
+                minimum_age_mo: 0,
+                injections_per_dose: 1,
+                contraindications: vec![],
+                restart_after_mo: None,
+                last_reviewed: Some("2026-10"),
+                source: "https://www.cdc.gov/rabies/hcp/prevention-recommendations/pre-exposure-prophylaxis.html",
+                confidence: Confidence::Established,
+                category: Category::Travel,
             })]))
     }
 
@@ -450,40 +1380,735 @@ impl Vaccine {
     //       real times with now base when we commit to an appointment.
     //
     // TODO: allow for some shots to have happened already. Need a record struct.
+    //
+    // Separate visits are kept at least `min_gap_mo` months apart; see `space_visits`. Doses
+    // that may not share a visit are then split up; see `separate_conflicts`. Medical spacing
+    // wins over the visit gap preference.
+    //
+    // Appointments come out ordered by month, then by the vaccine's place in `prio`, then by
+    // dose, so that the same inputs always give the same order.
     pub fn schedule(
         now: &Zoned,
         prio: impl Iterator<Item = String>,
         end_plan_year: i16,
         records: &[VaccineRecord],
+        options: &ScheduleOptions,
     ) -> Result<Vec<VaccineAppointment>> {
-        // Compute mo offset from current to end schedule at.
+        // Compute mo offset from current to end schedule at. An imported or stale end year may be
+        // in the past; clamp it so that we never plan with a negative window.
         let current_year = now.year();
-        let limit_mo = (end_plan_year - current_year) * 12;
+        let limit_mo = (end_plan_year.max(current_year) - current_year) * 12;
+        // Boosters may be cut off sooner than the initial series to reduce clutter.
+        let booster_limit_mo = options
+            .booster_horizon_years
+            .map_or(limit_mo, |years| limit_mo.min(years * 12));
 
         let vaccines = Vaccine::get_vaccines();
-        let mut appointments = Vec::new();
-        for vaccine_name in prio {
-            let vaccine = vaccines.get(vaccine_name.as_str()).unwrap();
-            let vaccine_records = records.iter().filter(|r| r.vaccine() == vaccine.name);
-            for (kind, dose_mo) in vaccine.all_doses(now, vaccine_records, limit_mo)? {
-                appointments.push(VaccineAppointment::from_month_offset(
-                    vaccine.name(),
-                    kind,
+        let mut doses = Vec::new();
+        let mut titer_checks = Vec::new();
+        let mut priority = HashMap::new();
+        for (i, vaccine_name) in prio.enumerate() {
+            let vaccine = vaccines
+                .get(vaccine_name.as_str())
+                .ok_or_else(|| anyhow!("unknown vaccine: {vaccine_name}"))?;
+            priority.entry(vaccine.name).or_insert(i);
+            if vaccine.held_off_by(&options.contraindicated).is_some() {
+                continue;
+            }
+            let mut vaccine_records = records
+                .iter()
+                .filter(|r| r.vaccine() == vaccine.name)
+                .cloned()
+                .collect_vec();
+            let plan = |records: &[VaccineRecord]| {
+                vaccine.all_doses(now, records.iter(), booster_limit_mo, options)
+            };
+            let mut vaccine_doses = plan(&vaccine_records)?;
+            if options.planning_mode == PlanningMode::FewestVisits {
+                vaccine.join_visits(
+                    &mut vaccine_doses,
+                    &doses.iter().map(|(_, _, mo)| *mo).collect(),
+                );
+            }
+            // An exposure calls for a booster now, with the usual cadence counted from it. A
+            // series still under way keeps its spacing; the next dose can't safely come sooner.
+            let series_done = !vaccine_doses
+                .iter()
+                .any(|(kind, _)| matches!(kind, DoseKind::Dose(_)));
+            if series_done && vaccine.exposed_since_last_dose(now, &vaccine_records, options) {
+                vaccine_records.push(VaccineRecord::new(
+                    vaccine.name,
+                    now.clone(),
+                    DoseKind::Booster,
+                    "",
+                ));
+                vaccine_doses = plan(&vaccine_records)?;
+                vaccine_doses.insert(0, (DoseKind::Booster, 0));
+            }
+            vaccine.apply_skips(
+                &mut vaccine_doses,
+                options
+                    .skipped
+                    .iter()
+                    .filter(|appt| appt.vaccine() == vaccine.name)
+                    .map(|appt| (appt.kind(), appt.month_offset(now))),
+            );
+            if vaccine.live {
+                for event in &options.antibody_events {
+                    defer_doses(&mut vaccine_doses, event.deferred_until_mo(now)?);
+                }
+            }
+            if let Some(until) = options.snoozed.get(vaccine.name) {
+                defer_doses(&mut vaccine_doses, month_offset(until.date(), now));
+            }
+            if let Some(years) = options.titer_checks.get(vaccine.name) {
+                for mo in vaccine.titer_check_months(
                     now,
-                    dose_mo,
-                ))
+                    records.iter().filter(|r| r.vaccine() == vaccine.name),
+                    &vaccine_doses,
+                    *years,
+                    booster_limit_mo,
+                )? {
+                    titer_checks.push((vaccine.name(), DoseKind::TiterCheck, mo));
+                }
+            }
+            for (kind, dose_mo) in vaccine_doses {
+                doses.push((vaccine.name(), kind, dose_mo));
             }
         }
-        appointments.sort();
+        space_visits(&mut doses, options.min_gap_mo);
+        let weeks = separate_conflicts(
+            &mut doses,
+            &options.coadministration,
+            options.spread_within_month,
+        );
+        // A blood draw doesn't need spacing from shots.
+        doses.extend(titer_checks);
+
+        let mut appointments = doses
+            .into_iter()
+            .filter(|(_, _, dose_mo)| *dose_mo <= limit_mo)
+            .map(|(name, kind, dose_mo)| {
+                // Everything in a month that was spread out goes on the first day of its week.
+                let day = if weeks.keys().any(|(_, mo)| *mo == dose_mo) {
+                    Some(1 + 7 * weeks.get(&(name, dose_mo)).copied().unwrap_or(0))
+                } else {
+                    options.preferred_day_of_month
+                };
+                VaccineAppointment::from_month_offset(name, kind, now, dose_mo)
+                    .with_day(day)
+                    .with_injections(vaccines[name].injections_per_dose)
+            })
+            .collect::<Vec<_>>();
+        appointments.sort_by(|a, b| {
+            a.cmp(b)
+                .then_with(|| a.date().cmp(&b.date()))
+                .then_with(|| priority[a.vaccine.as_str()].cmp(&priority[b.vaccine.as_str()]))
+                .then_with(|| a.kind.cmp(&b.kind))
+        });
         Ok(appointments)
     }
-}
+
+    // Pull doses that were planned late in their window forward to a visit that is already planned
+    // for a vaccine ahead of this one, to save a trip. The rest of this vaccine's plan moves with
+    // each dose so that its spacing holds, except for seasonal boosters, which keep to the season.
+    fn join_visits(&self, doses: &mut [(DoseKind, i16)], visits: &BTreeSet<i16>) {
+        let slack = self.initial_schedule.slack();
+        if slack == 0 {
+            return;
+        }
+        let seasonal = matches!(self.booster_schedule, BoosterSchedule::Seasonal { .. });
+        for i in 0..doses.len() {
+            let (kind, mo) = doses[i];
+            if !matches!(kind, DoseKind::Dose(n) if n > 0) {
+                continue;
+            }
+            let Some(visit) = visits.range((mo - slack).max(0)..mo).next_back() else {
+                continue;
+            };
+            let shift = mo - visit;
+            for (kind, later_mo) in doses[i..].iter_mut() {
+                if !(seasonal && *kind == DoseKind::Booster) {
+                    *later_mo -= shift;
+                }
+            }
+        }
+    }
+
+    // Whether there is a recent exposure to something this vaccine treats, with no dose of it on
+    // record since. Seasonal vaccines are boosted every year anyway, so exposures don't count.
+    fn exposed_since_last_dose(
+        &self,
+        now: &Zoned,
+        records: &[VaccineRecord],
+        options: &ScheduleOptions,
+    ) -> bool {
+        if matches!(self.booster_schedule, BoosterSchedule::Seasonal { .. }) {
+            return false;
+        }
+        options.exposures.iter().any(|exposure| {
+            self.treats.contains(&exposure.disease.as_str())
+                && exposure.date <= now.date()
+                && month_offset(exposure.date, now) >= -EXPOSURE_WINDOW_MO
+                && !records
+                    .iter()
+                    .any(|r| *r.kind() != DoseKind::TiterCheck && r.date().date() >= exposure.date)
+        })
+    }
+
+    // The month offsets `all_doses` gives for this vaccine as `schedule` would call it, with the
+    // (year, month) each comes to, for debugging. Visit spacing, skips and other adjustments
+    // come after this.
+    pub fn dose_offsets(
+        &self,
+        now: &Zoned,
+        records: &[VaccineRecord],
+        end_plan_year: i16,
+        options: &ScheduleOptions,
+    ) -> Result<Vec<DoseOffset>> {
+        let current_year = now.year();
+        let limit_mo = (end_plan_year.max(current_year) - current_year) * 12;
+        let booster_limit_mo = options
+            .booster_horizon_years
+            .map_or(limit_mo, |years| limit_mo.min(years * 12));
+        let doses = self.all_doses(
+            now,
+            records.iter().filter(|r| r.vaccine() == self.name),
+            booster_limit_mo,
+            options,
+        )?;
+        Ok(doses
+            .into_iter()
+            .map(|(kind, mo)| (kind, mo, VaccineAppointment::mo_to_ym(now, mo)))
+            .collect())
+    }
+
+    // When each remaining dose of the initial series can be given, going by the records. Only
+    // series with a range of intervals have a window wider than a month. Eligibility and other
+    // adjustments are left to `schedule`, which plans each dose at the start of its window.
+    pub fn dose_windows(&self, now: &Zoned, records: &[VaccineRecord]) -> Result<Vec<DoseWindow>> {
+        let dose_records = records
+            .iter()
+            .filter(|r| r.vaccine() == self.name && matches!(r.kind(), DoseKind::Dose(_)))
+            .sorted()
+            .collect_vec();
+        Ok(self
+            .initial_schedule
+            .all_month_windows(now, dose_records.iter(), self.restart_after_mo)?
+            .into_iter()
+            .map(|(kind, earliest, latest)| {
+                (
+                    kind,
+                    VaccineAppointment::mo_to_ym(now, earliest),
+                    VaccineAppointment::mo_to_ym(now, latest),
+                )
+            })
+            .collect())
+    }
+
+    // Titer checks every `years` years, up to `limit_mo`, timed from the last planned dose of the
+    // initial series or, once that is done, the last record of any kind. A check that is past
+    // due is due now.
+    fn titer_check_months<'a>(
+        &self,
+        now: &Zoned,
+        records: impl Iterator<Item = &'a VaccineRecord>,
+        planned: &[(DoseKind, i16)],
+        years: i16,
+        limit_mo: i16,
+    ) -> Result<Vec<i16>> {
+        let step = years.max(1) * 12;
+        let planned_mo = planned
+            .iter()
+            .filter(|(kind, _)| matches!(kind, DoseKind::Dose(_)))
+            .map(|(_, mo)| *mo)
+            .max();
+        let last_mo = match planned_mo {
+            Some(mo) => Some(mo),
+            None => records
+                .map(|r| months_since(&r.latest_date(now), now).map(|months| -months))
+                .fold_ok(None, |last: Option<i16>, mo| last.max(Some(mo)))?,
+        };
+        let first_mo = last_mo.map_or(0, |mo| (mo + step).max(0));
+        Ok((first_mo..=limit_mo).step_by(step as usize).collect())
+    }
+
+    // Why a plan made with the same arguments has nothing for this vaccine. Each reason is
+    // checked in turn and the first that applies is returned. None if there is nothing to
+    // explain, or if the cause is something else, such as a skipped appointment.
+    pub fn why_unscheduled(
+        &self,
+        now: &Zoned,
+        end_plan_year: i16,
+        records: &[VaccineRecord],
+        options: &ScheduleOptions,
+    ) -> Result<Option<Unscheduled>> {
+        let current_year = now.year();
+        let limit_mo = (end_plan_year.max(current_year) - current_year) * 12;
+        let booster_limit_mo = options
+            .booster_horizon_years
+            .map_or(limit_mo, |years| limit_mo.min(years * 12));
+        let ym = |mo| VaccineAppointment::mo_to_ym(now, mo);
+
+        // Look well past the end of the plan, to tell a finished series from one that is next
+        // due after the plan ends.
+        let immune = options.already_immune.contains(self.name);
+        let doses = self.all_doses(
+            now,
+            records.iter().filter(|r| r.vaccine() == self.name),
+            limit_mo + 100 * 12,
+            options,
+        )?;
+        let done = |next_booster| {
+            Some(if immune {
+                Unscheduled::Immune { next_booster }
+            } else {
+                Unscheduled::Complete { next_booster }
+            })
+        };
+        let Some(&(kind, first_mo)) = doses.iter().min_by_key(|(_, mo)| *mo) else {
+            return Ok(done(None));
+        };
+        // Only boosters are left, and the next one comes after the plan ends.
+        if kind == DoseKind::Booster && first_mo > limit_mo {
+            return Ok(done(Some(ym(first_mo))));
+        }
+        if let Some(contraindication) = self.held_off_by(&options.contraindicated) {
+            return Ok(Some(Unscheduled::Contraindicated(contraindication)));
+        }
+        let eligible_mo = self.eligible_mo(now, options.date_of_birth)?;
+        if eligible_mo > limit_mo {
+            return Ok(Some(Unscheduled::TooYoung {
+                eligible: ym(eligible_mo),
+            }));
+        }
+        if let Some(until) = options.snoozed.get(self.name) {
+            let until_mo = month_offset(until.date(), now);
+            if until_mo > limit_mo {
+                return Ok(Some(Unscheduled::Snoozed {
+                    until: ym(until_mo),
+                }));
+            }
+        }
+        if self.live {
+            for event in &options.antibody_events {
+                let until_mo = event.deferred_until_mo(now)?;
+                if until_mo > limit_mo {
+                    return Ok(Some(Unscheduled::Deferred {
+                        product: event.product.clone(),
+                        until: ym(until_mo),
+                    }));
+                }
+            }
+        }
+        let last_mo = match kind {
+            DoseKind::Dose(_) => limit_mo,
+            DoseKind::Booster | DoseKind::TiterCheck => booster_limit_mo,
+        };
+        if first_mo > last_mo {
+            return Ok(Some(Unscheduled::OutsideWindow { next: ym(first_mo) }));
+        }
+        Ok(None)
+    }
+}
+
+// A dose of an initial series with the earliest and latest (year, month) it can be given in.
+pub type DoseWindow = (DoseKind, (i16, i8), (i16, i8));
+
+// A dose as `all_doses` plans it, in months from now, and the (year, month) that comes to.
+pub type DoseOffset = (DoseKind, i16, (i16, i8));
+
+// A question to ask before planning a vaccine, since some answers rule it out for now.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Deserialize, Serialize)]
+pub enum Contraindication {
+    Pregnant,
+    Immunocompromised,
+    EggAllergy,
+}
+
+impl Contraindication {
+    pub fn question(&self) -> &'static str {
+        match self {
+            Self::Pregnant => "Are you pregnant, or planning to be within a month?",
+            Self::Immunocompromised => "Is your immune system weakened by illness or treatment?",
+            Self::EggAllergy => "Do you have a severe egg allergy?",
+        }
+    }
+
+    // Whether "yes" means the vaccine is not planned. Live vaccines wait until after a pregnancy
+    // and are not given to the immunocompromised. An egg allergy is only worth mentioning to
+    // the provider, since egg-free flu vaccines exist.
+    pub fn holds_off(&self) -> bool {
+        match self {
+            Self::Pregnant | Self::Immunocompromised => true,
+            Self::EggAllergy => false,
+        }
+    }
+
+    // What to tell the provider when "yes" doesn't hold the vaccine off.
+    pub fn advice(&self) -> Option<&'static str> {
+        match self {
+            Self::Pregnant | Self::Immunocompromised => None,
+            Self::EggAllergy => Some("Severe egg allergy: ask for an egg-free flu vaccine."),
+        }
+    }
+}
+
+// The reason an enabled vaccine has nothing planned; see `Vaccine::why_unscheduled`. Dates are
+// (year, month).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Unscheduled {
+    Complete { next_booster: Option<(i16, i8)> },
+    Immune { next_booster: Option<(i16, i8)> },
+    TooYoung { eligible: (i16, i8) },
+    Snoozed { until: (i16, i8) },
+    Deferred { product: String, until: (i16, i8) },
+    OutsideWindow { next: (i16, i8) },
+    Contraindicated(Contraindication),
+}
+
+impl fmt::Display for Unscheduled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let month = |(y, m): (i16, i8)| jiff::civil::date(y, m, 1).strftime("%B %Y").to_string();
+        match self {
+            Self::Complete { next_booster } => {
+                write!(f, "The initial series is complete")?;
+                match next_booster {
+                    Some(next) => write!(
+                        f,
+                        " and the next booster is not due until {}, after the end of the plan.",
+                        month(*next)
+                    ),
+                    None => write!(f, " and no boosters are needed."),
+                }
+            }
+            Self::Immune { next_booster } => {
+                write!(f, "Marked as already immune")?;
+                match next_booster {
+                    Some(next) => write!(
+                        f,
+                        " and the first booster is not due until {}, after the end of the plan.",
+                        month(*next)
+                    ),
+                    None => write!(f, " and no boosters are needed."),
+                }
+            }
+            Self::TooYoung { eligible } => write!(
+                f,
+                "Not old enough for it until {}, after the end of the plan.",
+                month(*eligible)
+            ),
+            Self::Snoozed { until } => write!(
+                f,
+                "Snoozed until {}, after the end of the plan.",
+                month(*until)
+            ),
+            Self::Deferred { product, until } => write!(
+                f,
+                "Live vaccines wait until {} after {product}, after the end of the plan.",
+                month(*until)
+            ),
+            Self::Contraindicated(contraindication) => write!(
+                f,
+                "On hold: you answered yes to \"{}\".",
+                contraindication.question()
+            ),
+            Self::OutsideWindow { next } => write!(
+                f,
+                "Nothing is due until {}, after the end of the plan.",
+                month(*next)
+            ),
+        }
+    }
+}
+
+// Which doses go into an export, e.g. just the initial series for a travel clinic.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ExportFilter {
+    pub include_boosters: bool,
+    // Doses already received, in the exports of the records. Plan exports have none to leave out.
+    #[serde(alias = "include_completed")]
+    pub include_records: bool,
+}
+
+impl Default for ExportFilter {
+    fn default() -> Self {
+        Self {
+            include_boosters: true,
+            include_records: true,
+        }
+    }
+}
+
+impl ExportFilter {
+    fn keeps_kind(&self, kind: DoseKind) -> bool {
+        self.include_boosters || kind != DoseKind::Booster
+    }
+
+    pub fn keeps_appointment(&self, appt: &VaccineAppointment) -> bool {
+        self.keeps_kind(appt.kind())
+    }
+
+    pub fn keeps_record(&self, record: &VaccineRecord) -> bool {
+        self.include_records && self.keeps_kind(*record.kind())
+    }
+}
+
+// Preferences that shape a schedule beyond which vaccines are wanted.
+#[derive(Clone, Debug, Default)]
+pub struct ScheduleOptions {
+    pub min_gap_mo: i16,
+    pub coadministration: CoAdministration,
+    // Set when the patient has never been exposed to the seasonal vaccines.
+    pub seasonal_naive: bool,
+    pub hemisphere: Hemisphere,
+    pub planning_mode: PlanningMode,
+    // Vaccines whose initial series is skipped because of documented prior infection or immunity.
+    pub already_immune: HashSet<String>,
+    // Planned appointments that the patient will miss; see `Vaccine::apply_skips`.
+    pub skipped: Vec<VaccineAppointment>,
+    // Used to hold off on vaccines until the patient is old enough for them.
+    pub date_of_birth: Option<Date>,
+    // Only plan boosters this many years out. None plans them through the end year.
+    pub booster_horizon_years: Option<i16>,
+    // Put appointments on this day rather than leaving them at month granularity. Days past the
+    // end of a short month fall on its last day.
+    pub preferred_day_of_month: Option<i8>,
+    // Keep doses that can't share a visit in the same month, a week apart, where there is room.
+    pub spread_within_month: bool,
+    // Antibody products that live vaccines have to wait for.
+    pub antibody_events: Vec<AntibodyEvent>,
+    // Known exposures, which call for a booster of the matching vaccines right away.
+    pub exposures: Vec<Exposure>,
+    // Vaccines put on hold until a date, by name. Nothing is planned for them before the month
+    // of that date; past dates have no effect.
+    pub snoozed: HashMap<String, Zoned>,
+    // Contraindications the patient has answered "yes" to.
+    pub contraindicated: HashSet<Contraindication>,
+    // Plan an antibody titer check this many years apart for these vaccines, by name. Checks
+    // are not doses, and go alongside whatever boosters the vaccine has.
+    pub titer_checks: HashMap<String, i16>,
+    // Booster schedules to use in place of the built-in ones, by name, e.g. yearly Tdap on a
+    // doctor's advice.
+    pub booster_overrides: HashMap<String, BoosterSchedule>,
+}
+
+impl ScheduleOptions {
+    fn season(&self) -> Season {
+        Season {
+            naive: self.seasonal_naive,
+            hemisphere: self.hemisphere,
+            date_of_birth: self.date_of_birth,
+        }
+    }
+}
+
+// A dose of a non-vaccine antibody product, such as immune globulin or blood products. These can
+// keep live vaccines from taking, so live vaccines wait `deferral_mo` months after one.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct AntibodyEvent {
+    pub product: String,
+    pub date: Date,
+    pub deferral_mo: i16,
+}
+
+// A known exposure to a disease, e.g. a rusty nail for tetanus. Any non-seasonal vaccine that
+// treats it gets a booster straight away, for a few months after the exposure, unless there is a
+// record of it on or after the exposure.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct Exposure {
+    pub disease: String,
+    pub date: Date,
+}
+
+impl Exposure {
+    // Every disease the built-in vaccines treat, for picking one.
+    pub fn diseases() -> Vec<&'static str> {
+        Vaccine::get_vaccines()
+            .values()
+            .flat_map(|v| v.treats.iter().copied())
+            .unique()
+            .sorted()
+            .collect()
+    }
+}
+
+impl AntibodyEvent {
+    // Month offset from now of the first month that live vaccines can be given again, counting
+    // the month the deferral ends in, as with minimum ages. Negative once it is long past.
+    fn deferred_until_mo(&self, now: &Zoned) -> Result<i16> {
+        let end = self
+            .date
+            .checked_add(Span::new().months(self.deferral_mo))?;
+        Ok(month_offset(end, now))
+    }
+}
+
+// Month offset from now of the month that `date` falls in.
+fn month_offset(date: Date, now: &Zoned) -> i16 {
+    (date.year() - now.year()) * 12 + (date.month() - now.month()) as i16
+}
+
+// Push a vaccine's doses later so that none fall before `until_mo`, moving the whole remaining
+// series together to keep its intervals.
+fn defer_doses(doses: &mut [(DoseKind, i16)], until_mo: i16) {
+    let Some(first_mo) = doses.iter().map(|(_, mo)| *mo).min() else {
+        return;
+    };
+    if first_mo < until_mo {
+        for (_, mo) in doses.iter_mut() {
+            *mo += until_mo - first_mo;
+        }
+    }
+}
+
+// Push visits later until no two distinct visits are less than `gap_mo` months apart. When a
+// dose moves, every later dose of the same vaccine moves with it so that the intervals within
+// the series are preserved. Doses only ever move later, never earlier, so minimum intervals
+// continue to hold. Doses that land in the same month are a single visit.
+fn space_visits(doses: &mut [(&'static str, DoseKind, i16)], gap_mo: i16) {
+    doses.sort_by_key(|(_, _, mo)| *mo);
+    if gap_mo <= 1 {
+        return;
+    }
+    let mut last_visit_mo: Option<i16> = None;
+    let mut i = 0;
+    while i < doses.len() {
+        let (name, _, mo) = doses[i];
+        if let Some(last) = last_visit_mo {
+            if mo != last && mo - last < gap_mo {
+                let delta = last + gap_mo - mo;
+                for dose in doses[i..].iter_mut().filter(|(n, _, _)| *n == name) {
+                    dose.2 += delta;
+                }
+                doses[i..].sort_by_key(|(_, _, mo)| *mo);
+                continue;
+            }
+        }
+        last_visit_mo = Some(mo);
+        i += 1;
+    }
+}
+
+// The week of its month, from 0, that a dose was put in to keep it apart from others, by
+// (vaccine, month offset). Doses that aren't listed are in the first week.
+type Weeks = HashMap<(&'static str, i16), i8>;
+
+// Weeks start on the 1st, 8th, 15th and 22nd, so that every one of them is in the month.
+const WEEKS_PER_MONTH: i8 = 4;
+
+// Move doses later until no month contains a pair of vaccines that may not be given at the same
+// visit, and no two different live vaccines are given in adjacent months. Live vaccines must be
+// given on the same day or at least 4 weeks apart; with month granularity, adjacent months may
+// be closer than that. The dose that moves is always the later one in priority order.
+//
+// With `spread`, a dose that can't share a visit is first put in another week of the same month,
+// if one is free, rather than a later month. Live vaccines still have to share a day, so they
+// only ever go in the week of the other live vaccines that month.
+fn separate_conflicts(
+    doses: &mut [(&'static str, DoseKind, i16)],
+    coadmin: &CoAdministration,
+    spread: bool,
+) -> Weeks {
+    let vaccines = Vaccine::get_vaccines();
+    let is_live = |name: &str| vaccines.get(name).map(|v| v.live()).unwrap_or(false);
+    let mut weeks = Weeks::new();
+    let week = |weeks: &Weeks, name, mo| weeks.get(&(name, mo)).copied().unwrap_or(0);
+    loop {
+        doses.sort_by_key(|(_, _, mo)| *mo);
+        let conflict = doses.iter().enumerate().find_map(|(i, (a, _, a_mo))| {
+            doses[i + 1..]
+                .iter()
+                .take_while(|(_, _, b_mo)| b_mo - a_mo <= 1)
+                .find(|(b, _, b_mo)| {
+                    a != b
+                        && if a_mo == b_mo {
+                            let same_week = week(&weeks, a, *a_mo) == week(&weeks, b, *b_mo);
+                            (same_week && !coadmin.allowed(a, b))
+                                || (!same_week && is_live(a) && is_live(b))
+                        } else {
+                            is_live(a) && is_live(b)
+                        }
+                })
+                .map(|(b, _, b_mo)| (*b, *b_mo, a_mo == b_mo))
+        });
+        let Some((name, from_mo, same_month)) = conflict else {
+            break;
+        };
+        if spread && same_month {
+            let others = doses
+                .iter()
+                .filter(|(n, _, mo)| *mo == from_mo && *n != name)
+                .collect_vec();
+            let taken = others
+                .iter()
+                .filter(|(n, _, _)| !coadmin.allowed(n, name))
+                .map(|(n, _, mo)| week(&weeks, n, *mo))
+                .collect::<HashSet<_>>();
+            let live_week = others
+                .iter()
+                .find(|(n, _, _)| is_live(name) && is_live(n))
+                .map(|(n, _, mo)| week(&weeks, n, *mo));
+            let free = match live_week {
+                Some(w) => (!taken.contains(&w)).then_some(w),
+                None => (0..WEEKS_PER_MONTH).find(|w| !taken.contains(w)),
+            };
+            if let Some(free) = free {
+                weeks.insert((name, from_mo), free);
+                continue;
+            }
+        }
+        weeks.retain(|(n, mo), _| !(*n == name && *mo >= from_mo));
+        for dose in doses
+            .iter_mut()
+            .filter(|(n, _, mo)| *n == name && *mo >= from_mo)
+        {
+            dose.2 += 1;
+        }
+    }
+    weeks
+}
+
+// Pairs of vaccines that should not be given at the same visit. Everything not listed here is
+// assumed to be safe to give together.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CoAdministration {
+    #[serde(deserialize_with = "deserialize_vaccine_pairs")]
+    separate: Vec<(String, String)>,
+}
+
+impl CoAdministration {
+    pub fn allowed(&self, a: &str, b: &str) -> bool {
+        !self
+            .separate
+            .iter()
+            .any(|(x, y)| (x == a && y == b) || (x == b && y == a))
+    }
+
+    pub fn pairs(&self) -> &[(String, String)] {
+        &self.separate
+    }
+
+    pub fn separate(&mut self, a: &str, b: &str) {
+        if a != b && self.allowed(a, b) {
+            self.separate.push((a.to_owned(), b.to_owned()));
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.separate.remove(index);
+    }
+}
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub enum DoseKind {
     Dose(u8),
     #[default]
     Booster,
+    // A blood test to see whether protection is holding up, planned in place of, or alongside,
+    // boosters. Not a dose.
+    TiterCheck,
 }
 
 impl fmt::Display for DoseKind {
@@ -491,6 +2116,7 @@ impl fmt::Display for DoseKind {
         match self {
             Self::Dose(index) => write!(f, "Dose#{}", index + 1),
             Self::Booster => write!(f, "Booster"),
+            Self::TiterCheck => write!(f, "Titer check"),
         }
     }
 }
@@ -499,9 +2125,13 @@ impl Ord for DoseKind {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
             (Self::Dose(a), Self::Dose(b)) => a.cmp(b),
-            (Self::Dose(_), Self::Booster) => Ordering::Less,
-            (Self::Booster, Self::Dose(_)) => Ordering::Greater,
-            (Self::Booster, Self::Booster) => Ordering::Equal,
+            (Self::Dose(_), _) => Ordering::Less,
+            (_, Self::Dose(_)) => Ordering::Greater,
+            (Self::Booster, Self::Booster) | (Self::TiterCheck, Self::TiterCheck) => {
+                Ordering::Equal
+            }
+            (Self::Booster, Self::TiterCheck) => Ordering::Less,
+            (Self::TiterCheck, Self::Booster) => Ordering::Greater,
         }
     }
 }
@@ -511,7 +2141,38 @@ impl PartialOrd for DoseKind {
     }
 }
 
+impl FromStr for DoseKind {
+    type Err = anyhow::Error;
+
+    // Parse the output of `Display` back into a kind.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("booster") {
+            return Ok(Self::Booster);
+        }
+        if s.eq_ignore_ascii_case("titer check") {
+            return Ok(Self::TiterCheck);
+        }
+        if let Some(index) = s.strip_prefix("Dose#") {
+            let index: u8 = index.trim().parse()?;
+            if index > 0 {
+                return Ok(Self::Dose(index - 1));
+            }
+        }
+        bail!("unknown dose kind: {s}")
+    }
+}
+
 impl DoseKind {
+    // How to show this kind to the user. `Display` is the canonical form used in files.
+    pub fn label(&self, labels: &DoseLabels) -> String {
+        match self {
+            Self::Dose(index) => labels.dose.replace("{n}", &(index + 1).to_string()),
+            Self::Booster => labels.booster.clone(),
+            Self::TiterCheck => "Titer check".to_owned(),
+        }
+    }
+
     pub fn all_kinds() -> &'static [(&'static str, DoseKind)] {
         static NAMES: OnceLock<&'static [(&'static str, DoseKind)]> = OnceLock::new();
         NAMES.get_or_init(|| {
@@ -526,21 +2187,206 @@ impl DoseKind {
     }
 }
 
+// User-facing names for dose kinds. In `dose`, `{n}` is replaced by the 1-based dose number.
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DoseLabels {
+    pub dose: String,
+    pub booster: String,
+}
+
+impl Default for DoseLabels {
+    fn default() -> Self {
+        Self {
+            dose: "Dose#{n}".to_owned(),
+            booster: "Booster".to_owned(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum Severity {
+    #[default]
+    Mild,
+    Moderate,
+    Severe,
+}
+
+impl Severity {
+    pub fn all() -> [Self; 3] {
+        [Self::Mild, Self::Moderate, Self::Severe]
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mild => write!(f, "Mild"),
+            Self::Moderate => write!(f, "Moderate"),
+            Self::Severe => write!(f, "Severe"),
+        }
+    }
+}
+
+// A side effect or adverse event that followed a dose.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Reaction {
+    pub severity: Severity,
+    pub description: String,
+}
+
+impl fmt::Display for Reaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.description)
+    }
+}
+
+impl FromStr for Reaction {
+    type Err = anyhow::Error;
+
+    // Parse the output of `Display`. Text without a severity is taken as a mild reaction.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            bail!("empty reaction");
+        }
+        let parsed = s.split_once(':').and_then(|(severity, description)| {
+            Severity::all()
+                .into_iter()
+                .find(|v| v.to_string().eq_ignore_ascii_case(severity.trim()))
+                .map(|severity| Reaction {
+                    severity,
+                    description: description.trim().to_owned(),
+                })
+        });
+        Ok(parsed.unwrap_or_else(|| Reaction {
+            severity: Severity::Mild,
+            description: s.to_owned(),
+        }))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub struct VaccineRecord {
+    #[serde(deserialize_with = "deserialize_vaccine_name")]
     vaccine: String,
+    #[serde(deserialize_with = "crate::tz::deserialize_zoned")]
     date: Zoned,
+    // How much of `date` is known. Old records are often only known to the month or year.
+    #[serde(default, skip_serializing_if = "DatePrecision::is_day")]
+    precision: DatePrecision,
     kind: DoseKind,
+    #[serde(default)]
     notes: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reaction: Option<Reaction>,
+    // The dose was given but doesn't count, e.g. it was stored wrong or given too early, and has
+    // to be repeated. It is kept for the history, and still times the repeat.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    invalid: bool,
+    #[serde(default, skip_serializing_if = "RecordSource::is_self_reported")]
+    source: RecordSource,
+}
+
+// Where a record came from, for how far to trust it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum RecordSource {
+    // Typed in by the user, often from memory.
+    #[default]
+    SelfReported,
+    // Read in from a file, e.g. a pharmacy's export.
+    Imported,
+    // Checked against an official record, such as a registry or a provider's printout.
+    Verified,
+    // Added by the app in place of records that likely exist but can't be found, e.g. for a
+    // routine childhood schedule. Taken back out when the assumption is turned off.
+    Assumed,
+}
+
+impl RecordSource {
+    // The sources a record can be given by hand.
+    pub fn all() -> [Self; 3] {
+        [Self::SelfReported, Self::Imported, Self::Verified]
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::SelfReported => "👤",
+            Self::Imported => "📥",
+            Self::Verified => "✔",
+            Self::Assumed => "❔",
+        }
+    }
+
+    fn is_self_reported(&self) -> bool {
+        *self == Self::SelfReported
+    }
+}
+
+impl fmt::Display for RecordSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SelfReported => write!(f, "Self-reported"),
+            Self::Imported => write!(f, "Imported"),
+            Self::Verified => write!(f, "Verified"),
+            Self::Assumed => write!(f, "Assumed"),
+        }
+    }
+}
+
+impl FromStr for RecordSource {
+    type Err = anyhow::Error;
+
+    // Parse the output of `Display`, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        Self::all()
+            .into_iter()
+            .chain([Self::Assumed])
+            .find(|source| source.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| anyhow!("unknown record source: {s}"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum DatePrecision {
+    #[default]
+    Day,
+    Month,
+    Year,
+}
+
+impl DatePrecision {
+    pub fn all() -> [Self; 3] {
+        [Self::Day, Self::Month, Self::Year]
+    }
+
+    fn is_day(&self) -> bool {
+        *self == Self::Day
+    }
+}
+
+impl fmt::Display for DatePrecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Day => write!(f, "Exact date"),
+            Self::Month => write!(f, "Month only"),
+            Self::Year => write!(f, "Year only"),
+        }
+    }
 }
 
 impl Default for VaccineRecord {
     fn default() -> Self {
         Self {
             vaccine: "Tdap".into(),
-            date: Zoned::now(),
+            date: crate::tz::now(),
+            precision: DatePrecision::Day,
             kind: DoseKind::Booster,
             notes: String::new(),
+            reaction: None,
+            invalid: false,
+            source: RecordSource::SelfReported,
         }
     }
 }
@@ -557,6 +2403,65 @@ impl PartialOrd for VaccineRecord {
 }
 
 impl VaccineRecord {
+    pub fn new(vaccine: &str, date: Zoned, kind: DoseKind, notes: &str) -> Self {
+        Self {
+            vaccine: current_vaccine_name(vaccine).to_owned(),
+            date,
+            precision: DatePrecision::Day,
+            kind,
+            notes: notes.to_owned(),
+            reaction: None,
+            invalid: false,
+            source: RecordSource::SelfReported,
+        }
+    }
+
+    pub fn with_precision(mut self, precision: DatePrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn precision(&self) -> DatePrecision {
+        self.precision
+    }
+
+    pub fn precision_mut(&mut self) -> &mut DatePrecision {
+        &mut self.precision
+    }
+
+    // The latest the dose could have been given, for timing what comes after it, so that the
+    // next dose is never planned too soon. Never later than now.
+    pub fn latest_date(&self, now: &Zoned) -> Zoned {
+        let date = self.date.date();
+        let last_day = match self.precision {
+            DatePrecision::Day => return self.date.clone(),
+            DatePrecision::Month => date.last_of_month(),
+            DatePrecision::Year => date.last_of_year(),
+        };
+        let latest = self
+            .date
+            .with()
+            .date(last_day)
+            .build()
+            .unwrap_or_else(|_| self.date.clone());
+        latest.min(now.clone()).max(self.date.clone())
+    }
+
+    // The date as far as it is known, e.g. "05 Mar 2024", "Mar 2024" or "2024".
+    pub fn date_label(&self) -> String {
+        let format = match self.precision {
+            DatePrecision::Day => "%d %b %Y",
+            DatePrecision::Month => "%b %Y",
+            DatePrecision::Year => "%Y",
+        };
+        self.date.strftime(format).to_string()
+    }
+
+    pub fn with_reaction(mut self, reaction: Reaction) -> Self {
+        self.reaction = Some(reaction);
+        self
+    }
+
     pub fn vaccine(&self) -> &str {
         &self.vaccine
     }
@@ -588,14 +2493,67 @@ impl VaccineRecord {
     pub fn notes_mut(&mut self) -> &mut String {
         &mut self.notes
     }
-}
 
-#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
-pub struct VaccineAppointment {
-    vaccine: String,
+    pub fn reaction(&self) -> Option<&Reaction> {
+        self.reaction.as_ref()
+    }
+
+    pub fn reaction_mut(&mut self) -> &mut Option<Reaction> {
+        &mut self.reaction
+    }
+
+    pub fn with_invalid(mut self, invalid: bool) -> Self {
+        self.invalid = invalid;
+        self
+    }
+
+    pub fn invalid(&self) -> bool {
+        self.invalid
+    }
+
+    pub fn invalid_mut(&mut self) -> &mut bool {
+        &mut self.invalid
+    }
+
+    pub fn with_source(mut self, source: RecordSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn source(&self) -> RecordSource {
+        self.source
+    }
+
+    pub fn source_mut(&mut self) -> &mut RecordSource {
+        &mut self.source
+    }
+
+    // The dose kind this record satisfies, if any. Invalid doses satisfy nothing.
+    pub fn counted_kind(&self) -> Option<DoseKind> {
+        (!self.invalid).then_some(self.kind)
+    }
+
+    // Whether both record the same shot: the same dose of the same vaccine on the same day,
+    // whatever the notes or where the records came from.
+    pub fn same_shot(&self, other: &Self) -> bool {
+        self.vaccine == other.vaccine
+            && self.kind == other.kind
+            && self.date.date() == other.date.date()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct VaccineAppointment {
+    #[serde(deserialize_with = "deserialize_vaccine_name")]
+    vaccine: String,
     kind: DoseKind,
     year: i16,
     month: i8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    day: Option<i8>,
+    // Shots given together for this dose. Still a single dose at a single visit.
+    #[serde(default = "one_injection", skip_serializing_if = "is_one_injection")]
+    injections: u8,
 }
 
 impl VaccineAppointment {
@@ -615,17 +2573,63 @@ impl VaccineAppointment {
         self.month
     }
 
-    fn from_month_offset(vaccine: &str, kind: DoseKind, now: &Zoned, mo: i16) -> Self {
+    // The preferred day, if the schedule was planned with one.
+    pub fn day(&self) -> Option<i8> {
+        self.day
+    }
+
+    // The day of the appointment: the preferred day, limited to the length of the month, or the
+    // first of the month.
+    pub fn date(&self) -> Date {
+        let first = jiff::civil::date(self.year, self.month, 1);
+        match self.day {
+            Some(day) => {
+                jiff::civil::date(self.year, self.month, day.clamp(1, first.days_in_month()))
+            }
+            None => first,
+        }
+    }
+
+    pub fn from_month_offset(vaccine: &str, kind: DoseKind, now: &Zoned, mo: i16) -> Self {
         let (year, month) = Self::mo_to_ym(now, mo);
         VaccineAppointment {
             vaccine: vaccine.to_string(),
             kind,
             year,
             month,
+            day: None,
+            injections: 1,
+        }
+    }
+
+    fn with_day(mut self, day: Option<i8>) -> Self {
+        self.day = day;
+        self
+    }
+
+    fn with_injections(mut self, injections: u8) -> Self {
+        self.injections = injections;
+        self
+    }
+
+    // The dose label, noting the number of shots when one dose takes more than one, e.g.
+    // "Dose#1 (2 injections)".
+    pub fn label(&self, labels: &DoseLabels) -> String {
+        let label = self.kind.label(labels);
+        if self.injections > 1 {
+            format!("{label} ({} injections)", self.injections)
+        } else {
+            label
         }
     }
 
-    fn mo_to_ym(now: &Zoned, mo: i16) -> (i16, i8) {
+    // The inverse of `from_month_offset`.
+    pub fn month_offset(&self, now: &Zoned) -> i16 {
+        (self.year - now.year()) * 12 + (self.month - now.month()) as i16
+    }
+
+    // The (year, month) `mo` months after now.
+    pub fn mo_to_ym(now: &Zoned, mo: i16) -> (i16, i8) {
         // guaranteed to be in range 1..=12
         let month = now.date().month();
         let year = now.date().year();
@@ -638,6 +2642,88 @@ impl VaccineAppointment {
     }
 }
 
+// Whether a dose or booster of the named vaccine is due this month, going by `records`, which
+// may include other vaccines. Overdue doses count as due now. For callers outside of the app.
+pub fn is_due_now(vaccine: &str, now: &Zoned, records: &[VaccineRecord]) -> Result<bool> {
+    let Some(vaccine) = Vaccine::get_vaccines().get(vaccine) else {
+        bail!("unknown vaccine: {vaccine}");
+    };
+    let records = records.iter().filter(|r| r.vaccine() == vaccine.name());
+    let doses = vaccine.all_doses(now, records, 12, &ScheduleOptions::default())?;
+    Ok(doses.first().is_some_and(|(_, mo)| *mo <= 0))
+}
+
+// The total shots at each visit, by (year, month). A dose given as several injections is still
+// one visit. Appointments are expected in schedule order.
+pub fn visit_injections(appointments: &[VaccineAppointment]) -> Vec<((i16, i8), u16)> {
+    appointments
+        .iter()
+        .chunk_by(|appt| (appt.year, appt.month))
+        .into_iter()
+        .map(|(ym, appts)| (ym, appts.map(|appt| appt.injections as u16).sum()))
+        .collect()
+}
+
+// The months between each planned dose and the one before it, for checking spacing. The first
+// has nothing before it.
+pub fn month_gaps(offsets: impl IntoIterator<Item = i16>) -> Vec<Option<i16>> {
+    let mut previous = None;
+    offsets
+        .into_iter()
+        .map(|mo| {
+            let gap = previous.map(|prev| mo - prev);
+            previous = Some(mo);
+            gap
+        })
+        .collect()
+}
+
+// The age in whole years that someone born on `date_of_birth` reaches during the given month,
+// matching how minimum ages are checked. None for months before the birth month.
+pub fn age_in_month(date_of_birth: Date, year: i16, month: i8) -> Option<i16> {
+    let months = (year - date_of_birth.year()) * 12 + (month - date_of_birth.month()) as i16;
+    (months >= 0).then_some(months / 12)
+}
+
+// How pressing a planned appointment is, for highlighting.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Urgency {
+    // Due now, and already later than the series called for.
+    Overdue,
+    // Due within the reminder lead time, so it is time to book it.
+    DueSoon,
+    DueThisYear,
+    Future,
+    // Already covered by a record; the plan just hasn't caught up yet.
+    Completed,
+}
+
+impl VaccineAppointment {
+    // `months_overdue` is from `Vaccine::months_overdue`; being late by up to `grace_mo` months
+    // is not flagged. Appointments up to `lead_mo` months out are due soon.
+    pub fn urgency(
+        &self,
+        now: &Zoned,
+        months_overdue: i16,
+        grace_mo: i16,
+        lead_mo: i16,
+        completed: bool,
+    ) -> Urgency {
+        let offset = self.month_offset(now);
+        if completed {
+            Urgency::Completed
+        } else if offset <= 0 && months_overdue > grace_mo {
+            Urgency::Overdue
+        } else if offset <= lead_mo {
+            Urgency::DueSoon
+        } else if self.year == now.year() {
+            Urgency::DueThisYear
+        } else {
+            Urgency::Future
+        }
+    }
+}
+
 impl Ord for VaccineAppointment {
     fn cmp(&self, other: &Self) -> Ordering {
         if self.year != other.year {
@@ -656,13 +2742,21 @@ impl PartialOrd for VaccineAppointment {
 mod tests {
     use super::*;
     use anyhow::Result;
-    use jiff::{civil::Date, tz::TimeZone, Span};
+    use itertools::Itertools;
+    use jiff::{civil::DateTime, tz::TimeZone};
     use std::ops::Sub;
 
     fn test_time() -> Result<Zoned> {
         Ok(Date::new(2025, 6, 1)?.to_zoned(TimeZone::get("America/Los_Angeles")?)?)
     }
 
+    fn naive_season() -> Season {
+        Season {
+            naive: true,
+            ..Season::default()
+        }
+    }
+
     #[test]
     fn test_mo_to_ym() -> Result<()> {
         assert_eq!((2025, 6), VaccineAppointment::mo_to_ym(&test_time()?, 0));
@@ -676,6 +2770,35 @@ mod tests {
         Ok(())
     }
 
+    // Check mo_to_ym against jiff's own month arithmetic over a sweep of starting dates, zones,
+    // and offsets, including month ends and leap days. Only non-negative offsets are covered:
+    // mo_to_ym truncates rather than flooring, so it is not meant for negative ones.
+    #[test]
+    fn test_mo_to_ym_matches_jiff() -> Result<()> {
+        let zones = ["UTC", "America/Los_Angeles", "Pacific/Auckland"].map(TimeZone::get);
+        let mut checked = 0;
+        for (i, year) in [1970, 1999, 2000, 2024, 2025, 2099].into_iter().enumerate() {
+            for month in 1..=12 {
+                let first = Date::new(year, month, 1)?;
+                for day in [1, 15, first.days_in_month()] {
+                    let zone = zones[(i + month as usize + day as usize) % zones.len()].clone()?;
+                    let now = Date::new(year, month, day)?.to_zoned(zone)?;
+                    for mo in 0..=1200 {
+                        let expected = first.checked_add(Span::new().months(mo))?;
+                        assert_eq!(
+                            (expected.year(), expected.month()),
+                            VaccineAppointment::mo_to_ym(&now, mo),
+                            "{mo} months after {now}"
+                        );
+                        checked += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(6 * 12 * 3 * 1201, checked);
+        Ok(())
+    }
+
     #[test]
     fn test_dosing_schedule_basic() -> Result<()> {
         assert_eq!(
@@ -688,7 +2811,7 @@ mod tests {
                 .get("Tdap")
                 .unwrap()
                 .dosage_schedule()
-                .all_months(&test_time()?, [].iter())?
+                .all_months(&test_time()?, [].iter(), None, PlanningMode::Soonest)?
         );
         assert_eq!(
             vec![(DoseKind::Dose(0), 0i16), (DoseKind::Dose(1), 1)],
@@ -696,7 +2819,7 @@ mod tests {
                 .get("Mpox")
                 .unwrap()
                 .dosage_schedule()
-                .all_months(&test_time()?, [].iter())?
+                .all_months(&test_time()?, [].iter(), None, PlanningMode::Soonest)?
         );
         Ok(())
     }
@@ -715,10 +2838,16 @@ mod tests {
                     [&&VaccineRecord {
                         vaccine: "Tdap".to_string(),
                         date: test_time()?.sub(Span::new().months(7)),
+                        precision: DatePrecision::Day,
                         kind: DoseKind::Dose(0),
                         notes: "".to_string(),
+                        reaction: None,
+                        invalid: false,
+                        source: RecordSource::SelfReported,
                     }]
-                    .into_iter()
+                    .into_iter(),
+                    None,
+                    PlanningMode::Soonest
                 )?
         );
         // Last dose close enough that we need to offset some to meet minimum intervals
@@ -733,22 +2862,588 @@ mod tests {
                     [&&VaccineRecord {
                         vaccine: "Tdap".to_string(),
                         date: test_time()?.sub(Span::new().months(5)),
+                        precision: DatePrecision::Day,
                         kind: DoseKind::Dose(0),
                         notes: "".to_string(),
+                        reaction: None,
+                        invalid: false,
+                        source: RecordSource::SelfReported,
                     }]
-                    .into_iter()
+                    .into_iter(),
+                    None,
+                    PlanningMode::Soonest
                 )?
         );
         Ok(())
     }
 
+    #[test]
+    fn test_contraindications_hold_off_vaccines() -> Result<()> {
+        let now = test_time()?;
+        let plan = |contraindicated: &[Contraindication]| -> Result<Vec<String>> {
+            let options = ScheduleOptions {
+                contraindicated: contraindicated.iter().copied().collect(),
+                ..Default::default()
+            };
+            let plan = Vaccine::schedule(
+                &now,
+                ["MMR", "Tdap", "Chickenpox", "Flu"]
+                    .into_iter()
+                    .map(str::to_owned),
+                2027,
+                &[],
+                &options,
+            )?;
+            Ok(plan
+                .iter()
+                .map(|appt| appt.vaccine().to_owned())
+                .unique()
+                .sorted()
+                .collect())
+        };
+        assert_eq!(vec!["Chickenpox", "Flu", "MMR", "Tdap"], plan(&[])?);
+        // Pregnant: the live vaccines wait.
+        assert_eq!(vec!["Flu", "Tdap"], plan(&[Contraindication::Pregnant])?);
+        // An egg allergy doesn't hold off the flu vaccine, but is passed on.
+        assert_eq!(
+            vec!["Chickenpox", "Flu", "MMR", "Tdap"],
+            plan(&[Contraindication::EggAllergy])?
+        );
+        let allergic = HashSet::from([Contraindication::EggAllergy]);
+        assert_eq!(
+            vec![Contraindication::EggAllergy],
+            Vaccine::get_vaccines()["Flu"].cautions(&allergic)
+        );
+        assert!(Vaccine::get_vaccines()["MMR"]
+            .cautions(&allergic)
+            .is_empty());
+
+        let options = ScheduleOptions {
+            contraindicated: HashSet::from([Contraindication::Pregnant]),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(Unscheduled::Contraindicated(Contraindication::Pregnant)),
+            Vaccine::get_vaccines()["MMR"].why_unscheduled(&now, 2027, &[], &options)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_titer_checks() -> Result<()> {
+        let now = test_time()?;
+        let records = [VaccineRecord::new(
+            "Hepatitis B",
+            Date::new(2020, 6, 1)?.to_zoned(TimeZone::get("America/Los_Angeles")?)?,
+            DoseKind::Dose(0),
+            "",
+        )];
+        let options = ScheduleOptions {
+            titer_checks: HashMap::from([("Hepatitis B".to_owned(), 5)]),
+            ..Default::default()
+        };
+        let plan = |records: &[VaccineRecord]| {
+            Vaccine::schedule(
+                &now,
+                ["Hepatitis B".to_owned()].into_iter(),
+                2040,
+                records,
+                &options,
+            )
+        };
+        // Five years after the dose is now, then every five years.
+        let checks = plan(&records)?
+            .iter()
+            .map(|appt| (appt.kind(), appt.year(), appt.month()))
+            .collect_vec();
+        assert_eq!(
+            vec![
+                (DoseKind::TiterCheck, 2025, 6),
+                (DoseKind::TiterCheck, 2030, 6),
+                (DoseKind::TiterCheck, 2035, 6),
+                (DoseKind::TiterCheck, 2040, 6)
+            ],
+            checks
+        );
+
+        // Recording a check neither counts as a dose nor moves the lifetime booster, but the
+        // next check is timed from it.
+        let mut checked = records.to_vec();
+        checked.push(VaccineRecord::new(
+            "Hepatitis B",
+            Date::new(2025, 6, 1)?.to_zoned(TimeZone::get("America/Los_Angeles")?)?,
+            DoseKind::TiterCheck,
+            "",
+        ));
+        let vaccine = &Vaccine::get_vaccines()["Hepatitis B"];
+        assert_eq!(
+            vaccine.all_doses(&now, records.iter(), 40 * 12, &ScheduleOptions::default())?,
+            vaccine.all_doses(&now, checked.iter(), 40 * 12, &ScheduleOptions::default())?
+        );
+        assert_eq!(0, vaccine.months_overdue(&now, checked.iter())?);
+        let next = plan(&checked)?;
+        assert_eq!((2030, 6), (next[0].year(), next[0].month()));
+        assert_eq!(DoseKind::TiterCheck, next[0].kind());
+        assert_eq!(DoseKind::TiterCheck, "Titer check".parse::<DoseKind>()?);
+
+        // Without a cadence, there are none.
+        let plain = Vaccine::schedule(
+            &now,
+            ["Hepatitis B".to_owned()].into_iter(),
+            2040,
+            &records,
+            &ScheduleOptions::default(),
+        )?;
+        assert!(plain.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dose_offsets_match_schedule() -> Result<()> {
+        let now = test_time()?;
+        let records = [VaccineRecord::new(
+            "Tdap",
+            now.checked_sub(Span::new().months(2))?,
+            DoseKind::Dose(0),
+            "",
+        )];
+        let options = ScheduleOptions::default();
+        let tdap = &Vaccine::get_vaccines()["Tdap"];
+        let offsets = tdap.dose_offsets(&now, &records, 2040, &options)?;
+        assert_eq!(
+            vec![
+                (DoseKind::Dose(1), 4, (2025, 10)),
+                (DoseKind::Dose(2), 10, (2026, 4))
+            ],
+            offsets[..2]
+        );
+        // With a single vaccine nothing else moves the doses, so each offset is an appointment.
+        let plan = Vaccine::schedule(
+            &now,
+            ["Tdap".to_owned()].into_iter(),
+            2040,
+            &records,
+            &options,
+        )?;
+        assert_eq!(
+            offsets
+                .iter()
+                .map(|(kind, _, ym)| (*kind, *ym))
+                .collect_vec(),
+            plan.iter()
+                .map(|appt| (appt.kind(), (appt.year(), appt.month())))
+                .collect_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_year_only_record() -> Result<()> {
+        let now = test_time()?;
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        let vaccine = &Vaccine::get_vaccines()["Meningitis"];
+        let first_dose = |precision| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Meningitis",
+                Date::new(2025, 1, 1)?.to_zoned(tz.clone())?,
+                DoseKind::Dose(0),
+                "",
+            )
+            .with_precision(precision))
+        };
+        let next = |record: VaccineRecord| -> Result<(DoseKind, i16)> {
+            Ok(vaccine.all_doses(&now, [record].iter(), 12, &ScheduleOptions::default())?[0])
+        };
+        // Taken as given, the first dose was in January, so the second is due in July.
+        assert_eq!(
+            (DoseKind::Dose(1), 1),
+            next(first_dose(DatePrecision::Day)?)?
+        );
+        // "Sometime in 2025" may have been as late as now, so wait the full interval from now.
+        assert_eq!(
+            (DoseKind::Dose(1), 6),
+            next(first_dose(DatePrecision::Year)?)?
+        );
+        // Only January is known, so assume the end of it.
+        let month = first_dose(DatePrecision::Month)?;
+        assert_eq!("2025-01-31", month.latest_date(&now).date().to_string());
+        assert_eq!("Jan 2025", month.date_label());
+
+        // An older year-only record is taken to be from the end of that year.
+        let old = VaccineRecord::new(
+            "Meningitis",
+            Date::new(2010, 3, 1)?.to_zoned(tz.clone())?,
+            DoseKind::Dose(1),
+            "",
+        )
+        .with_precision(DatePrecision::Year);
+        assert_eq!("2010-12-31", old.latest_date(&now).date().to_string());
+        assert_eq!("2010", old.date_label());
+
+        let data = ron::ser::to_string(&old)?;
+        assert!(data.contains("precision:Year"));
+        assert_eq!(old, ron::de::from_str(&data)?);
+        assert!(!ron::ser::to_string(&first_dose(DatePrecision::Day)?)?.contains("precision"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_order_within_month() -> Result<()> {
+        let now = test_time()?;
+        let plan = |prio: &[&str]| {
+            Vaccine::schedule(
+                &now,
+                prio.iter().map(|name| name.to_string()),
+                2027,
+                &[],
+                &ScheduleOptions::default(),
+            )
+        };
+        let first_month = |plan: &[VaccineAppointment]| {
+            plan.iter()
+                .take_while(|appt| appt.month_offset(&now) == 0)
+                .map(|appt| appt.vaccine().to_owned())
+                .collect_vec()
+        };
+        let forward = plan(&["Tdap", "Mpox", "Hepatitis B"])?;
+        assert_eq!(vec!["Tdap", "Mpox", "Hepatitis B"], first_month(&forward));
+        let backward = plan(&["Hepatitis B", "Mpox", "Tdap"])?;
+        assert_eq!(vec!["Hepatitis B", "Mpox", "Tdap"], first_month(&backward));
+        assert_eq!(forward, plan(&["Tdap", "Mpox", "Hepatitis B"])?);
+
+        // Whatever the priority, the output is ordered by month first.
+        for plan in [&forward, &backward] {
+            assert!(plan
+                .iter()
+                .tuple_windows()
+                .all(|(a, b)| a.month_offset(&now) <= b.month_offset(&now)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_southern_flu_season() -> Result<()> {
+        let now = test_time()?;
+        let flu = &Vaccine::get_vaccines()["Flu"];
+        let records = [VaccineRecord::new(
+            "Flu",
+            now.checked_sub(Span::new().months(2))?,
+            DoseKind::Dose(0),
+            "",
+        )];
+        let boosters = |hemisphere| -> Result<Vec<(i16, i8)>> {
+            let options = ScheduleOptions {
+                hemisphere,
+                ..ScheduleOptions::default()
+            };
+            Ok(flu
+                .all_doses(&now, records.iter(), 36, &options)?
+                .into_iter()
+                .map(|(_, mo)| VaccineAppointment::mo_to_ym(&now, mo))
+                .collect())
+        };
+        // The last shot was in April, too recent for this September, so the north waits a year.
+        assert_eq!(vec![(2026, 9), (2027, 9)], boosters(Hemisphere::Northern)?);
+        // The southern season opens in March; April is still in it.
+        assert_eq!(
+            vec![(2026, 4), (2027, 4), (2028, 4)],
+            boosters(Hemisphere::Southern)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exposure_triggers_booster() -> Result<()> {
+        let now = test_time()?;
+        let record = |kind, months_ago| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Tdap",
+                now.checked_sub(Span::new().months(months_ago))?,
+                kind,
+                "",
+            ))
+        };
+        let mut records = vec![
+            record(DoseKind::Dose(0), 40)?,
+            record(DoseKind::Dose(1), 39)?,
+            record(DoseKind::Dose(2), 33)?,
+        ];
+        let plan = |records: &[VaccineRecord], options: &ScheduleOptions| -> Result<Vec<String>> {
+            Ok(Vaccine::schedule(
+                &now,
+                ["Tdap".to_owned()].into_iter(),
+                2040,
+                records,
+                options,
+            )?
+            .iter()
+            .map(|a| format!("{}-{:02} {}", a.year(), a.month(), a.kind()))
+            .collect())
+        };
+        assert_eq!(
+            vec!["2032-09 Booster"],
+            plan(&records, &ScheduleOptions::default())?
+        );
+
+        let options = ScheduleOptions {
+            exposures: vec![Exposure {
+                disease: "Tetanus".to_owned(),
+                date: Date::new(2025, 5, 20)?,
+            }],
+            ..ScheduleOptions::default()
+        };
+        assert_eq!(
+            vec!["2025-06 Booster", "2035-06 Booster"],
+            plan(&records, &options)?
+        );
+
+        // An exposure from long ago no longer calls for a booster.
+        let old = ScheduleOptions {
+            exposures: vec![Exposure {
+                disease: "Tetanus".to_owned(),
+                date: Date::new(2024, 5, 20)?,
+            }],
+            ..ScheduleOptions::default()
+        };
+        assert_eq!(vec!["2032-09 Booster"], plan(&records, &old)?);
+
+        // A shot after the exposure already covers it.
+        records.push(record(DoseKind::Booster, 0)?);
+        assert_eq!(vec!["2035-06 Booster"], plan(&records, &options)?);
+
+        // Seasonal vaccines keep to the season whatever the exposure.
+        let flu = ScheduleOptions {
+            exposures: vec![Exposure {
+                disease: "Flu".to_owned(),
+                date: Date::new(2025, 5, 20)?,
+            }],
+            ..ScheduleOptions::default()
+        };
+        let flu_records = [VaccineRecord::new(
+            "Flu",
+            now.checked_sub(Span::new().months(8))?,
+            DoseKind::Dose(0),
+            "",
+        )];
+        let seasonal = |options: &ScheduleOptions| -> Result<Vec<(i16, i8)>> {
+            Ok(Vaccine::schedule(
+                &now,
+                ["Flu".to_owned()].into_iter(),
+                2026,
+                &flu_records,
+                options,
+            )?
+            .iter()
+            .map(|a| (a.year(), a.month()))
+            .collect())
+        };
+        assert_eq!(seasonal(&ScheduleOptions::default())?, seasonal(&flu)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fewest_visits_plan() -> Result<()> {
+        let now = test_time()?;
+        let plan = |vaccines: &[&str], mode, records: &[VaccineRecord]| -> Result<Vec<_>> {
+            let options = ScheduleOptions {
+                planning_mode: mode,
+                ..ScheduleOptions::default()
+            };
+            let prio = vaccines.iter().map(|v| v.to_string());
+            Ok(Vaccine::schedule(&now, prio, 2026, records, &options)?
+                .iter()
+                .map(|appt| (appt.vaccine().to_owned(), appt.month_offset(&now)))
+                .sorted()
+                .collect_vec())
+        };
+        let doses = |name: &str, months: &[i16]| {
+            months.iter().map(|mo| (name.to_owned(), *mo)).collect_vec()
+        };
+
+        // Each second dose waits as long as its schedule allows.
+        assert_eq!(
+            doses("Mpox", &[0, 1]),
+            plan(&["Mpox"], PlanningMode::Soonest, &[])?
+        );
+        assert_eq!(
+            doses("Mpox", &[0, 6]),
+            plan(&["Mpox"], PlanningMode::FewestVisits, &[])?
+        );
+        assert_eq!(
+            doses("Shingrix", &[0, 2]),
+            plan(&["Shingrix"], PlanningMode::Soonest, &[])?
+        );
+        assert_eq!(
+            doses("Shingrix", &[0, 6]),
+            plan(&["Shingrix"], PlanningMode::FewestVisits, &[])?
+        );
+
+        // Together, that is two visits rather than three.
+        let visits = |mode| -> Result<usize> {
+            Ok(plan(&["Shingrix", "Mpox"], mode, &[])?
+                .into_iter()
+                .map(|(_, mo)| mo)
+                .unique()
+                .count())
+        };
+        assert_eq!(3, visits(PlanningMode::Soonest)?);
+        assert_eq!(2, visits(PlanningMode::FewestVisits)?);
+
+        // A second dose comes a month early to share a visit that is already planned.
+        let records = [VaccineRecord::new(
+            "Shingrix",
+            now.checked_sub(Span::new().months(1))?,
+            DoseKind::Dose(0),
+            "",
+        )];
+        assert_eq!(
+            [doses("Mpox", &[0, 5]), doses("Shingrix", &[5])].concat(),
+            plan(&["Shingrix", "Mpox"], PlanningMode::FewestVisits, &records)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mpox_dose_window() -> Result<()> {
+        let now = test_time()?;
+        let mpox = &Vaccine::get_vaccines()["Mpox"];
+        let windows = |months_ago| -> Result<Vec<DoseWindow>> {
+            let record = VaccineRecord::new(
+                "Mpox",
+                now.checked_sub(Span::new().months(months_ago))?,
+                DoseKind::Dose(0),
+                "",
+            );
+            mpox.dose_windows(&now, &[record])
+        };
+        // Nothing on record: the second dose goes one to six months after the first.
+        assert_eq!(
+            vec![
+                (DoseKind::Dose(0), (2025, 6), (2025, 6)),
+                (DoseKind::Dose(1), (2025, 7), (2025, 12)),
+            ],
+            mpox.dose_windows(&now, &[])?
+        );
+        assert_eq!(
+            vec![(DoseKind::Dose(1), (2025, 7), (2025, 12))],
+            windows(0)?
+        );
+        // A delayed second dose can still go in the rest of its window, rather than being
+        // pinned to the minimum interval.
+        assert_eq!(vec![(DoseKind::Dose(1), (2025, 6), (2025, 7))], windows(5)?);
+        // Delayed past the longest interval, it is simply due now.
+        assert_eq!(vec![(DoseKind::Dose(1), (2025, 6), (2025, 6))], windows(8)?);
+
+        // Fixed intervals have no slack.
+        let tdap = &Vaccine::get_vaccines()["Tdap"];
+        assert!(tdap
+            .dose_windows(&now, &[])?
+            .iter()
+            .all(|(_, earliest, latest)| earliest == latest));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_year_boosters() -> Result<()> {
+        assert!(BoosterSchedule::years(0).is_err());
+        assert!(BoosterSchedule::years(-5).is_err());
+        assert_eq!(BoosterSchedule::Years(10), BoosterSchedule::years(10)?);
+        assert!(ron::from_str::<BoosterSchedule>("Years(0)").is_err());
+        assert_eq!(
+            BoosterSchedule::Years(3),
+            ron::from_str::<BoosterSchedule>("Years(3)")?
+        );
+
+        // Built directly, a zero interval plans no boosters rather than panicking.
+        let now = test_time()?;
+        for n in [0, -1] {
+            let boosters =
+                BoosterSchedule::Years(n).all_months(&now, 120, Some(0), &[], Season::default())?;
+            assert!(boosters.is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_boosters_without_an_anchor() -> Result<()> {
+        // Nothing left of the initial series and nothing on record: no boosters, and no panic.
+        let now = test_time()?;
+        for schedule in [
+            BoosterSchedule::Years(10),
+            BoosterSchedule::Seasonal {
+                second_dose_if_naive: true,
+            },
+        ] {
+            assert!(schedule
+                .all_months(&now, 120, None, &[], naive_season())?
+                .is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_booster_override() -> Result<()> {
+        let now = test_time()?;
+        let tdap = &Vaccine::get_vaccines()["Tdap"];
+        let boosters = |options: &ScheduleOptions| -> Result<Vec<i16>> {
+            Ok(tdap
+                .all_doses(&now, [].into_iter(), 120, options)?
+                .into_iter()
+                .filter(|(kind, _)| *kind == DoseKind::Booster)
+                .map(|(_, mo)| mo)
+                .collect())
+        };
+        // The series takes a year, and the first booster is ten years after that.
+        assert!(boosters(&ScheduleOptions::default())?.is_empty());
+        let options = ScheduleOptions {
+            booster_overrides: HashMap::from([("Tdap".to_owned(), BoosterSchedule::Years(1))]),
+            ..Default::default()
+        };
+        assert_eq!((24..=120).step_by(12).collect_vec(), boosters(&options)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_booster_schedule_ordering_is_total() {
+        let years = BoosterSchedule::Years(25);
+        let lifetime = BoosterSchedule::Lifetime;
+        assert_eq!(years.duration(), lifetime.duration());
+        assert_eq!(Ordering::Less, years.cmp(&lifetime));
+        assert_eq!(Ordering::Greater, lifetime.cmp(&years));
+        let naive = BoosterSchedule::Seasonal {
+            second_dose_if_naive: true,
+        };
+        let seasonal = BoosterSchedule::Seasonal {
+            second_dose_if_naive: false,
+        };
+        assert_eq!(Ordering::Less, seasonal.cmp(&naive));
+        assert_eq!(Ordering::Equal, lifetime.cmp(&BoosterSchedule::Lifetime));
+
+        // Vaccines with the same booster schedule fall back to their names, so the order does
+        // not depend on the order they came out of the map.
+        let vaccines = Vaccine::get_vaccines();
+        let sorted = |mut list: Vec<&Vaccine>| {
+            list.sort();
+            list.into_iter().map(|v| v.name()).collect_vec()
+        };
+        let order = sorted(vaccines.values().collect_vec());
+        assert_eq!(
+            order,
+            sorted(vaccines.values().collect_vec().into_iter().rev().collect())
+        );
+        assert_eq!(
+            Ordering::Less,
+            vaccines["Chickenpox"].cmp(&vaccines["Hepatitis B"])
+        );
+    }
+
     #[test]
     fn test_booster_schedule() -> Result<()> {
         // Lifetime with first of 3 doses
         assert_eq!(
             vec![(DoseKind::Booster, 12 + 25 * 12)],
             Vaccine::get_vaccines()
-                .get("Gardacil-9")
+                .get("Gardasil-9")
                 .unwrap()
                 .booster_schedule()
                 .all_months(
@@ -756,11 +3451,16 @@ mod tests {
                     50 * 12,
                     Some(12),
                     &[&VaccineRecord {
-                        vaccine: "Gardacil-9".to_string(),
+                        vaccine: "Gardasil-9".to_string(),
                         date: test_time()?,
+                        precision: DatePrecision::Day,
                         kind: DoseKind::Dose(0),
                         notes: "".to_string(),
-                    }]
+                        reaction: None,
+                        invalid: false,
+                        source: RecordSource::SelfReported,
+                    }],
+                    Season::default()
                 )?
         );
 
@@ -771,7 +3471,7 @@ mod tests {
                 (DoseKind::Booster, 50 * 12 - 1)
             ],
             Vaccine::get_vaccines()
-                .get("Gardacil-9")
+                .get("Gardasil-9")
                 .unwrap()
                 .booster_schedule()
                 .all_months(
@@ -780,48 +3480,61 @@ mod tests {
                     None,
                     &[
                         &VaccineRecord {
-                            vaccine: "Gardacil-9".to_string(),
+                            vaccine: "Gardasil-9".to_string(),
                             date: test_time()?.sub(Span::new().months(13)),
+                            precision: DatePrecision::Day,
                             kind: DoseKind::Dose(0),
                             notes: "".to_string(),
+                            reaction: None,
+                            invalid: false,
+                            source: RecordSource::SelfReported,
                         },
                         &VaccineRecord {
-                            vaccine: "Gardacil-9".to_string(),
+                            vaccine: "Gardasil-9".to_string(),
                             date: test_time()?.sub(Span::new().months(7)),
+                            precision: DatePrecision::Day,
                             kind: DoseKind::Dose(1),
                             notes: "".to_string(),
+                            reaction: None,
+                            invalid: false,
+                            source: RecordSource::SelfReported,
                         },
                         &VaccineRecord {
-                            vaccine: "Gardacil-9".to_string(),
+                            vaccine: "Gardasil-9".to_string(),
                             date: test_time()?.sub(Span::new().months(1)),
+                            precision: DatePrecision::Day,
                             kind: DoseKind::Dose(2),
                             notes: "".to_string(),
+                            reaction: None,
+                            invalid: false,
+                            source: RecordSource::SelfReported,
                         },
-                    ]
+                    ],
+                    Season::default()
                 )?
         );
 
-        // Seasonal with last dose scheduled at the start of the year
+        // Seasonal with last dose scheduled this fall (October): the booster is next fall.
         assert_eq!(
-            vec![(DoseKind::Booster, 8 + 12)],
+            vec![(DoseKind::Booster, 3 + 12)],
             Vaccine::get_vaccines()
                 .get("Flu")
                 .unwrap()
                 .booster_schedule()
-                .all_months(&test_time()?, 24, Some(4), &[])?
+                .all_months(&test_time()?, 24, Some(4), &[], Season::default())?
         );
-        // Seasonal with last dose scheduled at the end of the year
+        // Seasonal with last dose scheduled next spring (April): still next fall.
         assert_eq!(
-            vec![(DoseKind::Booster, 8 + 12)],
+            vec![(DoseKind::Booster, 3 + 12)],
             Vaccine::get_vaccines()
                 .get("Flu")
                 .unwrap()
                 .booster_schedule()
-                .all_months(&test_time()?, 24, Some(10), &[])?
+                .all_months(&test_time()?, 24, Some(10), &[], Season::default())?
         );
-        // Seasonal with last dose taken in middle of last year
+        // Seasonal with last dose taken last June: boosters each September.
         assert_eq!(
-            vec![(DoseKind::Booster, 8), (DoseKind::Booster, 8 + 12)],
+            vec![(DoseKind::Booster, 3), (DoseKind::Booster, 3 + 12)],
             Vaccine::get_vaccines()
                 .get("Flu")
                 .unwrap()
@@ -833,14 +3546,19 @@ mod tests {
                     &[&VaccineRecord {
                         vaccine: "Flu".to_string(),
                         date: test_time()?.sub(Span::new().months(12)),
+                        precision: DatePrecision::Day,
                         kind: DoseKind::Dose(0),
                         notes: "".to_string(),
-                    },]
+                        reaction: None,
+                        invalid: false,
+                        source: RecordSource::SelfReported,
+                    },],
+                    Season::default()
                 )?
         );
-        // Seasonal with last dose taken at the end of last year
+        // Seasonal with last dose taken last November, late in the season: a year on from it.
         assert_eq!(
-            vec![(DoseKind::Booster, 8), (DoseKind::Booster, 8 + 12)],
+            vec![(DoseKind::Booster, 5), (DoseKind::Booster, 5 + 12)],
             Vaccine::get_vaccines()
                 .get("Flu")
                 .unwrap()
@@ -852,14 +3570,1490 @@ mod tests {
                     &[&VaccineRecord {
                         vaccine: "Flu".to_string(),
                         date: test_time()?.sub(Span::new().months(7)),
+                        precision: DatePrecision::Day,
                         kind: DoseKind::Dose(0),
                         notes: "".to_string(),
-                    },]
+                        reaction: None,
+                        invalid: false,
+                        source: RecordSource::SelfReported,
+                    },],
+                    Season::default()
                 )?
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_seasonal_second_dose() -> Result<()> {
+        let flu = Vaccine::get_vaccines()
+            .get("Flu")
+            .unwrap()
+            .booster_schedule();
+        // A first-time recipient gets a second dose a month after their first, then boosters.
+        assert_eq!(
+            vec![
+                (DoseKind::Dose(1), 1),
+                (DoseKind::Booster, 3 + 12),
+                (DoseKind::Booster, 3 + 24)
+            ],
+            flu.all_months(&test_time()?, 36, Some(0), &[], naive_season())?
+        );
+        // Someone with prior exposure only gets the fall booster.
+        assert_eq!(
+            vec![(DoseKind::Booster, 3 + 12), (DoseKind::Booster, 3 + 24)],
+            flu.all_months(&test_time()?, 36, Some(0), &[], Season::default())?
+        );
+        // Any prior record means this isn't the first season.
+        assert_eq!(
+            vec![(DoseKind::Booster, 3), (DoseKind::Booster, 3 + 12)],
+            flu.all_months(
+                &test_time()?,
+                24,
+                None,
+                &[&VaccineRecord::new(
+                    "Flu",
+                    test_time()?.sub(Span::new().months(12)),
+                    DoseKind::Dose(0),
+                    ""
+                )],
+                naive_season()
+            )?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_seasonal_second_dose_by_age() -> Result<()> {
+        let flu = Vaccine::get_vaccines().get("Flu").unwrap();
+        let now = test_time()?;
+        let plan = |date_of_birth, records: &[VaccineRecord]| -> Result<Vec<String>> {
+            let options = ScheduleOptions {
+                seasonal_naive: true,
+                date_of_birth: Some(date_of_birth),
+                ..ScheduleOptions::default()
+            };
+            let doses = flu.all_doses(&now, records.iter(), 24, &options)?;
+            Ok(doses
+                .into_iter()
+                .map(|(kind, mo)| {
+                    let (year, month) = VaccineAppointment::mo_to_ym(&now, mo);
+                    format!("{year}-{month:02} {kind}")
+                })
+                .collect())
+        };
+        let child = Date::new(2020, 3, 1)?;
+        let adult = Date::new(1990, 3, 1)?;
+        let last_season = [VaccineRecord::new(
+            "Flu",
+            test_time()?.sub(Span::new().months(8)),
+            DoseKind::Dose(0),
+            "",
+        )];
+
+        // A first-time 5-year-old gets two doses four weeks apart, then boosters each fall.
+        assert_eq!(
+            vec!["2025-06 Dose#1", "2025-07 Dose#2", "2026-09 Booster"],
+            plan(child, &[])?
+        );
+        // A first-time adult gets one.
+        assert_eq!(vec!["2025-06 Dose#1", "2026-09 Booster"], plan(adult, &[])?);
+        // So does a child who had a flu shot last October.
+        assert_eq!(
+            vec!["2025-10 Booster", "2026-10 Booster"],
+            plan(child, &last_season)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_seasonal_second_dose_after_recorded_first() -> Result<()> {
+        let now = Date::new(2025, 10, 1)?.to_zoned(TimeZone::get("America/Los_Angeles")?)?;
+        let options = ScheduleOptions {
+            seasonal_naive: true,
+            date_of_birth: Some(Date::new(2020, 1, 1)?),
+            ..ScheduleOptions::default()
+        };
+        let plan = |records: &[VaccineRecord]| -> Result<Vec<String>> {
+            Ok(summarize(&Vaccine::schedule(
+                &now,
+                ["Flu".to_owned()].into_iter(),
+                2027,
+                records,
+                &options,
+            )?))
+        };
+        assert_eq!(
+            vec![
+                "2025-10 Flu Dose#1",
+                "2025-11 Flu Dose#2",
+                "2026-09 Flu Booster",
+                "2027-09 Flu Booster"
+            ],
+            plan(&[])?
+        );
+
+        // Recording the first dose leaves the second one planned four weeks after it.
+        let records = [VaccineRecord::new(
+            "Flu",
+            Date::new(2025, 9, 20)?.to_zoned(now.time_zone().clone())?,
+            DoseKind::Dose(0),
+            "",
+        )];
+        assert_eq!(
+            vec![
+                "2025-11 Flu Dose#2",
+                "2026-09 Flu Booster",
+                "2027-09 Flu Booster"
+            ],
+            plan(&records)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_seasonal_booster_after_late_last_dose() -> Result<()> {
+        let covid = Vaccine::get_vaccines()
+            .get("COVID-19")
+            .unwrap()
+            .booster_schedule();
+        // The last initial dose lands in November, two years out; the booster waits for the
+        // following September rather than snapping back to one before the series is done.
+        let last_dose_mo = 12 + 12 + 5;
+        let boosters = covid.all_months(
+            &test_time()?,
+            60,
+            Some(last_dose_mo),
+            &[],
+            Season::default(),
+        )?;
+        assert_eq!(Some(&(DoseKind::Booster, 36 + 3)), boosters.first());
+        assert!(boosters.iter().all(|(_, mo)| *mo > last_dose_mo));
+        Ok(())
+    }
+
+    // Every built-in vaccine must produce a sane plan, both from scratch and part way through
+    // its initial series.
+    #[test]
+    fn test_all_vaccines_produce_valid_plans() -> Result<()> {
+        let now = test_time()?;
+        let limit_mo = 10 * 12;
+        for vaccine in Vaccine::get_vaccines().values() {
+            let started = [VaccineRecord::new(
+                vaccine.name(),
+                now.checked_sub(Span::new().months(2))?,
+                DoseKind::Dose(0),
+                "",
+            )];
+            for records in [&[][..], &started[..]] {
+                for naive in [false, true] {
+                    let options = ScheduleOptions {
+                        seasonal_naive: naive,
+                        ..ScheduleOptions::default()
+                    };
+                    let doses = vaccine.all_doses(&now, records.iter(), limit_mo, &options)?;
+                    let name = vaccine.name();
+                    assert!(doses.iter().all(|(_, mo)| *mo >= 0), "{name}: {doses:?}");
+                    assert!(doses.is_sorted_by_key(|(_, mo)| *mo), "{name}: {doses:?}");
+                    // A single dose series may be complete after the first record.
+                    assert!(
+                        !records.is_empty() || doses.iter().any(|(_, mo)| *mo <= limit_mo),
+                        "{name}: nothing planned"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dose_labels() {
+        let defaults = DoseLabels::default();
+        for (name, kind) in DoseKind::all_kinds() {
+            assert_eq!(*name, kind.label(&defaults));
+        }
+        let labels = DoseLabels {
+            dose: "Primary {n}".to_owned(),
+            booster: "Top-up".to_owned(),
+        };
+        assert_eq!("Primary 1", DoseKind::Dose(0).label(&labels));
+        assert_eq!("Primary 3", DoseKind::Dose(2).label(&labels));
+        assert_eq!("Top-up", DoseKind::Booster.label(&labels));
+    }
+
+    #[test]
+    fn test_vaccines_have_review_metadata() -> Result<()> {
+        for vaccine in Vaccine::get_vaccines().values() {
+            let reviewed = vaccine
+                .last_reviewed()
+                .unwrap_or_else(|| panic!("{} has no review date", vaccine.name()));
+            let (year, month) = reviewed.split_once('-').expect("YYYY-MM review date");
+            Date::new(year.parse()?, month.parse()?, 1)?;
+            assert!(
+                vaccine.source().starts_with("https://"),
+                "{}",
+                vaccine.name()
+            );
+        }
+        assert!(Vaccine::data_last_reviewed().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_past_end_plan_year() -> Result<()> {
+        let schedule = |end_plan_year| {
+            Vaccine::schedule(
+                &test_time()?,
+                ["Tdap".to_owned()].into_iter(),
+                end_plan_year,
+                &[],
+                &ScheduleOptions::default(),
+            )
+        };
+        let clamped = schedule(2000)?;
+        assert_eq!(schedule(2025)?, clamped);
+        assert_eq!(1, clamped.len());
+        assert_eq!((2025, 6), (clamped[0].year(), clamped[0].month()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_already_immune_skips_initial_series() -> Result<()> {
+        let options = ScheduleOptions {
+            already_immune: HashSet::from(["Tdap".to_owned()]),
+            ..Default::default()
+        };
+        let appts = Vaccine::schedule(
+            &test_time()?,
+            ["Tdap".to_owned()].into_iter(),
+            2040,
+            &[],
+            &options,
+        )?;
+        assert_eq!(
+            vec!["2025-06 Tdap Booster", "2035-06 Tdap Booster"],
+            summarize(&appts)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_already_immune_seasonal() -> Result<()> {
+        // Nothing on record puts the last dose in the past; that must not trip up the check for
+        // a first-season second dose.
+        let options = ScheduleOptions {
+            already_immune: HashSet::from(["Flu".to_owned(), "COVID-19".to_owned()]),
+            seasonal_naive: true,
+            date_of_birth: Some(Date::new(2020, 1, 1)?),
+            ..Default::default()
+        };
+        let appts = Vaccine::schedule(
+            &test_time()?,
+            ["Flu".to_owned(), "COVID-19".to_owned()].into_iter(),
+            2030,
+            &[],
+            &options,
+        )?;
+        assert_eq!(
+            (2025..2030)
+                .flat_map(|year| [
+                    format!("{year}-09 Flu Booster"),
+                    format!("{year}-09 COVID-19 Booster")
+                ])
+                .collect_vec(),
+            summarize(&appts)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_dose_shifts_series() -> Result<()> {
+        let now = test_time()?;
+        let plan = |options: &ScheduleOptions| {
+            Vaccine::schedule(&now, ["Tdap".to_owned()].into_iter(), 2037, &[], options)
+        };
+        let appts = plan(&ScheduleOptions::default())?;
+        assert_eq!(
+            vec![
+                "2025-06 Tdap Dose#1",
+                "2025-12 Tdap Dose#2",
+                "2026-06 Tdap Dose#3",
+                "2036-06 Tdap Booster"
+            ],
+            summarize(&appts)
+        );
+        for appt in &appts {
+            assert_eq!(
+                appt,
+                &VaccineAppointment::from_month_offset(
+                    appt.vaccine(),
+                    appt.kind(),
+                    &now,
+                    appt.month_offset(&now)
+                )
+            );
+        }
+
+        // Missing the second dose re-plans it one interval after the missed slot, and the
+        // rest of the series follows.
+        let options = ScheduleOptions {
+            skipped: vec![appts[1].clone()],
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![
+                "2025-06 Tdap Dose#1",
+                "2026-06 Tdap Dose#2",
+                "2026-12 Tdap Dose#3",
+                "2036-12 Tdap Booster"
+            ],
+            summarize(&plan(&options)?)
+        );
+
+        // A skip that no longer matches the plan is ignored.
+        let options = ScheduleOptions {
+            skipped: vec![VaccineAppointment::from_month_offset(
+                "Tdap",
+                DoseKind::Dose(2),
+                &now,
+                3,
+            )],
+            ..Default::default()
+        };
+        assert_eq!(appts, plan(&options)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_months_since_truncates() -> Result<()> {
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        let now = test_time()?;
+        let at = |y, m, d| -> Result<Zoned> { Ok(Date::new(y, m, d)?.to_zoned(tz.clone())?) };
+        assert_eq!(6, months_since(&at(2024, 12, 1)?, &now)?);
+        assert_eq!(5, months_since(&at(2024, 12, 2)?, &now)?);
+        assert_eq!(5, months_since(&at(2024, 12, 12)?, &now)?);
+        assert_eq!(6, months_since(&at(2024, 11, 30)?, &now)?);
+        assert_eq!(0, months_since(&now, &now)?);
+        assert_eq!(-1, months_since(&at(2025, 7, 1)?, &now)?);
+        // Month lengths differ: a month from Jan 31 ends on the last day of February.
+        assert_eq!(0, months_since(&at(2025, 1, 31)?, &at(2025, 2, 27)?)?);
+        assert_eq!(1, months_since(&at(2025, 1, 31)?, &at(2025, 3, 1)?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_from_another_zone() -> Result<()> {
+        // Just after midnight on March 1 in Tokyo is still February 28 in Los Angeles, so six
+        // months later the second dose is due there at the end of August.
+        let given =
+            DateTime::new(2025, 3, 1, 0, 30, 0, 0)?.to_zoned(TimeZone::get("Asia/Tokyo")?)?;
+        let now = DateTime::new(2025, 8, 30, 12, 0, 0, 0)?
+            .to_zoned(TimeZone::get("America/Los_Angeles")?)?;
+        assert_eq!(6, months_since(&given, &now)?);
+        let record = VaccineRecord::new("Tdap", given, DoseKind::Dose(0), "");
+        assert_eq!(
+            vec![(DoseKind::Dose(1), 0), (DoseKind::Dose(2), 6)],
+            Vaccine::get_vaccines()["Tdap"]
+                .dosage_schedule()
+                .all_months(&now, [&&record].into_iter(), None, PlanningMode::Soonest)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_interval_not_cut_short_near_month_boundary() -> Result<()> {
+        let now = test_time()?;
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        let vaccine = &Vaccine::get_vaccines()["Meningitis"];
+        let next_dose = |y, m, d| -> Result<Vec<(DoseKind, i16)>> {
+            let date = Date::new(y, m, d)?.to_zoned(tz.clone())?;
+            let records = [VaccineRecord::new(
+                "Meningitis",
+                date,
+                DoseKind::Dose(0),
+                "",
+            )];
+            vaccine.all_doses(&now, records.iter(), 12, &ScheduleOptions::default())
+        };
+        // Exactly six months ago, so the second dose is due now.
+        assert_eq!((DoseKind::Dose(1), 0), next_dose(2024, 12, 1)?[0]);
+        // Five months and 20 days ago used to round up to six months; wait for next month.
+        assert_eq!((DoseKind::Dose(1), 1), next_dose(2024, 12, 12)?[0]);
+
+        // Boosters count elapsed months the same way.
+        let booster = |y, m, d| -> Result<Option<i16>> {
+            let first = Date::new(2019, 1, 1)?.to_zoned(tz.clone())?;
+            let last = Date::new(y, m, d)?.to_zoned(tz.clone())?;
+            let records = [
+                VaccineRecord::new("Meningitis", first, DoseKind::Dose(0), ""),
+                VaccineRecord::new("Meningitis", last, DoseKind::Dose(1), ""),
+            ];
+            Ok(vaccine
+                .all_doses(&now, records.iter(), 12, &ScheduleOptions::default())?
+                .first()
+                .map(|(_, mo)| *mo))
+        };
+        assert_eq!(Some(0), booster(2020, 6, 1)?);
+        assert_eq!(Some(1), booster(2020, 6, 12)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_age_in_month() -> Result<()> {
+        let dob = Date::new(1990, 7, 15)?;
+        assert_eq!(Some(44), age_in_month(dob, 2035, 1));
+        assert_eq!(Some(44), age_in_month(dob, 2035, 6));
+        assert_eq!(Some(45), age_in_month(dob, 2035, 7));
+        assert_eq!(Some(45), age_in_month(dob, 2035, 12));
+        assert_eq!(Some(0), age_in_month(dob, 1990, 7));
+        assert_eq!(None, age_in_month(dob, 1990, 6));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_dose_is_repeated() -> Result<()> {
+        let now = test_time()?;
+        let schedule = DoseSchedule::Custom(vec![0, 1, 6]);
+        let record = |kind, months_ago, invalid| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Custom",
+                now.checked_sub(Span::new().months(months_ago))?,
+                kind,
+                "stored warm",
+            )
+            .with_invalid(invalid))
+        };
+        let records = [record(DoseKind::Dose(0), 2, true)?];
+        assert_eq!(
+            vec![
+                (DoseKind::Dose(0), 0),
+                (DoseKind::Dose(1), 1),
+                (DoseKind::Dose(2), 6)
+            ],
+            schedule.all_months(
+                &now,
+                records.iter().collect_vec().iter(),
+                None,
+                PlanningMode::Soonest
+            )?
+        );
+
+        // The repeat is timed from the invalid dose.
+        let records = [
+            record(DoseKind::Dose(0), 3, false)?,
+            record(DoseKind::Dose(1), 0, true)?,
+        ];
+        assert_eq!(
+            vec![(DoseKind::Dose(1), 1), (DoseKind::Dose(2), 6)],
+            schedule.all_months(
+                &now,
+                records.iter().collect_vec().iter(),
+                None,
+                PlanningMode::Soonest
+            )?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_dose_schedule() -> Result<()> {
+        let now = test_time()?;
+        let schedule = DoseSchedule::Custom(vec![0, 1, 6]);
+        assert_eq!("3x at months 0, 1, 6", schedule.to_string());
+        assert_eq!(1, schedule.minimum_dose_interval());
+        assert_eq!(
+            vec![
+                (DoseKind::Dose(0), 0),
+                (DoseKind::Dose(1), 1),
+                (DoseKind::Dose(2), 6)
+            ],
+            schedule.all_months(&now, [].iter(), None, PlanningMode::Soonest)?
+        );
+
+        // Catch-up waits for the gap before the next dose in the list, not the shortest gap.
+        let record = |kind, months_ago| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Custom",
+                now.checked_sub(Span::new().months(months_ago))?,
+                kind,
+                "",
+            ))
+        };
+        let records = [record(DoseKind::Dose(0), 3)?, record(DoseKind::Dose(1), 2)?];
+        assert_eq!(
+            vec![(DoseKind::Dose(2), 3)],
+            schedule.all_months(
+                &now,
+                records.iter().collect_vec().iter(),
+                None,
+                PlanningMode::Soonest
+            )?
+        );
+        let records = [record(DoseKind::Dose(0), 2)?];
+        assert_eq!(
+            vec![(DoseKind::Dose(1), 0), (DoseKind::Dose(2), 5)],
+            schedule.all_months(
+                &now,
+                records.iter().collect_vec().iter(),
+                None,
+                PlanningMode::Soonest
+            )?
+        );
+        let records = [record(DoseKind::Dose(0), 0)?];
+        assert_eq!(
+            vec![(DoseKind::Dose(1), 1), (DoseKind::Dose(2), 6)],
+            schedule.all_months(
+                &now,
+                records.iter().collect_vec().iter(),
+                None,
+                PlanningMode::Soonest
+            )?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_renamed_vaccines() -> Result<()> {
+        assert_eq!("Shingrix", current_vaccine_name("Shinglex"));
+        assert_eq!("Gardasil-9", current_vaccine_name("Gardacil-9"));
+        assert_eq!("Tdap", current_vaccine_name("Tdap"));
+        for (old, current) in RENAMED_VACCINES {
+            assert!(!Vaccine::get_vaccines().contains_key(old));
+            assert!(Vaccine::get_vaccines().contains_key(current));
+        }
+
+        // Records saved under the old name are records of the renamed vaccine.
+        let record: VaccineRecord = ron::de::from_str(
+            r#"(vaccine: "Shinglex", date: "2025-01-01T00:00:00+00:00[UTC]", kind: Dose(0), notes: "")"#,
+        )?;
+        assert_eq!("Shingrix", record.vaccine());
+        let now = test_time()?;
+        let appts = Vaccine::schedule(
+            &now,
+            ["Shingrix".to_owned()].into_iter(),
+            2030,
+            &[record],
+            &ScheduleOptions::default(),
+        )?;
+        assert_eq!(
+            vec![DoseKind::Dose(1)],
+            appts.iter().map(|a| a.kind()).collect_vec()
+        );
+        assert_eq!(
+            "Gardasil-9",
+            VaccineRecord::new("Gardacil-9", now, DoseKind::Dose(0), "").vaccine()
+        );
+
+        let coadministration: CoAdministration =
+            ron::de::from_str(r#"(separate: [("Shinglex", "Tdap")])"#)?;
+        assert!(!coadministration.allowed("Shingrix", "Tdap"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_collapse_close_boosters() -> Result<()> {
+        // Two lifetime boosters a month apart around a step boundary are one booster.
+        let mut boosters = vec![
+            (DoseKind::Booster, 25 * 12 - 1),
+            (DoseKind::Booster, 25 * 12),
+            (DoseKind::Booster, 50 * 12 - 1),
+        ];
+        BoosterSchedule::Lifetime.collapse_close_boosters(&mut boosters);
+        assert_eq!(
+            vec![
+                (DoseKind::Booster, 25 * 12 - 1),
+                (DoseKind::Booster, 50 * 12 - 1)
+            ],
+            boosters
+        );
+
+        // A seasonal booster one month after another goes, but the first-season second dose
+        // stays.
+        let seasonal = BoosterSchedule::Seasonal {
+            second_dose_if_naive: true,
+        };
+        let mut boosters = vec![
+            (DoseKind::Booster, 8),
+            (DoseKind::Booster, 9),
+            (DoseKind::Booster, 12),
+            (DoseKind::Booster, 20),
+        ];
+        seasonal.collapse_close_boosters(&mut boosters);
+        assert_eq!(
+            vec![
+                (DoseKind::Booster, 8),
+                (DoseKind::Booster, 12),
+                (DoseKind::Booster, 20)
+            ],
+            boosters
+        );
+        assert_eq!(
+            vec![(DoseKind::Dose(1), 1), (DoseKind::Booster, 15)],
+            seasonal.all_months(&test_time()?, 24, Some(0), &[], naive_season())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_injection_dose() -> Result<()> {
+        let now = test_time()?;
+        let labels = DoseLabels::default();
+        let split = VaccineAppointment::from_month_offset("Rabies", DoseKind::Dose(0), &now, 0)
+            .with_injections(2);
+        let single = VaccineAppointment::from_month_offset("Tdap", DoseKind::Dose(0), &now, 0);
+        let later = VaccineAppointment::from_month_offset("Tdap", DoseKind::Dose(1), &now, 6);
+        assert_eq!("Dose#1 (2 injections)", split.label(&labels));
+        assert_eq!("Dose#1", single.label(&labels));
+
+        // Two vaccines and three shots, but still the one visit.
+        assert_eq!(
+            vec![((2025, 6), 3), ((2025, 12), 1)],
+            visit_injections(&[split.clone(), single, later])
+        );
+
+        let data = ron::ser::to_string(&split)?;
+        assert!(data.contains("injections:2"));
+        assert_eq!(split, ron::de::from_str(&data)?);
+        let plain = ron::de::from_str::<VaccineAppointment>(
+            r#"(vaccine: "Tdap", kind: Dose(0), year: 2025, month: 6)"#,
+        )?;
+        assert_eq!(1, plain.injections);
+        Ok(())
+    }
+
+    #[test]
+    fn test_month_gaps() -> Result<()> {
+        let now = test_time()?;
+        let tdap = &Vaccine::get_vaccines()["Tdap"];
+        let gaps = |records: &[VaccineRecord]| -> Result<Vec<Option<i16>>> {
+            let doses =
+                tdap.all_doses(&now, records.iter(), 12 * 5, &ScheduleOptions::default())?;
+            Ok(month_gaps(
+                doses
+                    .iter()
+                    .filter(|(kind, _)| matches!(kind, DoseKind::Dose(_)))
+                    .map(|(_, mo)| *mo),
+            ))
+        };
+        assert_eq!(vec![None, Some(6), Some(6)], gaps(&[])?);
+
+        // Catch-up keeps the spacing between the doses that are left.
+        let first = VaccineRecord::new(
+            "Tdap",
+            now.checked_sub(Span::new().months(2))?,
+            DoseKind::Dose(0),
+            "",
+        );
+        assert_eq!(vec![None, Some(6)], gaps(&[first])?);
+        assert!(month_gaps([]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_why_unscheduled() -> Result<()> {
+        let now = test_time()?;
+        let tz = TimeZone::get("America/Los_Angeles")?;
+        let vaccines = Vaccine::get_vaccines();
+        let options = ScheduleOptions::default();
+        let why = |name: &str, records: &[VaccineRecord]| -> Result<Option<Unscheduled>> {
+            // Only ask about vaccines that really have nothing planned.
+            let plan =
+                Vaccine::schedule(&now, [name.to_owned()].into_iter(), 2027, records, &options)?;
+            assert!(
+                plan.is_empty()
+                    == vaccines[name]
+                        .why_unscheduled(&now, 2027, records, &options)?
+                        .is_some()
+            );
+            vaccines[name].why_unscheduled(&now, 2027, records, &options)
+        };
+        let record = |name: &str, y, m, kind| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                name,
+                Date::new(y, m, 1)?.to_zoned(tz.clone())?,
+                kind,
+                "",
+            ))
+        };
+
+        assert_eq!(None, why("Tdap", &[])?);
+        // Already complete: the series is done and the next booster is years away.
+        let hep_b = [record("Hepatitis B", 2020, 1, DoseKind::Dose(0))?];
+        assert_eq!(
+            Some(Unscheduled::Complete {
+                next_booster: Some((2045, 1))
+            }),
+            why("Hepatitis B", &hep_b)?
+        );
+        let meningitis = [
+            record("Meningitis", 2024, 6, DoseKind::Dose(0))?,
+            record("Meningitis", 2024, 12, DoseKind::Dose(1))?,
+        ];
+        assert_eq!(
+            "The initial series is complete and the next booster is not due until December \
+             2029, after the end of the plan.",
+            why("Meningitis", &meningitis)?.unwrap().to_string()
+        );
+
+        // Outside the window: the second MMR dose comes five years after the first.
+        let mmr = [record("MMR", 2024, 1, DoseKind::Dose(0))?];
+        let reason = why("MMR", &mmr)?;
+        assert_eq!(Some(Unscheduled::OutsideWindow { next: (2029, 1) }), reason);
+        assert_eq!(
+            "Nothing is due until January 2029, after the end of the plan.",
+            reason.unwrap().to_string()
+        );
+
+        let young = ScheduleOptions {
+            date_of_birth: Some(Date::new(2020, 3, 15)?),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(Unscheduled::TooYoung {
+                eligible: (2070, 3)
+            }),
+            vaccines["Shingrix"].why_unscheduled(&now, 2027, &[], &young)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_due_now() -> Result<()> {
+        let now = test_time()?;
+        let record = |vaccine, kind, months_ago| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                vaccine,
+                now.checked_sub(Span::new().months(months_ago))?,
+                kind,
+                "",
+            ))
+        };
+        // Never started: the first dose is due.
+        assert!(is_due_now("Tdap", &now, &[])?);
+        // The second dose is 6 months after the first.
+        let records = [
+            record("Tdap", DoseKind::Dose(0), 2)?,
+            record("Hepatitis B", DoseKind::Dose(0), 2)?,
+        ];
+        assert!(!is_due_now("Tdap", &now, &records)?);
+        let records = [record("Tdap", DoseKind::Dose(0), 6)?];
+        assert!(is_due_now("Tdap", &now, &records)?);
+        assert!(is_due_now("Not A Vaccine", &now, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_round_trip() -> Result<()> {
+        let data = Vaccine::database_to_ron()?;
+        assert!(data.contains("last_reviewed"));
+        let parsed: HashMap<String, Vaccine> = ron::de::from_str(&data)?;
+        assert_eq!(Vaccine::get_vaccines().len(), parsed.len());
+        for (name, vaccine) in Vaccine::get_vaccines() {
+            assert_eq!(Some(vaccine), parsed.get(*name));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_restart_after_long_gap() -> Result<()> {
+        let now = test_time()?;
+        let schedule = Vaccine::get_vaccines()["Gardasil-9"].dosage_schedule();
+        let record = |kind, months_ago| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                "Gardasil-9",
+                now.checked_sub(Span::new().months(months_ago))?,
+                kind,
+                "",
+            ))
+        };
+        let records = [
+            record(DoseKind::Dose(0), 30)?,
+            record(DoseKind::Dose(1), 20)?,
+        ];
+        let dose_records = records.iter().collect_vec();
+
+        // Under the threshold, or with no threshold at all, pick up where the series left off.
+        let resumed = vec![(DoseKind::Dose(2), 0)];
+        assert_eq!(
+            resumed,
+            schedule.all_months(&now, dose_records.iter(), None, PlanningMode::Soonest)?
+        );
+        assert_eq!(
+            resumed,
+            schedule.all_months(&now, dose_records.iter(), Some(24), PlanningMode::Soonest)?
+        );
+
+        // Over it, start again at the first dose.
+        assert_eq!(
+            schedule.all_doses(),
+            schedule.all_months(&now, dose_records.iter(), Some(12), PlanningMode::Soonest)?
+        );
+
+        // A finished series is never restarted.
+        let finished = [
+            record(DoseKind::Dose(0), 50)?,
+            record(DoseKind::Dose(1), 44)?,
+            record(DoseKind::Dose(2), 38)?,
+        ];
+        let finished = finished.iter().collect_vec();
+        assert!(schedule
+            .all_months(&now, finished.iter(), Some(12), PlanningMode::Soonest)?
+            .is_empty());
+
+        let mut hpv = Vaccine::get_vaccines()["Gardasil-9"].clone();
+        assert!(!hpv.restart_recommended(&now, records.iter())?);
+        hpv.restart_after_mo = Some(12);
+        assert!(hpv.restart_recommended(&now, records.iter())?);
+        let recent = [record(DoseKind::Dose(0), 6)?];
+        assert!(!hpv.restart_recommended(&now, recent.iter())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_reaction_round_trip() -> Result<()> {
+        let date = test_time()?;
+        let plain = VaccineRecord::new("Tdap", date.clone(), DoseKind::Dose(0), "");
+        let data = ron::ser::to_string(&plain)?;
+        assert!(!data.contains("reaction"));
+        assert!(!data.contains("source"));
+        assert_eq!(plain, ron::de::from_str::<VaccineRecord>(&data)?);
+
+        let reacted = plain.with_reaction(Reaction {
+            severity: Severity::Moderate,
+            description: "Fever for two days".to_owned(),
+        });
+        let data = ron::ser::to_string(&reacted)?;
+        assert_eq!(reacted, ron::de::from_str::<VaccineRecord>(&data)?);
+        assert_eq!(
+            Some(Severity::Moderate),
+            reacted.reaction().map(|r| r.severity)
+        );
+
+        let reaction = reacted.reaction().unwrap();
+        assert_eq!(*reaction, reaction.to_string().parse()?);
+        assert_eq!(
+            Reaction {
+                severity: Severity::Mild,
+                description: "sore arm: left".to_owned()
+            },
+            "sore arm: left".parse()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_preferred_day_of_month() -> Result<()> {
+        let now = test_time()?;
+        let options = ScheduleOptions {
+            preferred_day_of_month: Some(15),
+            ..ScheduleOptions::default()
+        };
+        let appts = Vaccine::schedule(
+            &now,
+            ["Hepatitis B".to_owned()].into_iter(),
+            2026,
+            &[],
+            &options,
+        )?;
+        assert!(!appts.is_empty());
+        for appt in &appts {
+            assert_eq!(Some(15), appt.day());
+            assert_eq!(Date::new(appt.year(), appt.month(), 15)?, appt.date());
+        }
+        // The same plan, only with days filled in.
+        let monthly = Vaccine::schedule(
+            &now,
+            ["Hepatitis B".to_owned()].into_iter(),
+            2026,
+            &[],
+            &ScheduleOptions::default(),
+        )?;
+        assert_eq!(
+            monthly.iter().map(|a| (a.year(), a.month())).collect_vec(),
+            appts.iter().map(|a| (a.year(), a.month())).collect_vec()
+        );
+        assert_eq!(Date::new(2025, 6, 1)?, monthly[0].date());
+
+        let february = VaccineAppointment::from_month_offset("Tdap", DoseKind::Booster, &now, 8)
+            .with_day(Some(31));
+        assert_eq!(Date::new(2026, 2, 28)?, february.date());
+        Ok(())
+    }
+
+    #[test]
+    fn test_antibody_event_defers_live_vaccines() -> Result<()> {
+        let now = test_time()?;
+        let plan = |events: Vec<AntibodyEvent>| {
+            Vaccine::schedule(
+                &now,
+                ["MMR".to_owned(), "Tdap".to_owned()].into_iter(),
+                2040,
+                &[],
+                &ScheduleOptions {
+                    antibody_events: events,
+                    ..ScheduleOptions::default()
+                },
+            )
+        };
+        let first = |appts: &[VaccineAppointment], name| {
+            appts
+                .iter()
+                .find(|a| a.vaccine() == name)
+                .map(|a| a.month_offset(&now))
+        };
+        let before = plan(vec![])?;
+        assert_eq!(Some(0), first(&before, "MMR"));
+
+        // Immune globulin two months ago with a six month deferral: MMR waits four more months.
+        let event = AntibodyEvent {
+            product: "Immune globulin".to_owned(),
+            date: Date::new(2025, 4, 10)?,
+            deferral_mo: 6,
+        };
+        let after = plan(vec![event.clone()])?;
+        assert_eq!(Some(4), first(&after, "MMR"));
+        // The rest of the series keeps its spacing, and inactivated vaccines are not held back.
+        let mmr_offsets = |appts: &[VaccineAppointment]| {
+            appts
+                .iter()
+                .filter(|a| a.vaccine() == "MMR")
+                .map(|a| a.month_offset(&now))
+                .collect_vec()
+        };
+        let shifted = mmr_offsets(&after);
+        assert_eq!(
+            mmr_offsets(&before)
+                .iter()
+                .map(|mo| mo + 4)
+                .take(shifted.len())
+                .collect_vec(),
+            shifted
+        );
+        assert_eq!(first(&before, "Tdap"), first(&after, "Tdap"));
+
+        // An event whose deferral has run out changes nothing.
+        let old = AntibodyEvent {
+            date: Date::new(2020, 1, 1)?,
+            ..event
+        };
+        assert_eq!(before, plan(vec![old])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snooze() -> Result<()> {
+        let now = test_time()?;
+        let plan = |snoozed: HashMap<String, Zoned>| {
+            Vaccine::schedule(
+                &now,
+                ["Tdap".to_owned(), "Hepatitis B".to_owned()].into_iter(),
+                2030,
+                &[],
+                &ScheduleOptions {
+                    snoozed,
+                    ..ScheduleOptions::default()
+                },
+            )
+        };
+        let offsets = |appts: &[VaccineAppointment], name| {
+            appts
+                .iter()
+                .filter(|a| a.vaccine() == name)
+                .map(|a| a.month_offset(&now))
+                .collect_vec()
+        };
+        let before = plan(HashMap::new())?;
+
+        // Snoozed until mid-October: the series starts in October and keeps its spacing.
+        let until = Date::new(2025, 10, 15)?.to_zoned(now.time_zone().clone())?;
+        let snoozed = plan(HashMap::from([("Tdap".to_owned(), until.clone())]))?;
+        let tdap = offsets(&snoozed, "Tdap");
+        assert_eq!(4, tdap[0]);
+        assert_eq!(
+            offsets(&before, "Tdap")
+                .iter()
+                .map(|mo| mo + 4)
+                .take(tdap.len())
+                .collect_vec(),
+            tdap
+        );
+        assert_eq!(
+            offsets(&before, "Hepatitis B"),
+            offsets(&snoozed, "Hepatitis B")
+        );
+
+        // Once the date has passed, the snooze does nothing.
+        let past = now.checked_sub(Span::new().days(1))?;
+        assert_eq!(before, plan(HashMap::from([("Tdap".to_owned(), past)]))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_complete() -> Result<()> {
+        let now = test_time()?;
+        let vaccines = Vaccine::get_vaccines();
+        let record = |vaccine, kind, months_ago| -> Result<VaccineRecord> {
+            Ok(VaccineRecord::new(
+                vaccine,
+                now.checked_sub(Span::new().months(months_ago))?,
+                kind,
+                "",
+            ))
+        };
+        let hep_b = &vaccines["Hepatitis B"];
+        assert!(!hep_b.is_complete(&now, [].iter(), 24)?);
+        let hep_b_records = [record("Hepatitis B", DoseKind::Dose(0), 12)?];
+        assert!(hep_b.is_complete(&now, hep_b_records.iter(), 24)?);
+
+        let flu = &vaccines["Flu"];
+        let flu_records = [
+            record("Flu", DoseKind::Dose(0), 20)?,
+            record("Flu", DoseKind::Booster, 8)?,
+        ];
+        assert!(!flu.is_complete(&now, flu_records.iter(), 24)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_urgency() -> Result<()> {
+        let now = test_time()?;
+        let tdap = Vaccine::get_vaccines().get("Tdap").unwrap();
+        // The second dose was due 6 months after the first, a year ago.
+        let records = [VaccineRecord::new(
+            "Tdap",
+            now.checked_sub(Span::new().months(18))?,
+            DoseKind::Dose(0),
+            "",
+        )];
+        assert_eq!(12, tdap.months_overdue(&now, records.iter())?);
+        assert_eq!(0, tdap.months_overdue(&now, [].iter())?);
+
+        let appts = Vaccine::schedule(
+            &now,
+            ["Tdap".to_owned()].into_iter(),
+            2027,
+            &records,
+            &ScheduleOptions::default(),
+        )?;
+        let urgency =
+            |appt: &VaccineAppointment, grace_mo| appt.urgency(&now, 12, grace_mo, 1, false);
+        assert_eq!(Urgency::Overdue, urgency(&appts[0], 1));
+        assert_eq!(Urgency::DueSoon, urgency(&appts[0], 12));
+        assert_eq!(Urgency::DueThisYear, urgency(&appts[1], 1));
+        let next_year = VaccineAppointment::from_month_offset("Tdap", DoseKind::Booster, &now, 12);
+        assert_eq!(Urgency::Future, urgency(&next_year, 1));
+        assert_eq!(Urgency::Completed, appts[0].urgency(&now, 12, 1, 1, true));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reminder_lead_time() -> Result<()> {
+        let now = test_time()?;
+        let booster = VaccineAppointment::from_month_offset("Tdap", DoseKind::Booster, &now, 2);
+        assert_eq!(Urgency::DueSoon, booster.urgency(&now, 0, 1, 2, false));
+        assert_eq!(Urgency::DueThisYear, booster.urgency(&now, 0, 1, 1, false));
+        // With no lead time, only this month is due soon.
+        let this_month = VaccineAppointment::from_month_offset("Tdap", DoseKind::Booster, &now, 0);
+        assert_eq!(Urgency::DueSoon, this_month.urgency(&now, 0, 1, 0, false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimum_age() -> Result<()> {
+        let now = test_time()?;
+        let plan = |date_of_birth| {
+            let options = ScheduleOptions {
+                date_of_birth: Some(date_of_birth),
+                ..Default::default()
+            };
+            Vaccine::schedule(&now, ["Mpox".to_owned()].into_iter(), 2040, &[], &options)
+        };
+        // Mpox is for adults; a 16 year old waits two years and the booster follows.
+        let child = plan(Date::new(2009, 6, 15)?)?;
+        assert_eq!(
+            vec![
+                "2027-06 Mpox Dose#1",
+                "2027-07 Mpox Dose#2",
+                "2032-07 Mpox Booster",
+                "2037-07 Mpox Booster"
+            ],
+            summarize(&child)
+        );
+        // An adult is already eligible, so nothing changes.
+        let adult = plan(Date::new(1980, 1, 1)?)?;
+        assert_eq!(
+            Vaccine::schedule(
+                &now,
+                ["Mpox".to_owned()].into_iter(),
+                2040,
+                &[],
+                &ScheduleOptions::default()
+            )?,
+            adult
+        );
+        assert_eq!((2025, 6), (adult[0].year(), adult[0].month()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_booster_horizon() -> Result<()> {
+        let plan = |booster_horizon_years| {
+            let options = ScheduleOptions {
+                booster_horizon_years,
+                ..Default::default()
+            };
+            Vaccine::schedule(
+                &test_time()?,
+                ["Tdap".to_owned(), "Flu".to_owned()].into_iter(),
+                2060,
+                &[],
+                &options,
+            )
+        };
+        let boosters = |appts: &[VaccineAppointment], name: &str| {
+            appts
+                .iter()
+                .filter(|a| a.vaccine() == name && a.kind() == DoseKind::Booster)
+                .count()
+        };
+        let full = plan(None)?;
+        let limited = plan(Some(10))?;
+        assert_eq!(3, boosters(&full, "Tdap"));
+        assert_eq!(0, boosters(&limited, "Tdap"));
+        assert!(boosters(&limited, "Flu") < boosters(&full, "Flu"));
+        assert!(limited.iter().all(|a| a.year() <= 2035));
+        // The initial series is unaffected, and a horizon past the end year changes nothing.
+        assert_eq!(
+            full.iter()
+                .filter(|a| a.kind() != DoseKind::Booster)
+                .count(),
+            limited
+                .iter()
+                .filter(|a| a.kind() != DoseKind::Booster)
+                .count()
+        );
+        assert_eq!(full, plan(Some(50))?);
+        Ok(())
+    }
+
+    fn summarize(appts: &[VaccineAppointment]) -> Vec<String> {
+        appts
+            .iter()
+            .map(|a| format!("{}-{:02} {} {}", a.year(), a.month(), a.vaccine(), a.kind()))
+            .collect()
+    }
+
+    fn persona_schedule(
+        prio: &[&str],
+        end_plan_year: i16,
+        records: &[(&str, DoseKind, i64)],
+    ) -> Result<Vec<String>> {
+        let now = test_time()?;
+        let mut records = records
+            .iter()
+            .map(|(vaccine, kind, months_ago)| {
+                Ok(VaccineRecord::new(
+                    vaccine,
+                    now.checked_sub(Span::new().months(*months_ago))?,
+                    *kind,
+                    "",
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        records.sort();
+        Ok(summarize(&Vaccine::schedule(
+            &now,
+            prio.iter().map(|s| s.to_string()),
+            end_plan_year,
+            &records,
+            &ScheduleOptions::default(),
+        )?))
+    }
+
+    // Golden tests for the full schedule across a few representative people.
+    #[test]
+    fn test_persona_fresh_adult() -> Result<()> {
+        let prio = Vaccine::get_vaccines()
+            .values()
+            .filter(|v| v.recommended())
+            .map(|v| v.name())
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "2025-06 COVID-19 Dose#1",
+                "2025-06 Chickenpox Dose#1",
+                "2025-06 Flu Dose#1",
+                "2025-06 Gardasil-9 Dose#1",
+                "2025-06 Hepatitis A Dose#1",
+                "2025-06 Hepatitis B Dose#1",
+                "2025-06 IPV Dose#1",
+                "2025-06 MMR Dose#1",
+                "2025-06 Meningitis Dose#1",
+                "2025-06 Mpox Dose#1",
+                "2025-06 PCV20 Dose#1",
+                "2025-06 Shingrix Dose#1",
+                "2025-06 Tdap Dose#1",
+                "2025-07 COVID-19 Dose#2",
+                "2025-07 Mpox Dose#2",
+                "2025-08 Chickenpox Dose#2",
+                "2025-08 Shingrix Dose#2",
+                "2025-10 IPV Dose#2",
+                "2025-12 Gardasil-9 Dose#2",
+                "2025-12 Hepatitis A Dose#2",
+                "2025-12 Meningitis Dose#2",
+                "2025-12 PCV20 Dose#2",
+                "2025-12 Tdap Dose#2",
+                "2026-02 IPV Dose#3",
+                "2026-06 Gardasil-9 Dose#3",
+                "2026-06 IPV Dose#4",
+                "2026-06 Tdap Dose#3",
+                "2026-09 COVID-19 Booster",
+                "2026-09 Flu Booster",
+                "2027-09 COVID-19 Booster",
+                "2027-09 Flu Booster",
+                "2028-09 COVID-19 Booster",
+                "2028-09 Flu Booster",
+                "2029-09 COVID-19 Booster",
+                "2029-09 Flu Booster",
+                "2030-06 MMR Dose#2",
+            ],
+            persona_schedule(&prio, 2030, &[])?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_persona_partial_traveler() -> Result<()> {
+        // Part way through several series, with a dose taken today.
+        let plan = persona_schedule(
+            &["Tdap", "Hepatitis A", "Mpox", "Meningitis"],
+            2032,
+            &[
+                ("Hepatitis A", DoseKind::Dose(0), 8),
+                ("Tdap", DoseKind::Dose(0), 3),
+                ("Mpox", DoseKind::Dose(0), 0),
+            ],
+        )?;
+        assert_eq!(
+            vec![
+                "2025-06 Hepatitis A Dose#2",
+                "2025-06 Meningitis Dose#1",
+                "2025-07 Mpox Dose#2",
+                "2025-09 Tdap Dose#2",
+                "2025-12 Meningitis Dose#2",
+                "2026-03 Tdap Dose#3",
+                "2030-07 Mpox Booster",
+                "2030-12 Meningitis Booster",
+            ],
+            plan
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_persona_up_to_date_senior() -> Result<()> {
+        let plan = persona_schedule(
+            &["Flu", "COVID-19", "Shingrix", "PCV20", "Tdap"],
+            2030,
+            &[
+                ("Tdap", DoseKind::Dose(0), 600),
+                ("Tdap", DoseKind::Dose(1), 594),
+                ("Tdap", DoseKind::Dose(2), 588),
+                ("Tdap", DoseKind::Booster, 24),
+                ("PCV20", DoseKind::Dose(0), 30),
+                ("PCV20", DoseKind::Dose(1), 24),
+                ("Shingrix", DoseKind::Dose(0), 20),
+                ("Shingrix", DoseKind::Dose(1), 16),
+                ("COVID-19", DoseKind::Dose(0), 40),
+                ("COVID-19", DoseKind::Dose(1), 38),
+                ("COVID-19", DoseKind::Booster, 8),
+                ("Flu", DoseKind::Dose(0), 20),
+                ("Flu", DoseKind::Booster, 8),
+            ],
+        )?;
+        // Everything is complete; only the seasonal boosters remain, in October a year on from
+        // the last ones.
+        assert_eq!(
+            vec![
+                "2025-10 Flu Booster",
+                "2025-10 COVID-19 Booster",
+                "2026-10 Flu Booster",
+                "2026-10 COVID-19 Booster",
+                "2027-10 Flu Booster",
+                "2027-10 COVID-19 Booster",
+                "2028-10 Flu Booster",
+                "2028-10 COVID-19 Booster",
+                "2029-10 Flu Booster",
+                "2029-10 COVID-19 Booster",
+            ],
+            plan
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_space_visits() {
+        let mut doses = vec![
+            ("Mpox", DoseKind::Dose(0), 0),
+            ("Mpox", DoseKind::Dose(1), 1),
+            ("Flu", DoseKind::Dose(0), 2),
+            ("Mpox", DoseKind::Booster, 61),
+        ];
+        space_visits(&mut doses, 3);
+        assert_eq!(
+            vec![
+                ("Mpox", DoseKind::Dose(0), 0),
+                ("Flu", DoseKind::Dose(0), 3),
+                ("Mpox", DoseKind::Dose(1), 3),
+                ("Mpox", DoseKind::Booster, 63),
+            ],
+            doses
+        );
+    }
+
+    #[test]
+    fn test_schedule_minimum_gap() -> Result<()> {
+        let prio = || ["Mpox".to_owned(), "COVID-19".to_owned()].into_iter();
+        let visits = |appts: Vec<VaccineAppointment>| {
+            appts
+                .iter()
+                .map(|a| (a.year(), a.month()))
+                .dedup()
+                .collect::<Vec<_>>()
+        };
+        // Without a gap the second doses of both land the month after the first.
+        let mut options = ScheduleOptions::default();
+        let appts = Vaccine::schedule(&test_time()?, prio(), 2027, &[], &options)?;
+        assert_eq!(vec![(2025, 6), (2025, 7)], visits(appts)[..2]);
+        // With a gap of 3 months, the second visit is pushed out together.
+        options.min_gap_mo = 3;
+        let appts = Vaccine::schedule(&test_time()?, prio(), 2027, &[], &options)?;
+        assert_eq!(vec![(2025, 6), (2025, 9)], visits(appts)[..2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_coadministration_compatible() {
+        let mut doses = vec![
+            ("Tdap", DoseKind::Dose(0), 0),
+            ("Flu", DoseKind::Dose(0), 0),
+            ("MMR", DoseKind::Dose(0), 0),
+            ("Chickenpox", DoseKind::Dose(0), 0),
+        ];
+        let expect = doses.clone();
+        separate_conflicts(&mut doses, &CoAdministration::default(), false);
+        assert_eq!(expect, doses);
+    }
+
+    #[test]
+    fn test_coadministration_conflict() {
+        let mut coadmin = CoAdministration::default();
+        coadmin.separate("Flu", "Tdap");
+        assert!(!coadmin.allowed("Tdap", "Flu"));
+        let mut doses = vec![
+            ("Tdap", DoseKind::Dose(0), 0),
+            ("Flu", DoseKind::Dose(0), 0),
+            ("MMR", DoseKind::Dose(0), 0),
+            ("Tdap", DoseKind::Dose(1), 6),
+            ("Flu", DoseKind::Booster, 8),
+        ];
+        separate_conflicts(&mut doses, &coadmin, false);
+        assert_eq!(
+            vec![
+                ("Tdap", DoseKind::Dose(0), 0),
+                ("MMR", DoseKind::Dose(0), 0),
+                ("Flu", DoseKind::Dose(0), 1),
+                ("Tdap", DoseKind::Dose(1), 6),
+                ("Flu", DoseKind::Booster, 9),
+            ],
+            doses
+        );
+    }
+
+    #[test]
+    fn test_spread_within_month() -> Result<()> {
+        let mut coadmin = CoAdministration::default();
+        for (a, b) in [
+            ("Tdap", "Mpox"),
+            ("Tdap", "Hepatitis B"),
+            ("Mpox", "Hepatitis B"),
+        ] {
+            coadmin.separate(a, b);
+        }
+        let mut doses = vec![
+            ("Tdap", DoseKind::Dose(0), 0),
+            ("Mpox", DoseKind::Dose(0), 0),
+            ("Hepatitis B", DoseKind::Dose(0), 0),
+        ];
+        let weeks = separate_conflicts(&mut doses, &coadmin, true);
+        assert!(doses.iter().all(|(_, _, mo)| *mo == 0));
+        assert_eq!(
+            vec![0, 1, 2],
+            doses
+                .iter()
+                .map(|(name, _, mo)| weeks.get(&(*name, *mo)).copied().unwrap_or(0))
+                .collect_vec()
+        );
+
+        // Live vaccines can't be a week apart, so they still go to another month.
+        let mut coadmin = CoAdministration::default();
+        coadmin.separate("MMR", "Chickenpox");
+        let mut doses = vec![
+            ("MMR", DoseKind::Dose(0), 0),
+            ("Chickenpox", DoseKind::Dose(0), 0),
+        ];
+        assert!(separate_conflicts(&mut doses, &coadmin, true).is_empty());
+        assert_eq!(("Chickenpox", DoseKind::Dose(0), 2), doses[1]);
+
+        // The schedule puts each on the first day of its week.
+        let now = test_time()?;
+        let mut coadmin = CoAdministration::default();
+        for (a, b) in [
+            ("Tdap", "Mpox"),
+            ("Tdap", "Hepatitis B"),
+            ("Mpox", "Hepatitis B"),
+        ] {
+            coadmin.separate(a, b);
+        }
+        let options = ScheduleOptions {
+            coadministration: coadmin,
+            spread_within_month: true,
+            ..ScheduleOptions::default()
+        };
+        let prio = ["Tdap", "Mpox", "Hepatitis B"].map(|v| v.to_owned());
+        let plan = Vaccine::schedule(&now, prio.into_iter(), 2026, &[], &options)?;
+        assert_eq!(
+            vec![
+                ("Tdap", Some(1)),
+                ("Mpox", Some(8)),
+                ("Hepatitis B", Some(15))
+            ],
+            plan.iter()
+                .filter(|appt| appt.month_offset(&now) == 0)
+                .map(|appt| (appt.vaccine(), appt.day()))
+                .collect_vec()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_live_vaccine_spacing() {
+        let mut doses = vec![
+            ("MMR", DoseKind::Dose(0), 0),
+            ("Chickenpox", DoseKind::Dose(0), 1),
+            ("Chickenpox", DoseKind::Dose(1), 2),
+        ];
+        separate_conflicts(&mut doses, &CoAdministration::default(), false);
+        assert_eq!(
+            vec![
+                ("MMR", DoseKind::Dose(0), 0),
+                ("Chickenpox", DoseKind::Dose(0), 2),
+                ("Chickenpox", DoseKind::Dose(1), 3),
+            ],
+            doses
+        );
+    }
 }
 
 // pub struct ReceivedDose {
@@ -1,29 +1,159 @@
 use anyhow::Result;
-use std::fs;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+};
 
-pub fn download_file(data: &str, filename: &str, _mime_type: &str) -> Result<()> {
+// Saves still being written in the background. Each reports once, with an error message if the
+// write failed.
+static PENDING_SAVES: Mutex<Vec<Receiver<Result<(), String>>>> = Mutex::new(Vec::new());
+
+pub fn download_file(data: impl AsRef<[u8]>, filename: &str, _mime_type: &str) -> Result<()> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("ron");
     let filename = rfd::FileDialog::default()
         .set_title("Save Records")
         .set_file_name(filename)
-        .add_filter("RON Files", &["ron", "RON"])
-        .pick_file();
+        .add_filter(
+            format!("{} Files", extension.to_uppercase()),
+            &[extension.to_lowercase(), extension.to_uppercase()],
+        )
+        .save_file();
+    // The dialog has to run here, but the write can be slow for big files, so don't hold up the
+    // UI for it.
     if let Some(name) = filename {
-        fs::write(name, data)?;
+        let save = write_in_background(name, data.as_ref().to_vec());
+        PENDING_SAVES.lock().unwrap().push(save);
     }
     Ok(())
 }
 
-pub fn create_file_picker<F>(callback: F) -> Result<()>
+// Save several files at once, asking for the folder to put them in only once.
+pub fn download_files(files: &[(String, Vec<u8>)], mime_type: &str) -> Result<()> {
+    if let [(filename, data)] = files {
+        return download_file(data, filename, mime_type);
+    }
+    if let Some(folder) = rfd::FileDialog::default()
+        .set_title("Save Records To Folder")
+        .pick_folder()
+    {
+        let mut pending = PENDING_SAVES.lock().unwrap();
+        for (filename, data) in files {
+            pending.push(write_in_background(folder.join(filename), data.clone()));
+        }
+    }
+    Ok(())
+}
+
+fn write_in_background(
+    path: PathBuf,
+    data: impl AsRef<[u8]> + Send + 'static,
+) -> Receiver<Result<(), String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result =
+            fs::write(&path, data).map_err(|e| format!("Could not save {}: {e}", path.display()));
+        sender.send(result).ok();
+    });
+    receiver
+}
+
+// Check on the background saves, once per frame. Returns whether any are still being written,
+// and the errors from those that finished since the last call.
+pub fn poll_saves() -> (bool, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut pending = PENDING_SAVES.lock().unwrap();
+    pending.retain(|save| match save.try_recv() {
+        Ok(result) => {
+            errors.extend(result.err());
+            false
+        }
+        Err(TryRecvError::Empty) => true,
+        Err(TryRecvError::Disconnected) => {
+            errors.push("A save stopped without finishing.".to_owned());
+            false
+        }
+    });
+    (!pending.is_empty(), errors)
+}
+
+pub fn create_file_picker<F>(extensions: &[&str], callback: F) -> Result<()>
 where
     F: Fn(String) + 'static,
 {
-    let filename = rfd::FileDialog::default()
-        .set_title("Load Records")
-        .add_filter("RON Files", &["ron", "RON"])
-        .pick_file();
-    if let Some(name) = filename {
+    let mut dialog = rfd::FileDialog::default().set_title("Load Records");
+    for extension in extensions {
+        dialog = dialog.add_filter(
+            format!("{} Files", extension.to_uppercase()),
+            &[extension.to_lowercase(), extension.to_uppercase()],
+        );
+    }
+    if let Some(name) = dialog.pick_file() {
         let data = fs::read_to_string(name)?;
         callback(data);
     }
     Ok(())
 }
+
+// There is no page URL on native, so share links are neither read nor offered.
+pub fn location_hash() -> Option<String> {
+    None
+}
+
+pub fn clear_location_hash() {}
+
+pub fn share_url(_fragment: &str) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    // Poll like the UI does until the saves in flight are done.
+    fn wait_for_saves() -> Vec<String> {
+        let start = Instant::now();
+        let mut errors = Vec::new();
+        loop {
+            let (saving, finished) = poll_saves();
+            errors.extend(finished);
+            if !saving {
+                return errors;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "save never finished"
+            );
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_background_save() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("vaccine_helper_save_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("records.ron");
+        let data = "(records: [])".repeat(10_000);
+        let missing = dir.join("missing").join("records.ron");
+        PENDING_SAVES.lock().unwrap().extend([
+            write_in_background(path.clone(), data.clone()),
+            write_in_background(missing, data.clone()),
+        ]);
+
+        let errors = wait_for_saves();
+        assert_eq!(data, fs::read_to_string(&path)?);
+        assert_eq!(1, errors.len());
+        assert!(errors[0].starts_with("Could not save"));
+        assert_eq!((false, Vec::<String>::new()), poll_saves());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -6,12 +6,21 @@ use web_sys::{
     Url,
 };
 
-pub fn download_file(data: &str, filename: &str, mime_type: &str) -> Result<()> {
-    download_file_inner(data, filename, mime_type).map_err(|_| anyhow!("a js error occurred"))
+pub fn download_file(data: impl AsRef<[u8]>, filename: &str, mime_type: &str) -> Result<()> {
+    download_file_inner(data.as_ref(), filename, mime_type)
+        .map_err(|_| anyhow!("a js error occurred"))
+}
+
+// The browser saves each download without asking, so there is nothing to gain by bundling them.
+pub fn download_files(files: &[(String, Vec<u8>)], mime_type: &str) -> Result<()> {
+    for (filename, data) in files {
+        download_file(data, filename, mime_type)?;
+    }
+    Ok(())
 }
 
 pub fn download_file_inner(
-    data: &str,
+    data: &[u8],
     filename: &str,
     mime_type: &str,
 ) -> std::result::Result<(), JsValue> {
@@ -20,12 +29,12 @@ pub fn download_file_inner(
 
     // Create blob
     let blob_parts = js_sys::Array::new();
-    blob_parts.push(&JsValue::from_str(data));
+    blob_parts.push(&js_sys::Uint8Array::from(data));
 
     let blob_props = BlobPropertyBag::new();
     blob_props.set_type(mime_type);
 
-    let blob = Blob::new_with_str_sequence_and_options(&blob_parts, &blob_props)?;
+    let blob = Blob::new_with_u8_array_sequence_and_options(&blob_parts, &blob_props)?;
 
     // Create URL and download
     let url = Url::create_object_url_with_blob(&blob)?;
@@ -39,14 +48,20 @@ pub fn download_file_inner(
     Ok(())
 }
 
-pub fn create_file_picker<F>(callback: F) -> Result<()>
+// Downloads are handed to the browser as soon as the blob is made, so there is never anything
+// left to wait for.
+pub fn poll_saves() -> (bool, Vec<String>) {
+    (false, Vec::new())
+}
+
+pub fn create_file_picker<F>(extensions: &[&str], callback: F) -> Result<()>
 where
     F: Fn(String) + 'static,
 {
-    create_file_picker_inner(callback).map_err(|_| anyhow!("a js error occurred"))
+    create_file_picker_inner(extensions, callback).map_err(|_| anyhow!("a js error occurred"))
 }
 
-fn create_file_picker_inner<F>(callback: F) -> std::result::Result<(), JsValue>
+fn create_file_picker_inner<F>(extensions: &[&str], callback: F) -> std::result::Result<(), JsValue>
 where
     F: Fn(String) + 'static,
 {
@@ -56,7 +71,12 @@ where
     // Create file input
     let input: HtmlInputElement = document.create_element("input")?.dyn_into()?;
     input.set_type("file");
-    input.set_accept(".ron"); // optional: limit file types
+    let accept = extensions
+        .iter()
+        .map(|ext| format!(".{ext}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    input.set_accept(&accept); // optional: limit file types
 
     // Handle file selection
     let callback = Rc::new(callback);
@@ -97,3 +117,25 @@ where
 
     reader.read_as_text(&file).unwrap();
 }
+
+// The `#...` part of the page URL, if there is one.
+pub fn location_hash() -> Option<String> {
+    let hash = window()?.location().hash().ok()?;
+    (!hash.is_empty()).then_some(hash)
+}
+
+pub fn clear_location_hash() {
+    if let Some(window) = window() {
+        window.location().set_hash("").ok();
+    }
+}
+
+// A link to this page that carries the given fragment.
+pub fn share_url(fragment: &str) -> Option<String> {
+    let location = window()?.location();
+    Some(format!(
+        "{}{}#{fragment}",
+        location.origin().ok()?,
+        location.pathname().ok()?
+    ))
+}
@@ -0,0 +1,70 @@
+use crate::schedule::VaccineRecord;
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+// Links longer than this get mangled by browsers and chat apps, so refuse to make or read them.
+pub const MAX_FRAGMENT_LEN: usize = 8 * 1024;
+
+// Cap the decompressed size so that a crafted link can't exhaust memory.
+const MAX_DECODED_BYTES: usize = 1024 * 1024;
+
+const PREFIX: &str = "records=";
+
+// Encode the records into a URL fragment: RON, deflated, then URL-safe base64.
+pub fn encode_records(records: &[VaccineRecord]) -> Result<String> {
+    let data = ron::ser::to_string(records)?;
+    let compressed = miniz_oxide::deflate::compress_to_vec(data.as_bytes(), 9);
+    let fragment = format!("{PREFIX}{}", URL_SAFE_NO_PAD.encode(compressed));
+    if fragment.len() > MAX_FRAGMENT_LEN {
+        bail!("too many records to share as a link");
+    }
+    Ok(fragment)
+}
+
+// The inverse of `encode_records`. Accepts the fragment with or without its leading '#'.
+pub fn decode_records(fragment: &str) -> Result<Vec<VaccineRecord>> {
+    let fragment = fragment.strip_prefix('#').unwrap_or(fragment);
+    if fragment.len() > MAX_FRAGMENT_LEN {
+        bail!("shared link is too long");
+    }
+    let Some(encoded) = fragment.strip_prefix(PREFIX) else {
+        bail!("not a shared records link");
+    };
+    let compressed = URL_SAFE_NO_PAD.decode(encoded)?;
+    let data = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_DECODED_BYTES)
+        .map_err(|e| anyhow!("failed to decompress shared records: {e}"))?;
+    let mut records: Vec<VaccineRecord> = ron::de::from_bytes(&data)?;
+    records.sort();
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::DoseKind;
+    use jiff::{civil::Date, tz::TimeZone};
+
+    #[test]
+    fn test_share_round_trips() -> Result<()> {
+        let date = Date::new(2024, 3, 1)?.to_zoned(TimeZone::get("America/Los_Angeles")?)?;
+        let records = vec![
+            VaccineRecord::new("Tdap", date.clone(), DoseKind::Dose(0), "left arm"),
+            VaccineRecord::new("Flu", date, DoseKind::Booster, ""),
+        ];
+        let fragment = encode_records(&records)?;
+        assert!(fragment.starts_with(PREFIX));
+        let mut expect = records.clone();
+        expect.sort();
+        assert_eq!(expect, decode_records(&format!("#{fragment}"))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_rejects_bad_fragments() {
+        assert!(decode_records("").is_err());
+        assert!(decode_records("#section-2").is_err());
+        assert!(decode_records("#records=not*base64").is_err());
+        assert!(decode_records(&format!("#records={}", URL_SAFE_NO_PAD.encode("junk"))).is_err());
+        assert!(decode_records(&format!("#{PREFIX}{}", "A".repeat(MAX_FRAGMENT_LEN))).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use crate::schedule::VaccineAppointment;
+use itertools::Itertools;
+
+// A short plain-text plan for pasting into messages: one line per month with everything due
+// that month, e.g. `2026-03: COVID-19 Booster, Flu Booster`. Appointments are expected in
+// schedule order. Only appointments that `keep` accepts are listed. An empty plan gives an
+// empty string.
+pub fn plan_to_text(
+    appointments: &[VaccineAppointment],
+    keep: impl Fn(&VaccineAppointment) -> bool,
+) -> String {
+    let mut out = String::new();
+    for (_, appts) in &appointments
+        .iter()
+        .filter(|appt| keep(appt))
+        .chunk_by(|appt| (appt.year(), appt.month()))
+    {
+        let appts = appts.collect_vec();
+        let when = match appts[0].day() {
+            Some(_) => appts[0].date().strftime("%Y-%m-%d"),
+            None => appts[0].date().strftime("%Y-%m"),
+        };
+        let what = appts
+            .iter()
+            .map(|appt| format!("{} {}", appt.vaccine(), appt.kind()))
+            .join(", ");
+        out.push_str(&format!("{when}: {what}\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::{ScheduleOptions, Vaccine};
+    use anyhow::Result;
+    use jiff::{civil::Date, tz::TimeZone};
+
+    #[test]
+    fn test_plan_to_text() -> Result<()> {
+        assert_eq!("", plan_to_text(&[], |_| true));
+
+        let now = Date::new(2025, 6, 1)?.to_zoned(TimeZone::UTC)?;
+        let appts = Vaccine::schedule(
+            &now,
+            ["Hepatitis A&B".to_owned(), "MMR".to_owned()].into_iter(),
+            2026,
+            &[],
+            &ScheduleOptions::default(),
+        )?;
+        assert_eq!(
+            "2025-06: Hepatitis A&B Dose#1, MMR Dose#1\n\
+             2025-12: Hepatitis A&B Dose#2\n\
+             2026-06: Hepatitis A&B Dose#3\n",
+            plan_to_text(&appts, |_| true)
+        );
+
+        let options = ScheduleOptions {
+            preferred_day_of_month: Some(15),
+            ..ScheduleOptions::default()
+        };
+        let appts = Vaccine::schedule(&now, ["Tdap".to_owned()].into_iter(), 2026, &[], &options)?;
+        assert!(plan_to_text(&appts, |_| true).starts_with("2025-06-15: Tdap Dose#1\n"));
+        Ok(())
+    }
+}
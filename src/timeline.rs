@@ -0,0 +1,256 @@
+use crate::schedule::{Category, DoseKind, DoseLabels, Vaccine, VaccineAppointment};
+use egui::{pos2, vec2, Align2, Color32, FontId, Rect, Sense, Stroke};
+use jiff::Zoned;
+
+const ROW_HEIGHT: f32 = 22.0;
+const AXIS_HEIGHT: f32 = 18.0;
+const NAME_WIDTH: f32 = 150.0;
+const MARKER_RADIUS: f32 = 5.0;
+
+// One row per vaccine, in the order given, with the month offset of each of its appointments.
+// Vaccines with nothing planned still get an (empty) row, so that the rows don't shift around
+// as the plan changes.
+pub fn timeline_rows<'a>(
+    now: &Zoned,
+    vaccines: &[&'a str],
+    schedule: &'a [VaccineAppointment],
+) -> Vec<(&'a str, Vec<(i16, &'a VaccineAppointment)>)> {
+    vaccines
+        .iter()
+        .map(|name| {
+            let appts = schedule
+                .iter()
+                .filter(|appt| appt.vaccine() == *name)
+                .map(|appt| (appt.month_offset(now), appt))
+                .collect();
+            (*name, appts)
+        })
+        .collect()
+}
+
+// Draw the plan as a horizontal timeline from now until the start of `end_plan_year`, with one
+// row per vaccine and a marker for each appointment. Long plans scroll sideways; `month_width`
+// is the zoom, in points per month.
+pub fn show_timeline(
+    ui: &mut egui::Ui,
+    now: &Zoned,
+    end_plan_year: i16,
+    vaccines: &[&str],
+    schedule: &[VaccineAppointment],
+    labels: &DoseLabels,
+    month_width: &mut f32,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Zoom:");
+        ui.add(egui::Slider::new(month_width, 2.0..=40.0).suffix(" pt/month"));
+    });
+    show_category_legend(ui);
+
+    let rows = timeline_rows(now, vaccines, schedule);
+    let span_mo = ((end_plan_year - now.year()) * 12 - now.month() as i16 + 1).max(1);
+    let height = AXIS_HEIGHT + rows.len() as f32 * ROW_HEIGHT;
+    let text_color = ui.visuals().text_color();
+    let grid_color = ui.visuals().weak_text_color();
+    let stripe_color = ui.visuals().faint_bg_color;
+    let font = FontId::proportional(12.0);
+
+    ui.horizontal_top(|ui| {
+        // The names stay put while the chart scrolls.
+        let (names, _) = ui.allocate_exact_size(vec2(NAME_WIDTH, height), Sense::hover());
+        let painter = ui.painter_at(names);
+        for (i, (name, _)) in rows.iter().enumerate() {
+            let y = names.top() + AXIS_HEIGHT + (i as f32 + 0.5) * ROW_HEIGHT;
+            painter.text(
+                pos2(names.left(), y),
+                Align2::LEFT_CENTER,
+                *name,
+                font.clone(),
+                text_color,
+            );
+        }
+
+        egui::ScrollArea::horizontal()
+            .id_salt("timeline_scroll")
+            .show(ui, |ui| {
+                let width = span_mo as f32 * *month_width + 2.0 * MARKER_RADIUS;
+                let (chart, _) = ui.allocate_exact_size(vec2(width, height), Sense::hover());
+                let painter = ui.painter_at(chart);
+                let x_of = |mo: i16| chart.left() + MARKER_RADIUS + mo as f32 * *month_width;
+
+                for i in (0..rows.len()).step_by(2) {
+                    let top = chart.top() + AXIS_HEIGHT + i as f32 * ROW_HEIGHT;
+                    painter.rect_filled(
+                        Rect::from_min_size(pos2(chart.left(), top), vec2(width, ROW_HEIGHT)),
+                        0.0,
+                        stripe_color,
+                    );
+                }
+
+                // A tick at each new year, labelled when there is room for it.
+                let label_every = if *month_width * 12.0 < 40.0 { 5 } else { 1 };
+                for mo in 0..=span_mo {
+                    let (year, month) = VaccineAppointment::mo_to_ym(now, mo);
+                    if month != 1 && mo != 0 {
+                        continue;
+                    }
+                    let x = x_of(mo);
+                    painter.line_segment(
+                        [pos2(x, chart.top() + AXIS_HEIGHT), pos2(x, chart.bottom())],
+                        Stroke::new(1.0, grid_color),
+                    );
+                    if year % label_every == 0 || mo == 0 {
+                        painter.text(
+                            pos2(x + 2.0, chart.top()),
+                            Align2::LEFT_TOP,
+                            year.to_string(),
+                            font.clone(),
+                            grid_color,
+                        );
+                    }
+                }
+
+                for (i, (name, appts)) in rows.iter().enumerate() {
+                    let fill = vaccine_color(ui.visuals(), name);
+                    let y = chart.top() + AXIS_HEIGHT + (i as f32 + 0.5) * ROW_HEIGHT;
+                    for (mo, appt) in appts {
+                        let center = pos2(x_of(*mo), y);
+                        // Doses are filled in, boosters are drawn as rings, and titer checks,
+                        // which aren't shots, as small dots.
+                        match appt.kind() {
+                            DoseKind::Dose(_) => {
+                                painter.circle_filled(center, MARKER_RADIUS, fill);
+                            }
+                            DoseKind::Booster => {
+                                painter.circle_stroke(
+                                    center,
+                                    MARKER_RADIUS - 1.0,
+                                    Stroke::new(2.0, fill),
+                                );
+                            }
+                            DoseKind::TiterCheck => {
+                                painter.circle_filled(center, MARKER_RADIUS / 2.0, grid_color);
+                            }
+                        }
+                        let hit = Rect::from_center_size(center, vec2(2.0, 2.0) * MARKER_RADIUS);
+                        ui.interact(
+                            hit,
+                            ui.id().with(("timeline_marker", *name, appt.kind(), *mo)),
+                            Sense::hover(),
+                        )
+                        .on_hover_text(format!(
+                            "{name} {} — {}",
+                            appt.kind().label(labels),
+                            appt.date().strftime("%B %Y")
+                        ));
+                    }
+                }
+                if rows.is_empty() {
+                    painter.text(
+                        chart.left_top() + vec2(0.0, AXIS_HEIGHT),
+                        Align2::LEFT_TOP,
+                        "No vaccines selected.",
+                        font.clone(),
+                        Color32::GRAY,
+                    );
+                }
+            });
+    });
+}
+
+// A color per vaccine category, for the vaccine list, the schedule, and the timeline. Lighter
+// shades for dark mode and darker ones for light mode, so that they stand out from the
+// background either way. Reds and oranges are left for overdue and due-soon highlighting.
+pub fn category_color(visuals: &egui::Visuals, category: Category) -> Color32 {
+    match (category, visuals.dark_mode) {
+        (Category::Routine, true) => Color32::from_rgb(100, 160, 255),
+        (Category::Routine, false) => Color32::from_rgb(30, 90, 200),
+        (Category::Travel, true) => Color32::from_rgb(90, 200, 120),
+        (Category::Travel, false) => Color32::from_rgb(20, 130, 60),
+        (Category::AtRisk, true) => Color32::from_rgb(220, 140, 255),
+        (Category::AtRisk, false) => Color32::from_rgb(140, 50, 180),
+    }
+}
+
+// The color of the named vaccine's category.
+pub fn vaccine_color(visuals: &egui::Visuals, name: &str) -> Color32 {
+    let category = Vaccine::get_vaccines()
+        .get(name)
+        .map_or(Category::default(), |v| v.category());
+    category_color(visuals, category)
+}
+
+// A dot in the category's color, to put in front of a vaccine's name.
+pub fn category_dot(visuals: &egui::Visuals, category: Category) -> egui::RichText {
+    egui::RichText::new("●").color(category_color(visuals, category))
+}
+
+pub fn show_category_legend(ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        for category in Category::all() {
+            ui.label(category_dot(ui.visuals(), category));
+            ui.label(category.to_string());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::{ScheduleOptions, Vaccine};
+    use anyhow::Result;
+    use jiff::{civil::Date, tz::TimeZone};
+
+    #[test]
+    fn test_timeline_rows() -> Result<()> {
+        let now = Date::new(2025, 6, 1)?.to_zoned(TimeZone::UTC)?;
+        let schedule = Vaccine::schedule(
+            &now,
+            ["Tdap".to_owned(), "Mpox".to_owned()].into_iter(),
+            2027,
+            &[],
+            &ScheduleOptions::default(),
+        )?;
+        let rows = timeline_rows(&now, &["Mpox", "Tdap", "Flu"], &schedule);
+        assert_eq!(
+            vec!["Mpox", "Tdap", "Flu"],
+            rows.iter().map(|(name, _)| *name).collect::<Vec<_>>()
+        );
+        let months = |row: usize| rows[row].1.iter().map(|(mo, _)| *mo).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1], months(0));
+        assert_eq!(vec![0, 6, 12], months(1));
+        assert!(rows[2].1.is_empty());
+        Ok(())
+    }
+
+    // WCAG contrast ratio between two colors.
+    fn contrast(a: Color32, b: Color32) -> f32 {
+        let luminance = |c: Color32| {
+            let c = egui::Rgba::from(c);
+            0.2126 * c.r() + 0.7152 * c.g() + 0.0722 * c.b()
+        };
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    #[test]
+    fn test_category_colors() {
+        for visuals in [egui::Visuals::dark(), egui::Visuals::light()] {
+            let colors = Category::all().map(|c| category_color(&visuals, c));
+            for (i, a) in colors.iter().enumerate() {
+                assert!(contrast(*a, visuals.panel_fill) >= 3.0, "{a:?}");
+                for b in &colors[i + 1..] {
+                    assert_ne!(a, b);
+                }
+            }
+        }
+        let dark = egui::Visuals::dark();
+        assert_eq!(
+            category_color(&dark, Category::Travel),
+            vaccine_color(&dark, "Rabies")
+        );
+        assert_eq!(
+            category_color(&dark, Category::Routine),
+            vaccine_color(&dark, "Not a vaccine")
+        );
+    }
+}
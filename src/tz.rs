@@ -0,0 +1,101 @@
+use anyhow::Result;
+use jiff::{
+    fmt::temporal::{Pieces, PiecesOffset},
+    tz::{Offset, TimeZone},
+    Timestamp, Zoned,
+};
+use serde::{Deserialize, Deserializer};
+use std::sync::Mutex;
+
+// Set when a time zone could not be found, so that the app can say why times may be off.
+static FALLBACK_WARNING: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn fallback_warning() -> Option<String> {
+    FALLBACK_WARNING.lock().unwrap().clone()
+}
+
+fn warn(message: String) {
+    log::warn!("{message}");
+    FALLBACK_WARNING.lock().unwrap().get_or_insert(message);
+}
+
+// Use the looked-up zone, or UTC if the lookup failed, e.g. because the tzdb is missing or the
+// system zone is misconfigured.
+fn zone_or_utc(lookup: Result<TimeZone, jiff::Error>, what: &str) -> TimeZone {
+    lookup.unwrap_or_else(|e| {
+        warn(format!(
+            "Could not load the {what} time zone ({e}); using UTC."
+        ));
+        TimeZone::UTC
+    })
+}
+
+pub fn system() -> TimeZone {
+    zone_or_utc(TimeZone::try_system(), "system")
+}
+
+// A zone by IANA name, e.g. "America/Denver", or UTC if it can't be found.
+pub fn named(name: &str) -> TimeZone {
+    zone_or_utc(TimeZone::get(name), name)
+}
+
+pub fn now() -> Zoned {
+    Timestamp::now().to_zoned(system())
+}
+
+// Parse a stored time. If its zone is unknown here, keep the wall clock time and UTC offset it
+// was saved with rather than failing to load it.
+pub fn parse_zoned(s: &str) -> Result<Zoned> {
+    let err = match s.parse::<Zoned>() {
+        Ok(zoned) => return Ok(zoned),
+        Err(err) => err,
+    };
+    let pieces = Pieces::parse(s).map_err(|_| err.clone())?;
+    let offset = match pieces.offset() {
+        Some(PiecesOffset::Numeric(offset)) => offset.offset(),
+        _ => Offset::UTC,
+    };
+    let zoned = pieces
+        .date()
+        .to_datetime(pieces.time().unwrap_or_default())
+        .to_zoned(TimeZone::fixed(offset))?;
+    warn(format!(
+        "Could not load a saved time zone ({err}); using its UTC offset."
+    ));
+    Ok(zoned)
+}
+
+// For `#[serde(deserialize_with)]` on stored times.
+pub fn deserialize_zoned<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Zoned, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_zoned(&s).map_err(serde::de::Error::custom)
+}
+
+pub fn deserialize_option_zoned<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Zoned>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| parse_zoned(&s).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_zone_falls_back() -> Result<()> {
+        let zone = zone_or_utc(TimeZone::get("Not/AZone"), "saved");
+        assert_eq!(TimeZone::UTC, zone);
+        assert!(fallback_warning().is_some_and(|w| w.contains("using UTC")));
+
+        let zoned = parse_zoned("2024-03-01T00:00:00+09:00[Not/AZone]")?;
+        assert_eq!("2024-03-01", zoned.date().to_string());
+        assert_eq!(Offset::constant(9), zoned.offset());
+
+        let known = parse_zoned("2024-03-01T00:00:00+00:00[UTC]")?;
+        assert_eq!(TimeZone::UTC, *known.time_zone());
+        assert!(parse_zoned("not a date").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use jiff::{civil::Date, tz::TimeZone};
+use vaccine_helper::cli::{self, Format};
+
+const PROFILE: &str = include_str!("fixtures/profile.ron");
+
+#[test]
+fn test_plan_fixture_profile() -> Result<()> {
+    let now = Date::new(2025, 6, 1)?.to_zoned(TimeZone::UTC)?;
+    let text = cli::plan_from_ron(PROFILE, &now, Format::Text)?;
+    assert_eq!(
+        "2025-06: Mpox Dose#1\n2025-07: Mpox Dose#2\n2025-09: Tdap Dose#2\n2026-03: Tdap Dose#3\n",
+        text
+    );
+
+    let json = cli::plan_from_ron(PROFILE, &now, Format::Json)?;
+    assert!(json.starts_with("[\n"));
+    assert!(json.contains(r#"{"vaccine": "Tdap", "kind": "Dose#2", "date": "2025-09-01"}"#));
+    assert_eq!(4, json.matches("\"vaccine\"").count());
+
+    let ics = cli::plan_from_ron(PROFILE, &now, Format::Ics)?;
+    assert_eq!(4, ics.matches("BEGIN:VEVENT").count());
+    assert!(ics.contains("DESCRIPTION:Prefers the left arm\r\n"));
+    Ok(())
+}
+
+#[test]
+fn test_plan_profile_with_unknown_vaccine() -> Result<()> {
+    let now = Date::new(2025, 6, 1)?.to_zoned(TimeZone::UTC)?;
+    let profile = r#"(
+        vaccines: [(name: "Smallpox", enabled: true), (name: "Mpox", enabled: true)],
+        end_plan_year: 2026,
+    )"#;
+    assert_eq!(
+        "2025-06: Mpox Dose#1\n2025-07: Mpox Dose#2\n",
+        cli::plan_from_ron(profile, &now, Format::Text)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_run_arguments() -> Result<()> {
+    let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/profile.ron");
+    assert!(cli::run(args(&[path, "--format", "ron"]).into_iter())?.contains("vaccine: \"Tdap\""));
+    assert!(cli::run(args(&[]).into_iter()).is_err());
+    assert!(cli::run(args(&[path, "--format", "pdf"]).into_iter()).is_err());
+    assert!(cli::run(args(&[path, "extra"]).into_iter()).is_err());
+    Ok(())
+}